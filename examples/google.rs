@@ -29,11 +29,13 @@ use std::process::exit;
 
 use failure::Fail;
 use oauth2::prelude::*;
-use oauth2::{AuthorizationCode, ClientId, ClientSecret, CsrfToken, RedirectUrl, Scope};
+use oauth2::{ClientId, ClientSecret, CsrfToken, RedirectUrl, Scope};
 use url::Url;
 
-use openidconnect::core::{CoreClient, CoreIdTokenClaims, CoreIdTokenVerifier};
-use openidconnect::{AuthenticationFlow, IssuerUrl, Nonce};
+use openidconnect::core::{
+    CoreAuthorizationResponse, CoreClient, CoreIdTokenClaims, CoreIdTokenVerifier,
+};
+use openidconnect::{AuthenticationFlow, CurlHttpClient, IssuerUrl, Nonce};
 
 fn handle_error<T: Fail>(fail: &T, msg: &'static str) {
     let mut err_msg = format!("ERROR: {}", msg);
@@ -60,19 +62,23 @@ fn main() {
         IssuerUrl::new("https://accounts.google.com".to_string()).expect("Invalid issuer URL");
 
     // Set up the config for the Google OAuth2 process.
-    let client = CoreClient::discover(google_client_id, Some(google_client_secret), &issuer_url)
-        .unwrap_or_else(|err| {
-            handle_error(&err, "Failed to discover OpenID Provider");
-            unreachable!();
-        })
-        // This example is requesting access to the "calendar" features and the user's profile.
-        .add_scope(Scope::new("email".to_string()))
-        .add_scope(Scope::new("profile".to_string()))
-        // This example will be running its own server at localhost:8080.
-        // See below for the server implementation.
-        .set_redirect_uri(RedirectUrl::new(
-            Url::parse("http://localhost:8080").expect("Invalid redirect URL"),
-        ));
+    let client = CoreClient::discover(
+        google_client_id,
+        Some(google_client_secret),
+        &issuer_url,
+        &CurlHttpClient,
+    ).unwrap_or_else(|err| {
+        handle_error(&err, "Failed to discover OpenID Provider");
+        unreachable!();
+    })
+    // This example is requesting access to the "calendar" features and the user's profile.
+    .add_scope(Scope::new("email".to_string()))
+    .add_scope(Scope::new("profile".to_string()))
+    // This example will be running its own server at localhost:8080.
+    // See below for the server implementation.
+    .set_redirect_uri(RedirectUrl::new(
+        Url::parse("http://localhost:8080").expect("Invalid redirect URL"),
+    ));
 
     // Generate the authorization URL to which we'll redirect the user.
     let (authorize_url, csrf_state, nonce) = client.authorize_url(
@@ -90,8 +96,7 @@ fn main() {
     let listener = TcpListener::bind("127.0.0.1:8080").unwrap();
     for stream in listener.incoming() {
         if let Ok(mut stream) = stream {
-            let code;
-            let state;
+            let authorization_response;
             {
                 let mut reader = BufReader::new(&stream);
 
@@ -101,25 +106,10 @@ fn main() {
                 let redirect_url = request_line.split_whitespace().nth(1).unwrap();
                 let url = Url::parse(&("http://localhost".to_string() + redirect_url)).unwrap();
 
-                let code_pair = url
-                    .query_pairs()
-                    .find(|pair| {
-                        let &(ref key, _) = pair;
-                        key == "code"
-                    }).unwrap();
-
-                let (_, value) = code_pair;
-                code = AuthorizationCode::new(value.into_owned());
-
-                let state_pair = url
-                    .query_pairs()
-                    .find(|pair| {
-                        let &(ref key, _) = pair;
-                        key == "state"
-                    }).unwrap();
-
-                let (_, value) = state_pair;
-                state = CsrfToken::new(value.into_owned());
+                // Parse the code from the redirect URL, verifying that the returned state matches
+                // the CSRF token we generated above.
+                authorization_response =
+                    CoreAuthorizationResponse::from_redirect_query(&url, &csrf_state);
             }
 
             let message = "Go back to your terminal :)";
@@ -130,18 +120,22 @@ fn main() {
             );
             stream.write_all(response.as_bytes()).unwrap();
 
+            let code = authorization_response
+                .unwrap_or_else(|err| {
+                    handle_error(&err, "Failed to parse authorization response");
+                    unreachable!();
+                }).code()
+                .cloned()
+                .expect("Missing authorization code");
             println!("Google returned the following code:\n{}\n", code.secret());
-            println!(
-                "Google returned the following state:\n{} (expected `{}`)\n",
-                state.secret(),
-                csrf_state.secret()
-            );
 
             // Exchange the code with a token.
-            let token_response = client.exchange_code(code).unwrap_or_else(|err| {
-                handle_error(&err, "Failed to access token endpoint");
-                unreachable!();
-            });
+            let token_response = client
+                .exchange_code(code, &CurlHttpClient)
+                .unwrap_or_else(|err| {
+                    handle_error(&err, "Failed to access token endpoint");
+                    unreachable!();
+                });
 
             println!(
                 "Google returned access token:\n{}\n",
@@ -150,7 +144,7 @@ fn main() {
             println!("Google returned scopes: {:?}", token_response.scopes());
 
             let id_token_verifier: CoreIdTokenVerifier =
-                client.id_token_verifier().unwrap_or_else(|err| {
+                client.id_token_verifier(&CurlHttpClient).unwrap_or_else(|err| {
                     handle_error(&err, "Failed to create ID token verifier");
                     unreachable!();
                 });
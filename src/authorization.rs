@@ -0,0 +1,379 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Error as FormatterError, Formatter};
+use std::marker::PhantomData;
+
+use oauth2::prelude::*;
+use oauth2::{AccessToken, AuthorizationCode, CsrfToken, ErrorResponseType};
+use serde_json::{self, Value};
+use url::{form_urlencoded, Url};
+
+use super::{
+    AdditionalClaims, GenderClaim, IdToken, IssuerUrl, JsonWebKeyType,
+    JweContentEncryptionAlgorithm, JwsSigningAlgorithm,
+};
+
+///
+/// Successful response returned from the authorization endpoint to the client's redirect URI
+/// (see [Section 3.1.2.5](
+///     https://openid.net/specs/openid-connect-core-1_0.html#AuthResponse) and
+/// [Section 3.2.2.5](
+///     https://openid.net/specs/openid-connect-core-1_0.html#ImplicitAuthResponse) of OpenID
+/// Connect Core 1.0).
+///
+/// Depending on the authentication flow, the response contains an authorization code, an ID
+/// token, an access token, or some combination of these. Any ID token must still be verified
+/// using an [`IdTokenVerifier`](struct.IdTokenVerifier.html).
+///
+/// Error responses are returned as an
+/// [`AuthorizationResponseError::ServerResponse`](enum.AuthorizationResponseError.html), with
+/// error codes of type `AE` (e.g., `CoreAuthErrorResponseType`).
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuthorizationResponse<AC, AE, GC, JE, JS, JT>
+where
+    AC: AdditionalClaims,
+    AE: AuthErrorResponseType,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    access_token: Option<AccessToken>,
+    code: Option<AuthorizationCode>,
+    id_token: Option<IdToken<AC, GC, JE, JS, JT>>,
+    issuer: Option<IssuerUrl>,
+    state: CsrfToken,
+    _phantom_ae: PhantomData<AE>,
+}
+impl<AC, AE, GC, JE, JS, JT> AuthorizationResponse<AC, AE, GC, JE, JS, JT>
+where
+    AC: AdditionalClaims,
+    AE: AuthErrorResponseType,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    ///
+    /// Parses an authorization response encoded in the query string of the redirect URL, as
+    /// returned by the `query` response mode (the default for the Authorization Code Flow).
+    ///
+    /// The `state` parameter must match `expected_state`, which is the `CsrfToken` returned by
+    /// `Client::authorize_url()`.
+    ///
+    pub fn from_redirect_query(
+        redirect_url: &Url,
+        expected_state: &CsrfToken,
+    ) -> Result<Self, AuthorizationResponseError<AE>> {
+        Self::from_params(redirect_url.query_pairs(), expected_state)
+    }
+
+    ///
+    /// Parses an authorization response encoded in the fragment of the redirect URL, as returned
+    /// by the `fragment` response mode (the default for the Implicit and Hybrid Flows).
+    ///
+    /// The `state` parameter must match `expected_state`, which is the `CsrfToken` returned by
+    /// `Client::authorize_url()`.
+    ///
+    pub fn from_redirect_fragment(
+        redirect_url: &Url,
+        expected_state: &CsrfToken,
+    ) -> Result<Self, AuthorizationResponseError<AE>> {
+        Self::from_params(
+            form_urlencoded::parse(redirect_url.fragment().unwrap_or("").as_bytes()),
+            expected_state,
+        )
+    }
+
+    ///
+    /// Parses an authorization response from the `application/x-www-form-urlencoded` body of the
+    /// `POST` request sent to the redirect URI by the `form_post` response mode (see
+    /// [OAuth 2.0 Form Post Response Mode](
+    ///     http://openid.net/specs/oauth-v2-form-post-response-mode-1_0.html)).
+    ///
+    /// The `state` parameter must match `expected_state`, which is the `CsrfToken` returned by
+    /// `Client::authorize_url()`.
+    ///
+    pub fn from_form_post(
+        body: &[u8],
+        expected_state: &CsrfToken,
+    ) -> Result<Self, AuthorizationResponseError<AE>> {
+        Self::from_params(form_urlencoded::parse(body), expected_state)
+    }
+
+    fn from_params(
+        params: form_urlencoded::Parse,
+        expected_state: &CsrfToken,
+    ) -> Result<Self, AuthorizationResponseError<AE>> {
+        let mut params_map = HashMap::new();
+        for (key, value) in params {
+            // Parameters must not be included more than once (see Section 3.1 of RFC 6749).
+            if params_map
+                .insert(key.to_string(), value.into_owned())
+                .is_some()
+            {
+                return Err(AuthorizationResponseError::Parse(format!(
+                    "duplicate `{}` parameter",
+                    key
+                )));
+            }
+        }
+
+        // The state is checked before any error response is returned so that an attacker can't
+        // inject error responses into another user's session.
+        let state = params_map
+            .remove("state")
+            .map(CsrfToken::new)
+            .ok_or_else(|| {
+                AuthorizationResponseError::InvalidState("missing `state` parameter".to_string())
+            })?;
+        if state.secret() != expected_state.secret() {
+            return Err(AuthorizationResponseError::InvalidState(
+                "`state` parameter does not match the expected value".to_string(),
+            ));
+        }
+
+        if let Some(error) = params_map.remove("error") {
+            let error = serde_json::from_value(Value::String(error)).map_err(|err| {
+                AuthorizationResponseError::Parse(format!("invalid `error`: {}", err))
+            })?;
+            return Err(AuthorizationResponseError::ServerResponse(
+                AuthorizationErrorResponse {
+                    error,
+                    error_description: params_map.remove("error_description"),
+                    error_uri: params_map.remove("error_uri"),
+                },
+            ));
+        }
+
+        let id_token = if let Some(id_token) = params_map.remove("id_token") {
+            Some(
+                serde_json::from_value(Value::String(id_token)).map_err(|err| {
+                    AuthorizationResponseError::Parse(format!("invalid `id_token`: {}", err))
+                })?,
+            )
+        } else {
+            None
+        };
+        let issuer = if let Some(issuer) = params_map.remove("iss") {
+            Some(IssuerUrl::new(issuer).map_err(|err| {
+                AuthorizationResponseError::Parse(format!("invalid `iss`: {}", err))
+            })?)
+        } else {
+            None
+        };
+        let response = AuthorizationResponse {
+            access_token: params_map.remove("access_token").map(AccessToken::new),
+            code: params_map.remove("code").map(AuthorizationCode::new),
+            id_token,
+            issuer,
+            state,
+            _phantom_ae: PhantomData,
+        };
+        if response.access_token.is_none() && response.code.is_none() && response.id_token.is_none()
+        {
+            return Err(AuthorizationResponseError::Parse(
+                "response contains no `code`, `id_token` or `access_token`".to_string(),
+            ));
+        }
+        Ok(response)
+    }
+
+    pub fn access_token(&self) -> Option<&AccessToken> {
+        self.access_token.as_ref()
+    }
+    pub fn code(&self) -> Option<&AuthorizationCode> {
+        self.code.as_ref()
+    }
+    pub fn id_token(&self) -> Option<&IdToken<AC, GC, JE, JS, JT>> {
+        self.id_token.as_ref()
+    }
+    ///
+    /// Returns the `iss` parameter, which providers supporting
+    /// [RFC 9207](https://tools.ietf.org/html/rfc9207) include to identify the issuer of the
+    /// response.
+    ///
+    pub fn issuer(&self) -> Option<&IssuerUrl> {
+        self.issuer.as_ref()
+    }
+    pub fn state(&self) -> &CsrfToken {
+        &self.state
+    }
+}
+
+///
+/// Error code returned from the authorization endpoint.
+///
+pub trait AuthErrorResponseType: 'static + Clone + ErrorResponseType + Send + Sync {}
+
+///
+/// Error response returned from the authorization endpoint to the client's redirect URI (see
+/// [Section 3.1.2.6 of OpenID Connect Core 1.0](
+///     https://openid.net/specs/openid-connect-core-1_0.html#AuthError)).
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuthorizationErrorResponse<AE: AuthErrorResponseType> {
+    error: AE,
+    error_description: Option<String>,
+    error_uri: Option<String>,
+}
+impl<AE: AuthErrorResponseType> AuthorizationErrorResponse<AE> {
+    pub fn error(&self) -> &AE {
+        &self.error
+    }
+    pub fn error_description(&self) -> Option<&String> {
+        self.error_description.as_ref()
+    }
+    pub fn error_uri(&self) -> Option<&String> {
+        self.error_uri.as_ref()
+    }
+}
+impl<AE: AuthErrorResponseType> Display for AuthorizationErrorResponse<AE> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        write!(f, "{}", self.error)?;
+        if let Some(ref error_description) = self.error_description {
+            write!(f, ": {}", error_description)?;
+        }
+        if let Some(ref error_uri) = self.error_uri {
+            write!(f, " / See {}", error_uri)?;
+        }
+        Ok(())
+    }
+}
+
+///
+/// Error encountered while parsing an authorization response.
+///
+#[derive(Debug, Fail)]
+pub enum AuthorizationResponseError<AE: AuthErrorResponseType> {
+    ///
+    /// Error response returned by the authorization server.
+    ///
+    #[fail(display = "Server returned error response `{}`", _0)]
+    ServerResponse(AuthorizationErrorResponse<AE>),
+    ///
+    /// The `state` parameter is missing or doesn't match the expected value, which may indicate
+    /// a CSRF attack.
+    ///
+    #[fail(display = "Invalid state: {}", _0)]
+    InvalidState(String),
+    ///
+    /// Failed to parse the authorization response.
+    ///
+    #[fail(display = "Failed to parse authorization response: {}", _0)]
+    Parse(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use oauth2::prelude::*;
+    use oauth2::CsrfToken;
+    use serde_json;
+    use url::Url;
+
+    use super::super::core::{CoreAuthErrorResponseType, CoreAuthorizationResponse};
+    use super::super::id_token::tests::new_signed_id_token;
+    use super::AuthorizationResponseError;
+
+    #[test]
+    fn test_authorization_response() {
+        let expected_state = CsrfToken::new("CSRF123".to_string());
+
+        let response = CoreAuthorizationResponse::from_redirect_query(
+            &Url::parse(
+                "http://localhost:8888/?code=CODE456&state=CSRF123&iss=https%3A%2F%2Fexample",
+            ).unwrap(),
+            &expected_state,
+        ).expect("failed to parse response");
+        assert_eq!("CODE456", response.code().unwrap().secret());
+        assert_eq!("CSRF123", response.state().secret());
+        assert_eq!("https://example", response.issuer().unwrap().as_str());
+        assert!(response.access_token().is_none());
+        assert!(response.id_token().is_none());
+
+        let id_token = new_signed_id_token("{}");
+        let raw_id_token = serde_json::to_value(&id_token).unwrap();
+        let fragment = format!(
+            "access_token=ACCESS789&token_type=bearer&state=CSRF123&id_token={}",
+            raw_id_token.as_str().unwrap()
+        );
+        let mut redirect_url = Url::parse("http://localhost:8888/").unwrap();
+        redirect_url.set_fragment(Some(&fragment));
+        let response =
+            CoreAuthorizationResponse::from_redirect_fragment(&redirect_url, &expected_state)
+                .expect("failed to parse response");
+        assert_eq!("ACCESS789", response.access_token().unwrap().secret());
+        assert_eq!(Some(&id_token), response.id_token());
+        assert!(response.code().is_none());
+
+        let response = CoreAuthorizationResponse::from_form_post(
+            format!("code=CODE456&{}", fragment).as_bytes(),
+            &expected_state,
+        ).expect("failed to parse response");
+        assert_eq!("CODE456", response.code().unwrap().secret());
+        assert_eq!("ACCESS789", response.access_token().unwrap().secret());
+        assert_eq!(Some(&id_token), response.id_token());
+    }
+
+    #[test]
+    fn test_authorization_response_errors() {
+        let expected_state = CsrfToken::new("CSRF123".to_string());
+        let parse_query = |query: &str| {
+            CoreAuthorizationResponse::from_redirect_query(
+                &Url::parse(&format!("http://localhost:8888/?{}", query)).unwrap(),
+                &expected_state,
+            )
+        };
+
+        match parse_query("error=access_denied&error_description=The+user+declined&state=CSRF123") {
+            Err(AuthorizationResponseError::ServerResponse(ref error_response)) => {
+                assert_eq!(
+                    &CoreAuthErrorResponseType::AccessDenied,
+                    error_response.error()
+                );
+                assert_eq!(
+                    Some(&"The user declined".to_string()),
+                    error_response.error_description()
+                );
+                assert_eq!(None, error_response.error_uri());
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        match parse_query("error=login_required&state=CSRF123") {
+            Err(AuthorizationResponseError::ServerResponse(ref error_response))
+                if error_response.error().requires_interaction() => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // The state is validated before any error response.
+        match parse_query("error=access_denied&state=CSRF999") {
+            Err(AuthorizationResponseError::InvalidState(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match parse_query("code=CODE456") {
+            Err(AuthorizationResponseError::InvalidState(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match parse_query("code=CODE456&state=CSRF123&state=CSRF999") {
+            Err(AuthorizationResponseError::Parse(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match parse_query("state=CSRF123") {
+            Err(AuthorizationResponseError::Parse(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match parse_query("id_token=not_a_jwt&state=CSRF123") {
+            Err(AuthorizationResponseError::Parse(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        // The query string is ignored when parsing the fragment.
+        match CoreAuthorizationResponse::from_redirect_fragment(
+            &Url::parse("http://localhost:8888/?code=CODE456&state=CSRF123").unwrap(),
+            &expected_state,
+        ) {
+            Err(AuthorizationResponseError::InvalidState(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,270 @@
+use std::collections::BTreeMap;
+
+use oauth2::prelude::*;
+use serde_json::Value;
+
+use super::{AuthContextPolicy, AuthenticationContextClass, LanguageTag};
+
+///
+/// Claims requested from the OpenID Provider via the `claims` authorization request parameter
+/// (see [Section 5.5 of OpenID Connect Core 1.0](
+///     https://openid.net/specs/openid-connect-core-1_0.html#ClaimsParameter)).
+///
+/// Claims may be requested individually for the ID token (the `id_token` member) and for the
+/// UserInfo response (the `userinfo` member). The request is serialized as a JSON object, so it
+/// may be passed to [`Client::set_claims_request`](struct.Client.html#method.set_claims_request)
+/// or embedded in a request object.
+///
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ClaimsRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    userinfo: Option<BTreeMap<String, Option<IndividualClaimRequest>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id_token: Option<BTreeMap<String, Option<IndividualClaimRequest>>>,
+}
+impl ClaimsRequest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Requests the given claim in the ID token, optionally in the given language (e.g.,
+    /// `family_name#ja-Kana-JP`).
+    ///
+    /// If `request` is `None`, the claim is requested in the default manner, as a voluntary
+    /// claim.
+    ///
+    pub fn add_id_token_claim(
+        mut self,
+        name: &str,
+        language_tag: Option<&LanguageTag>,
+        request: Option<IndividualClaimRequest>,
+    ) -> Self {
+        self.id_token
+            .get_or_insert_with(BTreeMap::new)
+            .insert(claim_key(name, language_tag), request);
+        self
+    }
+
+    ///
+    /// Requests the given claim from the UserInfo endpoint, optionally in the given language
+    /// (e.g., `family_name#ja-Kana-JP`).
+    ///
+    /// If `request` is `None`, the claim is requested in the default manner, as a voluntary
+    /// claim.
+    ///
+    pub fn add_userinfo_claim(
+        mut self,
+        name: &str,
+        language_tag: Option<&LanguageTag>,
+        request: Option<IndividualClaimRequest>,
+    ) -> Self {
+        self.userinfo
+            .get_or_insert_with(BTreeMap::new)
+            .insert(claim_key(name, language_tag), request);
+        self
+    }
+
+    ///
+    /// Returns the claims requested in the ID token, keyed by claim name (including any
+    /// language tag).
+    ///
+    pub fn id_token_claims(&self) -> Option<&BTreeMap<String, Option<IndividualClaimRequest>>> {
+        self.id_token.as_ref()
+    }
+
+    ///
+    /// Returns the claims requested from the UserInfo endpoint, keyed by claim name (including
+    /// any language tag).
+    ///
+    pub fn userinfo_claims(&self) -> Option<&BTreeMap<String, Option<IndividualClaimRequest>>> {
+        self.userinfo.as_ref()
+    }
+
+    // Returns the policy for verifying the `acr` claim of ID tokens issued in response to this
+    // request, if it requests specific `acr` values.
+    pub(crate) fn auth_context_policy(&self) -> Option<AuthContextPolicy> {
+        let acr_request = match self.id_token.as_ref().and_then(|claims| claims.get("acr")) {
+            Some(&Some(ref acr_request)) => acr_request,
+            _ => return None,
+        };
+        let acr_values = acr_request
+            .value
+            .iter()
+            .chain(acr_request.values.iter().flat_map(|values| values.iter()))
+            .filter_map(|value| value.as_str())
+            .map(|value| AuthenticationContextClass::new(value.to_string()))
+            .collect::<Vec<_>>();
+        if acr_values.is_empty() {
+            None
+        } else if acr_request.essential == Some(true) {
+            Some(AuthContextPolicy::Essential(acr_values))
+        } else {
+            Some(AuthContextPolicy::AnyOf(acr_values))
+        }
+    }
+}
+
+fn claim_key(name: &str, language_tag: Option<&LanguageTag>) -> String {
+    if let Some(language_tag) = language_tag {
+        format!("{}#{}", name, language_tag.as_str())
+    } else {
+        name.to_string()
+    }
+}
+
+///
+/// Request for an individual claim, specifying whether it's essential and any requested values.
+///
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct IndividualClaimRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    essential: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    values: Option<Vec<Value>>,
+}
+impl IndividualClaimRequest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn essential(&self) -> Option<bool> {
+        self.essential
+    }
+    ///
+    /// Sets whether the claim is essential for the authorization to succeed for the specific
+    /// task requested by the End-User.
+    ///
+    pub fn set_essential(mut self, essential: Option<bool>) -> Self {
+        self.essential = essential;
+        self
+    }
+
+    pub fn value(&self) -> Option<&Value> {
+        self.value.as_ref()
+    }
+    ///
+    /// Requests that the claim be returned with the given value.
+    ///
+    pub fn set_value(mut self, value: Option<Value>) -> Self {
+        self.value = value;
+        self
+    }
+
+    pub fn values(&self) -> Option<&Vec<Value>> {
+        self.values.as_ref()
+    }
+    ///
+    /// Requests that the claim be returned with one of the given values, in order of preference.
+    ///
+    pub fn set_values(mut self, values: Option<Vec<Value>>) -> Self {
+        self.values = values;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use oauth2::prelude::*;
+    use serde_json::{self, Value};
+
+    use super::super::{AuthContextPolicy, AuthenticationContextClass, LanguageTag};
+    use super::{ClaimsRequest, IndividualClaimRequest};
+
+    #[test]
+    fn test_claims_request() {
+        // Example from Section 5.5 of OpenID Connect Core 1.0.
+        let claims_request = ClaimsRequest::new()
+            .add_userinfo_claim(
+                "given_name",
+                None,
+                Some(IndividualClaimRequest::new().set_essential(Some(true))),
+            ).add_userinfo_claim("nickname", None, None)
+            .add_userinfo_claim(
+                "email",
+                None,
+                Some(IndividualClaimRequest::new().set_essential(Some(true))),
+            ).add_userinfo_claim(
+                "family_name",
+                Some(&LanguageTag::new("ja-Kana-JP".to_string())),
+                None,
+            ).add_id_token_claim(
+                "auth_time",
+                None,
+                Some(IndividualClaimRequest::new().set_essential(Some(true))),
+            ).add_id_token_claim(
+                "acr",
+                None,
+                Some(IndividualClaimRequest::new().set_values(Some(vec![
+                    Value::String("urn:mace:incommon:iap:silver".to_string()),
+                ]))),
+            );
+
+        let serialized = serde_json::to_string(&claims_request).unwrap();
+        assert_eq!(
+            "{\"userinfo\":{\"email\":{\"essential\":true},\"family_name#ja-Kana-JP\":null,\
+             \"given_name\":{\"essential\":true},\"nickname\":null},\
+             \"id_token\":{\"acr\":{\"values\":[\"urn:mace:incommon:iap:silver\"]},\
+             \"auth_time\":{\"essential\":true}}}",
+            serialized
+        );
+        assert_eq!(
+            claims_request,
+            serde_json::from_str::<ClaimsRequest>(&serialized).unwrap()
+        );
+        assert_eq!(
+            Some(&None),
+            claims_request.userinfo_claims().unwrap().get("nickname")
+        );
+        assert_eq!(
+            Some(AuthContextPolicy::AnyOf(vec![AuthenticationContextClass::new(
+                "urn:mace:incommon:iap:silver".to_string()
+            )])),
+            claims_request.auth_context_policy()
+        );
+    }
+
+    #[test]
+    fn test_claims_request_auth_context_policy() {
+        assert_eq!(None, ClaimsRequest::new().auth_context_policy());
+        assert_eq!(
+            None,
+            ClaimsRequest::new()
+                .add_id_token_claim(
+                    "acr",
+                    None,
+                    Some(IndividualClaimRequest::new().set_essential(Some(true))),
+                ).auth_context_policy()
+        );
+        assert_eq!(
+            Some(AuthContextPolicy::Essential(vec![
+                AuthenticationContextClass::new("gold".to_string()),
+            ])),
+            ClaimsRequest::new()
+                .add_id_token_claim(
+                    "acr",
+                    None,
+                    Some(
+                        IndividualClaimRequest::new()
+                            .set_essential(Some(true))
+                            .set_value(Some(Value::String("gold".to_string()))),
+                    ),
+                ).auth_context_policy()
+        );
+        // Only the ID token's `acr` claim is enforced by the ID token verifier.
+        assert_eq!(
+            None,
+            ClaimsRequest::new()
+                .add_userinfo_claim(
+                    "acr",
+                    None,
+                    Some(
+                        IndividualClaimRequest::new()
+                            .set_value(Some(Value::String("gold".to_string()))),
+                    ),
+                ).auth_context_policy()
+        );
+    }
+}
@@ -1,10 +1,25 @@
+use std::sync::Arc;
+
+use aes::cipher::block_padding::Pkcs7;
+use aes::cipher::generic_array::GenericArray;
+use aes::cipher::{BlockDecryptMut, BlockEncryptMut, BlockSizeUser, KeyInit, KeyIvInit};
+use aes::{Aes128, Aes192, Aes256};
+use aes_gcm::aead::consts::U12;
+use aes_gcm::aead::AeadInPlace;
+use aes_gcm::AesGcm;
+use aes_kw::Kek;
+use ring::constant_time;
 use ring::digest;
 use ring::hmac;
+use ring::rand::{SecureRandom, SystemRandom};
 use ring::signature as ring_signature;
 use untrusted::Input;
 
-use super::super::{JsonWebKey, SignatureVerificationError};
-use super::{CoreJsonWebKey, CoreJsonWebKeyType};
+use super::super::{
+    DecryptionError, EncryptedContent, EncryptionError, JsonWebEncryption, JsonWebKey,
+    SignatureVerificationError, SigningError,
+};
+use super::{CoreJsonCurveType, CoreJsonWebKey, CoreJsonWebKeyType};
 
 pub fn verify_hmac(
     key: &CoreJsonWebKey,
@@ -54,3 +69,641 @@ pub fn verify_rsa_signature(
         ))
     }
 }
+
+pub fn verify_ec_signature(
+    key: &CoreJsonWebKey,
+    curve: &CoreJsonCurveType,
+    alg: &'static ring_signature::VerificationAlgorithm,
+    msg: &str,
+    signature: &[u8],
+) -> Result<(), SignatureVerificationError> {
+    if *key.key_type() != CoreJsonWebKeyType::EllipticCurve {
+        return Err(SignatureVerificationError::InvalidKey(
+            "elliptic curve key required".to_string(),
+        ));
+    }
+
+    match key.crv {
+        Some(ref key_curve) if key_curve == curve => {}
+        Some(ref key_curve) => {
+            return Err(SignatureVerificationError::InvalidKey(format!(
+                "key curve {:?} does not match signature algorithm",
+                key_curve
+            )))
+        }
+        None => {
+            return Err(SignatureVerificationError::InvalidKey(
+                "elliptic curve `crv` is missing or unsupported".to_string(),
+            ))
+        }
+    }
+
+    if let Some(x) = key.x.as_ref() {
+        if let Some(y) = key.y.as_ref() {
+            // ring expects the public key as an uncompressed point (see Section 2.3.3 of SEC 1).
+            let mut public_key = Vec::with_capacity(1 + x.len() + y.len());
+            public_key.push(0x04);
+            public_key.extend_from_slice(x);
+            public_key.extend_from_slice(y);
+
+            ring_signature::verify(
+                alg,
+                Input::from(&public_key),
+                Input::from(msg.as_bytes()),
+                Input::from(signature),
+            ).map_err(|_| SignatureVerificationError::CryptoError("bad signature".to_string()))
+        } else {
+            Err(SignatureVerificationError::InvalidKey(
+                "elliptic curve `y` coordinate is missing".to_string(),
+            ))
+        }
+    } else {
+        Err(SignatureVerificationError::InvalidKey(
+            "elliptic curve `x` coordinate is missing".to_string(),
+        ))
+    }
+}
+
+pub fn verify_eddsa_signature(
+    key: &CoreJsonWebKey,
+    msg: &str,
+    signature: &[u8],
+) -> Result<(), SignatureVerificationError> {
+    if *key.key_type() != CoreJsonWebKeyType::OctetKeyPair {
+        return Err(SignatureVerificationError::InvalidKey(
+            "octet key pair required".to_string(),
+        ));
+    }
+
+    match key.crv {
+        Some(CoreJsonCurveType::Ed25519) => {}
+        Some(ref key_curve) => {
+            return Err(SignatureVerificationError::InvalidKey(format!(
+                "key curve {:?} does not match signature algorithm",
+                key_curve
+            )))
+        }
+        None => {
+            return Err(SignatureVerificationError::InvalidKey(
+                "octet key pair `crv` is missing or unsupported".to_string(),
+            ))
+        }
+    }
+
+    if let Some(x) = key.x.as_ref() {
+        ring_signature::verify(
+            &ring_signature::ED25519,
+            Input::from(x),
+            Input::from(msg.as_bytes()),
+            Input::from(signature),
+        ).map_err(|_| SignatureVerificationError::CryptoError("bad signature".to_string()))
+    } else {
+        Err(SignatureVerificationError::InvalidKey(
+            "octet key pair public key `x` is missing".to_string(),
+        ))
+    }
+}
+
+pub fn sign_hmac(
+    key: &CoreJsonWebKey,
+    digest_alg: &'static digest::Algorithm,
+    msg: &[u8],
+) -> Result<Vec<u8>, SigningError> {
+    if let Some(k) = key.k.as_ref() {
+        let signing_key = hmac::SigningKey::new(digest_alg, k);
+        Ok(hmac::sign(&signing_key, msg).as_ref().to_vec())
+    } else {
+        Err(SigningError::InvalidKey(
+            "Symmetric key `k` is missing".to_string(),
+        ))
+    }
+}
+
+pub fn sign_rsa(
+    key: &CoreJsonWebKey,
+    padding_alg: &'static ring_signature::RSAEncoding,
+    msg: &[u8],
+) -> Result<Vec<u8>, SigningError> {
+    if *key.key_type() != CoreJsonWebKeyType::RSA {
+        return Err(SigningError::InvalidKey("RSA key required".to_string()));
+    }
+
+    let private_key_der = rsa_private_key_der(key)?;
+    let key_pair = ring_signature::RSAKeyPair::from_der(Input::from(&private_key_der))
+        .map_err(|_| SigningError::InvalidKey("invalid RSA private key".to_string()))?;
+    let mut signing_state = ring_signature::RSASigningState::new(Arc::new(key_pair))
+        .map_err(|_| SigningError::CryptoError("failed to initialize RSA signer".to_string()))?;
+
+    let mut signature = vec![0; signing_state.key_pair().public_modulus_len()];
+    signing_state
+        .sign(padding_alg, &SystemRandom::new(), msg, &mut signature)
+        .map_err(|_| SigningError::CryptoError("failed to compute RSA signature".to_string()))?;
+    Ok(signature)
+}
+
+pub fn sign_ec(
+    key: &CoreJsonWebKey,
+    curve: &CoreJsonCurveType,
+    msg: &[u8],
+) -> Result<Vec<u8>, SigningError> {
+    if *key.key_type() != CoreJsonWebKeyType::EllipticCurve {
+        return Err(SigningError::InvalidKey(
+            "elliptic curve key required".to_string(),
+        ));
+    }
+    if key.crv.as_ref() != Some(curve) {
+        return Err(SigningError::InvalidKey(
+            "key curve does not match signature algorithm".to_string(),
+        ));
+    }
+
+    let d = key.d.as_ref().ok_or_else(|| {
+        SigningError::InvalidKey("elliptic curve private key `d` is missing".to_string())
+    })?;
+    let x = key.x.as_ref().ok_or_else(|| {
+        SigningError::InvalidKey("elliptic curve `x` coordinate is missing".to_string())
+    })?;
+    let y = key.y.as_ref().ok_or_else(|| {
+        SigningError::InvalidKey("elliptic curve `y` coordinate is missing".to_string())
+    })?;
+
+    let mut public_key = Vec::with_capacity(1 + x.len() + y.len());
+    public_key.push(0x04);
+    public_key.extend_from_slice(x);
+    public_key.extend_from_slice(y);
+
+    // NB: ring doesn't expose a nameable type for its ECDSA signing algorithms, so we can't pass
+    // one in as a parameter.
+    let key_pair = match *curve {
+        CoreJsonCurveType::P256 => ring_signature::ECDSAKeyPair::from_private_key_and_public_key(
+            &ring_signature::ECDSA_P256_SHA256_FIXED_SIGNING,
+            Input::from(d),
+            Input::from(&public_key),
+        ),
+        CoreJsonCurveType::P384 => ring_signature::ECDSAKeyPair::from_private_key_and_public_key(
+            &ring_signature::ECDSA_P384_SHA384_FIXED_SIGNING,
+            Input::from(d),
+            Input::from(&public_key),
+        ),
+        ref other => {
+            return Err(SigningError::UnsupportedAlg(format!(
+                "ECDSA with curve {:?}",
+                other
+            )))
+        }
+    }.map_err(|_| SigningError::InvalidKey("invalid elliptic curve private key".to_string()))?;
+
+    key_pair
+        .sign(Input::from(msg), &SystemRandom::new())
+        .map(|signature| signature.as_ref().to_vec())
+        .map_err(|_| SigningError::CryptoError("failed to compute ECDSA signature".to_string()))
+}
+
+pub fn sign_eddsa(key: &CoreJsonWebKey, msg: &[u8]) -> Result<Vec<u8>, SigningError> {
+    if *key.key_type() != CoreJsonWebKeyType::OctetKeyPair {
+        return Err(SigningError::InvalidKey(
+            "octet key pair required".to_string(),
+        ));
+    }
+    if key.crv != Some(CoreJsonCurveType::Ed25519) {
+        return Err(SigningError::InvalidKey("Ed25519 key required".to_string()));
+    }
+
+    let d = key.d.as_ref().ok_or_else(|| {
+        SigningError::InvalidKey("octet key pair private key `d` is missing".to_string())
+    })?;
+    let x = key.x.as_ref().ok_or_else(|| {
+        SigningError::InvalidKey("octet key pair public key `x` is missing".to_string())
+    })?;
+
+    let key_pair =
+        ring_signature::Ed25519KeyPair::from_seed_and_public_key(Input::from(d), Input::from(x))
+            .map_err(|_| SigningError::InvalidKey("invalid Ed25519 private key".to_string()))?;
+    Ok(key_pair.sign(msg).as_ref().to_vec())
+}
+
+// Decrypts a content encryption key of `content_key_len` bytes. To avoid acting as a padding
+// oracle, a random content key of the expected length is returned instead if decryption fails or
+// yields a key of the wrong length, so that the subsequent content decryption fails in the same
+// way as when the content itself is invalid (see Section 11.5 of RFC 7516).
+#[cfg(feature = "rsa-oaep")]
+pub fn decrypt_rsa_oaep(
+    key: &CoreJsonWebKey,
+    padding: rsa::Oaep,
+    encrypted_key: &[u8],
+    content_key_len: usize,
+) -> Result<Vec<u8>, DecryptionError> {
+    let params = [
+        ("n", key.n.as_ref()),
+        ("e", key.e.as_ref()),
+        ("d", key.d.as_ref()),
+        ("p", key.p.as_ref()),
+        ("q", key.q.as_ref()),
+    ];
+    let mut components = Vec::with_capacity(params.len());
+    for &(name, value) in &params {
+        let value = value.ok_or_else(|| {
+            DecryptionError::InvalidKey(format!("RSA private key parameter `{}` is missing", name))
+        })?;
+        components.push(rsa::BigUint::from_bytes_be(value));
+    }
+    let q = components.pop().unwrap();
+    let p = components.pop().unwrap();
+    let d = components.pop().unwrap();
+    let e = components.pop().unwrap();
+    let n = components.pop().unwrap();
+
+    let private_key = rsa::RsaPrivateKey::from_components(n, e, d, vec![p, q])
+        .map_err(|err| DecryptionError::InvalidKey(format!("invalid RSA private key: {}", err)))?;
+    // The random key is generated up front so that both outcomes take similar time.
+    let mut random_key = vec![0; content_key_len];
+    SystemRandom::new()
+        .fill(&mut random_key)
+        .map_err(|_| DecryptionError::Other("failed to generate random bytes".to_string()))?;
+
+    // Blinding protects the private key against timing side channels.
+    match private_key.decrypt_blinded(&mut rsa::rand_core::OsRng, padding, encrypted_key) {
+        Ok(ref content_key) if content_key.len() == content_key_len => Ok(content_key.clone()),
+        _ => Ok(random_key),
+    }
+}
+
+pub fn unwrap_aes_key(
+    key: &CoreJsonWebKey,
+    key_len: usize,
+    encrypted_key: &[u8],
+) -> Result<Vec<u8>, DecryptionError> {
+    let k = symmetric_key(key, key_len)?;
+
+    // AES Key Wrap (RFC 3394) adds a single 64-bit integrity check block to the wrapped key.
+    if encrypted_key.len() < 24 || encrypted_key.len() % 8 != 0 {
+        return Err(DecryptionError::CryptoError(
+            "invalid wrapped content key length".to_string(),
+        ));
+    }
+    let mut content_key = vec![0; encrypted_key.len() - 8];
+    match key_len {
+        16 => {
+            Kek::<Aes128>::new(GenericArray::from_slice(k)).unwrap(encrypted_key, &mut content_key)
+        }
+        24 => {
+            Kek::<Aes192>::new(GenericArray::from_slice(k)).unwrap(encrypted_key, &mut content_key)
+        }
+        32 => {
+            Kek::<Aes256>::new(GenericArray::from_slice(k)).unwrap(encrypted_key, &mut content_key)
+        }
+        _ => {
+            return Err(DecryptionError::CryptoError(format!(
+                "unsupported AES key length {}",
+                key_len
+            )))
+        }
+    }.map_err(|_| DecryptionError::CryptoError("failed to unwrap content key".to_string()))?;
+    Ok(content_key)
+}
+
+pub fn symmetric_key(key: &CoreJsonWebKey, key_len: usize) -> Result<&[u8], DecryptionError> {
+    symmetric_key_bytes(key, key_len).map_err(DecryptionError::InvalidKey)
+}
+
+pub fn symmetric_encryption_key(
+    key: &CoreJsonWebKey,
+    key_len: usize,
+) -> Result<&[u8], EncryptionError> {
+    symmetric_key_bytes(key, key_len).map_err(EncryptionError::InvalidKey)
+}
+
+fn symmetric_key_bytes(key: &CoreJsonWebKey, key_len: usize) -> Result<&[u8], String> {
+    if *key.key_type() != CoreJsonWebKeyType::Symmetric {
+        return Err("symmetric key required".to_string());
+    }
+
+    let k = key
+        .k
+        .as_ref()
+        .ok_or_else(|| "Symmetric key `k` is missing".to_string())?;
+    if k.len() != key_len {
+        return Err(format!("expected {}-bit symmetric key", key_len * 8));
+    }
+    Ok(k)
+}
+
+pub fn decrypt_aes_gcm(
+    content_key: &[u8],
+    jwe: &JsonWebEncryption,
+) -> Result<Vec<u8>, DecryptionError> {
+    if jwe.initialization_vector().len() != 12 {
+        return Err(DecryptionError::CryptoError(
+            "invalid initialization vector length".to_string(),
+        ));
+    }
+    if jwe.authentication_tag().len() != 16 {
+        return Err(DecryptionError::CryptoError(
+            "invalid authentication tag length".to_string(),
+        ));
+    }
+
+    let nonce = GenericArray::from_slice(jwe.initialization_vector());
+    let tag = GenericArray::from_slice(jwe.authentication_tag());
+    let mut plaintext = jwe.ciphertext().to_vec();
+    match content_key.len() {
+        16 => AesGcm::<Aes128, U12>::new(GenericArray::from_slice(content_key))
+            .decrypt_in_place_detached(
+                nonce,
+                jwe.additional_authenticated_data(),
+                &mut plaintext,
+                tag,
+            ),
+        24 => AesGcm::<Aes192, U12>::new(GenericArray::from_slice(content_key))
+            .decrypt_in_place_detached(
+                nonce,
+                jwe.additional_authenticated_data(),
+                &mut plaintext,
+                tag,
+            ),
+        32 => AesGcm::<Aes256, U12>::new(GenericArray::from_slice(content_key))
+            .decrypt_in_place_detached(
+                nonce,
+                jwe.additional_authenticated_data(),
+                &mut plaintext,
+                tag,
+            ),
+        _ => {
+            return Err(DecryptionError::InvalidKey(
+                "invalid content encryption key length".to_string(),
+            ))
+        }
+    }.map_err(|_| DecryptionError::CryptoError("failed to decrypt content".to_string()))?;
+    Ok(plaintext)
+}
+
+// See Section 5.2 of RFC 7518. The content encryption key consists of the HMAC key followed by the
+// AES-CBC key, each of which is half of the content key.
+pub fn decrypt_aes_cbc_hmac(
+    content_key: &[u8],
+    digest_alg: &'static digest::Algorithm,
+    jwe: &JsonWebEncryption,
+) -> Result<Vec<u8>, DecryptionError> {
+    let key_len = digest_alg.output_len / 2;
+    if content_key.len() != key_len * 2 {
+        return Err(DecryptionError::InvalidKey(
+            "invalid content encryption key length".to_string(),
+        ));
+    }
+    let (mac_key, enc_key) = content_key.split_at(key_len);
+
+    let tag = aes_cbc_hmac_tag(
+        mac_key,
+        digest_alg,
+        jwe.additional_authenticated_data(),
+        jwe.initialization_vector(),
+        jwe.ciphertext(),
+    );
+    constant_time::verify_slices_are_equal(&tag, jwe.authentication_tag())
+        .map_err(|_| DecryptionError::CryptoError("failed to decrypt content".to_string()))?;
+
+    if jwe.initialization_vector().len() != Aes128::block_size() {
+        return Err(DecryptionError::CryptoError(
+            "invalid initialization vector length".to_string(),
+        ));
+    }
+    let iv = jwe.initialization_vector();
+    let mut buffer = jwe.ciphertext().to_vec();
+    let plaintext_len = match key_len {
+        16 => cbc::Decryptor::<Aes128>::new_from_slices(enc_key, iv)
+            .map_err(|_| DecryptionError::InvalidKey("invalid AES key".to_string()))?
+            .decrypt_padded_mut::<Pkcs7>(&mut buffer)
+            .map(|plaintext| plaintext.len()),
+        24 => cbc::Decryptor::<Aes192>::new_from_slices(enc_key, iv)
+            .map_err(|_| DecryptionError::InvalidKey("invalid AES key".to_string()))?
+            .decrypt_padded_mut::<Pkcs7>(&mut buffer)
+            .map(|plaintext| plaintext.len()),
+        32 => cbc::Decryptor::<Aes256>::new_from_slices(enc_key, iv)
+            .map_err(|_| DecryptionError::InvalidKey("invalid AES key".to_string()))?
+            .decrypt_padded_mut::<Pkcs7>(&mut buffer)
+            .map(|plaintext| plaintext.len()),
+        _ => {
+            return Err(DecryptionError::CryptoError(format!(
+                "unsupported AES key length {}",
+                key_len
+            )))
+        }
+    }.map_err(|_| DecryptionError::CryptoError("failed to decrypt content".to_string()))?;
+    buffer.truncate(plaintext_len);
+    Ok(buffer)
+}
+
+fn aes_cbc_hmac_tag(
+    mac_key: &[u8],
+    digest_alg: &'static digest::Algorithm,
+    aad: &[u8],
+    iv: &[u8],
+    ciphertext: &[u8],
+) -> Vec<u8> {
+    let mut mac_input = Vec::with_capacity(aad.len() + iv.len() + ciphertext.len() + 8);
+    mac_input.extend_from_slice(aad);
+    mac_input.extend_from_slice(iv);
+    mac_input.extend_from_slice(ciphertext);
+    let aad_bits = (aad.len() as u64) * 8;
+    mac_input.extend((0..8).rev().map(|i| (aad_bits >> (i * 8)) as u8));
+
+    let signing_key = hmac::SigningKey::new(digest_alg, mac_key);
+    let mac = hmac::sign(&signing_key, &mac_input);
+    // The authentication tag is the first half of the HMAC output.
+    mac.as_ref()[..mac_key.len()].to_vec()
+}
+
+pub fn random_bytes(len: usize) -> Result<Vec<u8>, EncryptionError> {
+    let mut bytes = vec![0; len];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| EncryptionError::CryptoError("failed to generate random bytes".to_string()))?;
+    Ok(bytes)
+}
+
+#[cfg(feature = "rsa-oaep")]
+pub fn encrypt_rsa_oaep(
+    key: &CoreJsonWebKey,
+    padding: rsa::Oaep,
+    content_key: &[u8],
+) -> Result<Vec<u8>, EncryptionError> {
+    if *key.key_type() != CoreJsonWebKeyType::RSA {
+        return Err(EncryptionError::InvalidKey("RSA key required".to_string()));
+    }
+    let n = key.n.as_ref().ok_or_else(|| {
+        EncryptionError::InvalidKey("RSA public key parameter `n` is missing".to_string())
+    })?;
+    let e = key.e.as_ref().ok_or_else(|| {
+        EncryptionError::InvalidKey("RSA public key parameter `e` is missing".to_string())
+    })?;
+
+    let public_key = rsa::RsaPublicKey::new(
+        rsa::BigUint::from_bytes_be(n),
+        rsa::BigUint::from_bytes_be(e),
+    ).map_err(|err| EncryptionError::InvalidKey(format!("invalid RSA public key: {}", err)))?;
+    public_key
+        .encrypt(&mut rsa::rand_core::OsRng, padding, content_key)
+        .map_err(|_| EncryptionError::CryptoError("failed to encrypt content key".to_string()))
+}
+
+pub fn wrap_aes_key(
+    key: &CoreJsonWebKey,
+    key_len: usize,
+    content_key: &[u8],
+) -> Result<Vec<u8>, EncryptionError> {
+    let k = symmetric_encryption_key(key, key_len)?;
+
+    let mut wrapped_key = vec![0; content_key.len() + 8];
+    match key_len {
+        16 => Kek::<Aes128>::new(GenericArray::from_slice(k)).wrap(content_key, &mut wrapped_key),
+        24 => Kek::<Aes192>::new(GenericArray::from_slice(k)).wrap(content_key, &mut wrapped_key),
+        32 => Kek::<Aes256>::new(GenericArray::from_slice(k)).wrap(content_key, &mut wrapped_key),
+        _ => {
+            return Err(EncryptionError::CryptoError(format!(
+                "unsupported AES key length {}",
+                key_len
+            )))
+        }
+    }.map_err(|_| EncryptionError::CryptoError("failed to wrap content key".to_string()))?;
+    Ok(wrapped_key)
+}
+
+pub fn encrypt_aes_gcm(
+    content_key: &[u8],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> Result<EncryptedContent, EncryptionError> {
+    let iv = random_bytes(12)?;
+    let nonce = GenericArray::from_slice(&iv);
+    let mut ciphertext = plaintext.to_vec();
+    let tag = match content_key.len() {
+        16 => AesGcm::<Aes128, U12>::new(GenericArray::from_slice(content_key))
+            .encrypt_in_place_detached(nonce, aad, &mut ciphertext),
+        24 => AesGcm::<Aes192, U12>::new(GenericArray::from_slice(content_key))
+            .encrypt_in_place_detached(nonce, aad, &mut ciphertext),
+        32 => AesGcm::<Aes256, U12>::new(GenericArray::from_slice(content_key))
+            .encrypt_in_place_detached(nonce, aad, &mut ciphertext),
+        _ => {
+            return Err(EncryptionError::InvalidKey(
+                "invalid content encryption key length".to_string(),
+            ))
+        }
+    }.map_err(|_| EncryptionError::CryptoError("failed to encrypt content".to_string()))?;
+
+    Ok(EncryptedContent {
+        encrypted_key: Vec::new(),
+        initialization_vector: iv,
+        ciphertext,
+        authentication_tag: tag.to_vec(),
+    })
+}
+
+pub fn encrypt_aes_cbc_hmac(
+    content_key: &[u8],
+    digest_alg: &'static digest::Algorithm,
+    aad: &[u8],
+    plaintext: &[u8],
+) -> Result<EncryptedContent, EncryptionError> {
+    let key_len = digest_alg.output_len / 2;
+    if content_key.len() != key_len * 2 {
+        return Err(EncryptionError::InvalidKey(
+            "invalid content encryption key length".to_string(),
+        ));
+    }
+    let (mac_key, enc_key) = content_key.split_at(key_len);
+
+    let iv = random_bytes(Aes128::block_size())?;
+    // PKCS#7 padding adds at most one block to the plaintext.
+    let mut buffer = plaintext.to_vec();
+    buffer.resize(plaintext.len() + Aes128::block_size(), 0);
+    let ciphertext_len = match key_len {
+        16 => cbc::Encryptor::<Aes128>::new_from_slices(enc_key, &iv)
+            .map_err(|_| EncryptionError::InvalidKey("invalid AES key".to_string()))?
+            .encrypt_padded_mut::<Pkcs7>(&mut buffer, plaintext.len())
+            .map(|ciphertext| ciphertext.len()),
+        24 => cbc::Encryptor::<Aes192>::new_from_slices(enc_key, &iv)
+            .map_err(|_| EncryptionError::InvalidKey("invalid AES key".to_string()))?
+            .encrypt_padded_mut::<Pkcs7>(&mut buffer, plaintext.len())
+            .map(|ciphertext| ciphertext.len()),
+        32 => cbc::Encryptor::<Aes256>::new_from_slices(enc_key, &iv)
+            .map_err(|_| EncryptionError::InvalidKey("invalid AES key".to_string()))?
+            .encrypt_padded_mut::<Pkcs7>(&mut buffer, plaintext.len())
+            .map(|ciphertext| ciphertext.len()),
+        _ => {
+            return Err(EncryptionError::CryptoError(format!(
+                "unsupported AES key length {}",
+                key_len
+            )))
+        }
+    }.map_err(|_| EncryptionError::CryptoError("failed to encrypt content".to_string()))?;
+    buffer.truncate(ciphertext_len);
+
+    let authentication_tag = aes_cbc_hmac_tag(mac_key, digest_alg, aad, &iv, &buffer);
+    Ok(EncryptedContent {
+        encrypted_key: Vec::new(),
+        initialization_vector: iv,
+        ciphertext: buffer,
+        authentication_tag,
+    })
+}
+
+// ring only accepts RSA private keys as DER-encoded PKCS#1 `RSAPrivateKey` structures (see
+// Appendix A.1.2 of RFC 3447), so we re-encode the JWK parameters in that format.
+fn rsa_private_key_der(key: &CoreJsonWebKey) -> Result<Vec<u8>, SigningError> {
+    let params = [
+        ("n", key.n.as_ref()),
+        ("e", key.e.as_ref()),
+        ("d", key.d.as_ref()),
+        ("p", key.p.as_ref()),
+        ("q", key.q.as_ref()),
+        ("dp", key.dp.as_ref()),
+        ("dq", key.dq.as_ref()),
+        ("qi", key.qi.as_ref()),
+    ];
+
+    // Version 0 (two-prime).
+    let mut contents = vec![0x02, 0x01, 0x00];
+    for &(name, value) in &params {
+        let value = value.ok_or_else(|| {
+            SigningError::InvalidKey(format!("RSA private key parameter `{}` is missing", name))
+        })?;
+        der_encode_unsigned_integer(value, &mut contents);
+    }
+
+    let mut der = vec![0x30];
+    der_encode_length(contents.len(), &mut der);
+    der.extend(contents);
+    Ok(der)
+}
+
+fn der_encode_unsigned_integer(value: &[u8], out: &mut Vec<u8>) {
+    let first_nonzero = value.iter().position(|b| *b != 0).unwrap_or(value.len());
+    let value = &value[first_nonzero..];
+
+    out.push(0x02);
+    if value.is_empty() {
+        out.extend_from_slice(&[0x01, 0x00]);
+    } else if value[0] & 0x80 != 0 {
+        // Prepend a zero byte so that the integer isn't interpreted as negative.
+        der_encode_length(value.len() + 1, out);
+        out.push(0x00);
+        out.extend_from_slice(value);
+    } else {
+        der_encode_length(value.len(), out);
+        out.extend_from_slice(value);
+    }
+}
+
+fn der_encode_length(len: usize, out: &mut Vec<u8>) {
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let len_bytes = (0..8)
+            .rev()
+            .map(|i| ((len as u64) >> (i * 8)) as u8)
+            .skip_while(|b| *b == 0)
+            .collect::<Vec<_>>();
+        out.push(0x80 | len_bytes.len() as u8);
+        out.extend(len_bytes);
+    }
+}
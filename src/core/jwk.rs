@@ -1,3 +1,5 @@
+use std::fmt::{Debug, Formatter, Result as FormatterResult};
+
 use oauth2::helpers::variant_name;
 use oauth2::prelude::*;
 use ring::digest;
@@ -5,10 +7,16 @@ use ring::signature as ring_signature;
 
 use super::super::types::helpers::deserialize_option_or_none;
 use super::super::{
-    Base64UrlEncodedBytes, JsonWebKey, JsonWebKeyId, JsonWebKeyType, JsonWebKeyUse,
-    JwsSigningAlgorithm, SignatureVerificationError,
+    Base64UrlEncodedBytes, DecryptionError, EncryptedContent, EncryptionError, JsonWebEncryption,
+    JsonWebKey, JsonWebKeyId, JsonWebKeyType, JsonWebKeyUse, JweContentEncryptionAlgorithm,
+    JwsSigningAlgorithm, PrivateDecryptionKey, PrivateSigningKey, PublicEncryptionKey,
+    SignatureVerificationError, SigningError,
+};
+use super::key_import::{self, KeyImportError};
+use super::{
+    crypto, CoreJweContentEncryptionAlgorithm, CoreJweKeyManagementAlgorithm,
+    CoreJwsSigningAlgorithm,
 };
-use super::{crypto, CoreJwsSigningAlgorithm};
 
 // Other than the 'kty' (key type) parameter, which must be present in all JWKs, Section 4 of RFC
 // 7517 states that "member names used for representing key parameters for different keys types
@@ -17,7 +25,7 @@ use super::{crypto, CoreJwsSigningAlgorithm};
 // support such key types, we'll need to define a new impl for JsonWebKey. Deserializing the new
 // impl would probably need to involve first deserializing the raw values to access the 'kty'
 // parameter, and then deserializing the fields and types appropriate for that key type.
-#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[derive(Clone, Deserialize, PartialEq, Serialize)]
 pub struct CoreJsonWebKey {
     pub(crate) kty: CoreJsonWebKeyType,
     #[serde(rename = "use", skip_serializing_if = "Option::is_none")]
@@ -42,6 +50,67 @@ pub struct CoreJsonWebKey {
     )]
     pub(crate) e: Option<Base64UrlEncodedBytes>,
 
+    // Used for elliptic curve keys. Octet key pairs (RFC 8037) use only `crv` and `x`.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_option_or_none",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub(crate) crv: Option<CoreJsonCurveType>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_option_or_none",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub(crate) x: Option<Base64UrlEncodedBytes>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_option_or_none",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub(crate) y: Option<Base64UrlEncodedBytes>,
+
+    // Private key parameters (see Sections 6.2.2 and 6.3.2 of RFC 7518, and Section 2 of RFC 8037).
+    // The private exponent `d` is shared by RSA keys and the private key `d` by elliptic curve and
+    // octet key pairs. These are only present for keys used to sign JWTs; keys retrieved from a
+    // provider's JWK set only contain public key parameters.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_option_or_none",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub(crate) d: Option<Base64UrlEncodedBytes>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_option_or_none",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub(crate) p: Option<Base64UrlEncodedBytes>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_option_or_none",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub(crate) q: Option<Base64UrlEncodedBytes>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_option_or_none",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub(crate) dp: Option<Base64UrlEncodedBytes>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_option_or_none",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub(crate) dq: Option<Base64UrlEncodedBytes>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_option_or_none",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub(crate) qi: Option<Base64UrlEncodedBytes>,
+
     // Used for symmetric keys, which we only generate internally from the client secret; these
     // are never part of the JWK set.
     #[serde(
@@ -51,6 +120,133 @@ pub struct CoreJsonWebKey {
     )]
     pub(crate) k: Option<Base64UrlEncodedBytes>,
 }
+// Private key parameters are redacted to avoid leaking them into logs.
+impl Debug for CoreJsonWebKey {
+    fn fmt(&self, f: &mut Formatter) -> FormatterResult {
+        let redacted =
+            |param: &Option<Base64UrlEncodedBytes>| param.as_ref().map(|_| "[redacted]");
+        f.debug_struct("CoreJsonWebKey")
+            .field("kty", &self.kty)
+            .field("use_", &self.use_)
+            .field("kid", &self.kid)
+            .field("n", &self.n)
+            .field("e", &self.e)
+            .field("crv", &self.crv)
+            .field("x", &self.x)
+            .field("y", &self.y)
+            .field("d", &redacted(&self.d))
+            .field("p", &redacted(&self.p))
+            .field("q", &redacted(&self.q))
+            .field("dp", &redacted(&self.dp))
+            .field("dq", &redacted(&self.dq))
+            .field("qi", &redacted(&self.qi))
+            .field("k", &redacted(&self.k))
+            .finish()
+    }
+}
+impl CoreJsonWebKey {
+    ///
+    /// Imports an RSA or elliptic curve key from its PEM encoding.
+    ///
+    /// The following PEM labels are supported:
+    ///  * `RSA PRIVATE KEY`: PKCS#1 RSA private key
+    ///  * `RSA PUBLIC KEY`: PKCS#1 RSA public key
+    ///  * `PRIVATE KEY`: unencrypted PKCS#8 RSA or elliptic curve private key
+    ///  * `EC PRIVATE KEY`: SEC 1 elliptic curve private key
+    ///  * `PUBLIC KEY`: X.509 `SubjectPublicKeyInfo` RSA or elliptic curve public key
+    ///
+    /// Elliptic curve keys must use a named curve, and private keys must include the public key.
+    /// The imported key has no key ID or intended use; see `set_key_id` and `set_key_use`.
+    ///
+    /// # Security Warning
+    ///
+    /// Serializing an imported private key includes its private parameters. Only the key
+    /// returned by [`public_key`](#method.public_key) may be published (e.g., in the client's
+    /// `jwks` registration metadata).
+    ///
+    pub fn from_pem(pem: &str) -> Result<Self, KeyImportError> {
+        key_import::key_from_pem(pem)
+    }
+
+    ///
+    /// Imports a DER-encoded PKCS#1 RSA private key.
+    ///
+    /// # Security Warning
+    ///
+    /// As with [`from_pem`](#method.from_pem), only the key returned by
+    /// [`public_key`](#method.public_key) may be published.
+    ///
+    pub fn from_rsa_private_key_der(der: &[u8]) -> Result<Self, KeyImportError> {
+        key_import::rsa_private_key(der)
+    }
+
+    ///
+    /// Imports a DER-encoded PKCS#1 RSA public key.
+    ///
+    pub fn from_rsa_public_key_der(der: &[u8]) -> Result<Self, KeyImportError> {
+        key_import::rsa_public_key(der)
+    }
+
+    ///
+    /// Imports a DER-encoded, unencrypted PKCS#8 RSA or elliptic curve private key.
+    ///
+    /// # Security Warning
+    ///
+    /// As with [`from_pem`](#method.from_pem), only the key returned by
+    /// [`public_key`](#method.public_key) may be published.
+    ///
+    pub fn from_pkcs8_der(der: &[u8]) -> Result<Self, KeyImportError> {
+        key_import::pkcs8_private_key(der)
+    }
+
+    ///
+    /// Imports a DER-encoded SEC 1 elliptic curve private key.
+    ///
+    /// # Security Warning
+    ///
+    /// As with [`from_pem`](#method.from_pem), only the key returned by
+    /// [`public_key`](#method.public_key) may be published.
+    ///
+    pub fn from_ec_private_key_der(der: &[u8]) -> Result<Self, KeyImportError> {
+        key_import::ec_private_key(der, None)
+    }
+
+    ///
+    /// Imports a DER-encoded X.509 `SubjectPublicKeyInfo` RSA or elliptic curve public key.
+    ///
+    pub fn from_spki_der(der: &[u8]) -> Result<Self, KeyImportError> {
+        key_import::subject_public_key_info(der)
+    }
+
+    ///
+    /// Sets the key ID (`kid`), which providers use to select the key when verifying signatures.
+    ///
+    pub fn set_key_id(mut self, key_id: Option<JsonWebKeyId>) -> Self {
+        self.kid = key_id;
+        self
+    }
+
+    ///
+    /// Sets the intended use of the key (`use`).
+    ///
+    pub fn set_key_use(mut self, key_use: Option<CoreJsonWebKeyUse>) -> Self {
+        self.use_ = key_use;
+        self
+    }
+
+    ///
+    /// Returns the public half of an RSA, elliptic curve, or octet key pair key, suitable for
+    /// publishing in the client's `jwks` registration metadata. Returns `None` for symmetric keys,
+    /// which must never be published.
+    ///
+    pub fn public_key(&self) -> Option<Self> {
+        if self.kty == CoreJsonWebKeyType::Symmetric {
+            None
+        } else {
+            Some(self.as_verification_key())
+        }
+    }
+}
 impl JsonWebKey<CoreJwsSigningAlgorithm, CoreJsonWebKeyType, CoreJsonWebKeyUse> for CoreJsonWebKey {
     fn key_id(&self) -> Option<&JsonWebKeyId> {
         self.kid.as_ref()
@@ -69,6 +265,15 @@ impl JsonWebKey<CoreJwsSigningAlgorithm, CoreJsonWebKeyType, CoreJsonWebKeyUse>
             kid: None,
             n: None,
             e: None,
+            crv: None,
+            x: None,
+            y: None,
+            d: None,
+            p: None,
+            q: None,
+            dp: None,
+            dq: None,
+            qi: None,
             k: Some(Base64UrlEncodedBytes::new(key)),
         };
     }
@@ -133,6 +338,20 @@ impl JsonWebKey<CoreJwsSigningAlgorithm, CoreJsonWebKeyType, CoreJsonWebKeyUse>
                 msg,
                 signature,
             ),
+            CoreJwsSigningAlgorithm::EcdsaP256Sha256 => crypto::verify_ec_signature(
+                self,
+                &CoreJsonCurveType::P256,
+                &ring_signature::ECDSA_P256_SHA256_FIXED,
+                msg,
+                signature,
+            ),
+            CoreJwsSigningAlgorithm::EcdsaP384Sha384 => crypto::verify_ec_signature(
+                self,
+                &CoreJsonCurveType::P384,
+                &ring_signature::ECDSA_P384_SHA384_FIXED,
+                msg,
+                signature,
+            ),
             CoreJwsSigningAlgorithm::HmacSha256 => {
                 crypto::verify_hmac(self, &digest::SHA256, msg, signature)
             }
@@ -142,17 +361,306 @@ impl JsonWebKey<CoreJwsSigningAlgorithm, CoreJsonWebKeyType, CoreJsonWebKeyUse>
             CoreJwsSigningAlgorithm::HmacSha512 => {
                 crypto::verify_hmac(self, &digest::SHA512, msg, signature)
             }
+            CoreJwsSigningAlgorithm::EdDsa => crypto::verify_eddsa_signature(self, msg, signature),
             ref other => Err(SignatureVerificationError::UnsupportedAlg(
                 variant_name(other).to_string(),
             )),
         }
     }
 }
+impl
+    PrivateSigningKey<
+        CoreJwsSigningAlgorithm,
+        CoreJsonWebKeyType,
+        CoreJsonWebKeyUse,
+        CoreJsonWebKey,
+    > for CoreJsonWebKey
+{
+    fn sign(
+        &self,
+        signature_alg: &CoreJwsSigningAlgorithm,
+        message: &[u8],
+    ) -> Result<Vec<u8>, SigningError> {
+        if let Some(key_use) = self.key_use() {
+            if *key_use != CoreJsonWebKeyUse::Signature {
+                return Err(SigningError::InvalidKey(
+                    "key usage not permitted for digital signatures".to_string(),
+                ));
+            }
+        }
+
+        let key_type = signature_alg.key_type().map_err(SigningError::Other)?;
+        if *self.key_type() != key_type {
+            return Err(SigningError::InvalidKey(
+                "key type does not match signature algorithm".to_string(),
+            ));
+        }
+
+        match *signature_alg {
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256 => {
+                crypto::sign_rsa(self, &ring_signature::RSA_PKCS1_SHA256, message)
+            }
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha384 => {
+                crypto::sign_rsa(self, &ring_signature::RSA_PKCS1_SHA384, message)
+            }
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha512 => {
+                crypto::sign_rsa(self, &ring_signature::RSA_PKCS1_SHA512, message)
+            }
+            CoreJwsSigningAlgorithm::RsaSsaPssSha256 => {
+                crypto::sign_rsa(self, &ring_signature::RSA_PSS_SHA256, message)
+            }
+            CoreJwsSigningAlgorithm::RsaSsaPssSha384 => {
+                crypto::sign_rsa(self, &ring_signature::RSA_PSS_SHA384, message)
+            }
+            CoreJwsSigningAlgorithm::RsaSsaPssSha512 => {
+                crypto::sign_rsa(self, &ring_signature::RSA_PSS_SHA512, message)
+            }
+            CoreJwsSigningAlgorithm::EcdsaP256Sha256 => {
+                crypto::sign_ec(self, &CoreJsonCurveType::P256, message)
+            }
+            CoreJwsSigningAlgorithm::EcdsaP384Sha384 => {
+                crypto::sign_ec(self, &CoreJsonCurveType::P384, message)
+            }
+            CoreJwsSigningAlgorithm::EdDsa => crypto::sign_eddsa(self, message),
+            CoreJwsSigningAlgorithm::HmacSha256 => {
+                crypto::sign_hmac(self, &digest::SHA256, message)
+            }
+            CoreJwsSigningAlgorithm::HmacSha384 => {
+                crypto::sign_hmac(self, &digest::SHA384, message)
+            }
+            CoreJwsSigningAlgorithm::HmacSha512 => {
+                crypto::sign_hmac(self, &digest::SHA512, message)
+            }
+            ref other => Err(SigningError::UnsupportedAlg(
+                variant_name(other).to_string(),
+            )),
+        }
+    }
+
+    fn as_verification_key(&self) -> CoreJsonWebKey {
+        CoreJsonWebKey {
+            d: None,
+            p: None,
+            q: None,
+            dp: None,
+            dq: None,
+            qi: None,
+            ..self.clone()
+        }
+    }
+}
+
+impl
+    PrivateDecryptionKey<
+        CoreJweContentEncryptionAlgorithm,
+        CoreJweKeyManagementAlgorithm,
+        CoreJwsSigningAlgorithm,
+        CoreJsonWebKeyType,
+        CoreJsonWebKeyUse,
+    > for CoreJsonWebKey
+{
+    fn allows_key_management_alg(
+        &self,
+        key_management_alg: &CoreJweKeyManagementAlgorithm,
+    ) -> bool {
+        match *key_management_alg {
+            CoreJweKeyManagementAlgorithm::RsaOaep
+            | CoreJweKeyManagementAlgorithm::RsaOaepSha256 => {
+                *self.key_type() == CoreJsonWebKeyType::RSA
+            }
+            CoreJweKeyManagementAlgorithm::AesKeyWrap128
+            | CoreJweKeyManagementAlgorithm::AesKeyWrap192
+            | CoreJweKeyManagementAlgorithm::AesKeyWrap256
+            | CoreJweKeyManagementAlgorithm::Direct => {
+                *self.key_type() == CoreJsonWebKeyType::Symmetric
+            }
+            _ => false,
+        }
+    }
+
+    fn decrypt(
+        &self,
+        key_management_alg: &CoreJweKeyManagementAlgorithm,
+        content_encryption_alg: &CoreJweContentEncryptionAlgorithm,
+        jwe: &JsonWebEncryption,
+    ) -> Result<Vec<u8>, DecryptionError> {
+        if let Some(key_use) = self.key_use() {
+            if *key_use != CoreJsonWebKeyUse::Encryption {
+                return Err(DecryptionError::InvalidKey(
+                    "key usage not permitted for encryption".to_string(),
+                ));
+            }
+        }
+
+        let content_key_len = content_encryption_alg.key_len();
+
+        let content_key = match *key_management_alg {
+            #[cfg(feature = "rsa-oaep")]
+            CoreJweKeyManagementAlgorithm::RsaOaep => crypto::decrypt_rsa_oaep(
+                self,
+                rsa::Oaep::new::<sha1::Sha1>(),
+                jwe.encrypted_key(),
+                content_key_len,
+            )?,
+            #[cfg(feature = "rsa-oaep")]
+            CoreJweKeyManagementAlgorithm::RsaOaepSha256 => crypto::decrypt_rsa_oaep(
+                self,
+                rsa::Oaep::new::<sha2::Sha256>(),
+                jwe.encrypted_key(),
+                content_key_len,
+            )?,
+            CoreJweKeyManagementAlgorithm::AesKeyWrap128 => {
+                crypto::unwrap_aes_key(self, 16, jwe.encrypted_key())?
+            }
+            CoreJweKeyManagementAlgorithm::AesKeyWrap192 => {
+                crypto::unwrap_aes_key(self, 24, jwe.encrypted_key())?
+            }
+            CoreJweKeyManagementAlgorithm::AesKeyWrap256 => {
+                crypto::unwrap_aes_key(self, 32, jwe.encrypted_key())?
+            }
+            CoreJweKeyManagementAlgorithm::Direct => {
+                // With direct encryption, the key itself is the content encryption key, and the
+                // JWE Encrypted Key must be empty (see Section 4.5 of RFC 7518).
+                if !jwe.encrypted_key().is_empty() {
+                    return Err(DecryptionError::CryptoError(
+                        "encrypted key must be empty when using direct encryption".to_string(),
+                    ));
+                }
+                crypto::symmetric_key(self, content_key_len)?.to_vec()
+            }
+            ref other => {
+                return Err(DecryptionError::UnsupportedAlg(
+                    variant_name(other).to_string(),
+                ))
+            }
+        };
+
+        // A content key of the wrong length must not be used (see Section 11.5 of RFC 7516 for
+        // a related attack on RSA key management).
+        if content_key.len() != content_key_len {
+            return Err(DecryptionError::CryptoError(
+                "failed to decrypt content".to_string(),
+            ));
+        }
+
+        match *content_encryption_alg {
+            CoreJweContentEncryptionAlgorithm::Aes128CbcHmacSha256 => {
+                crypto::decrypt_aes_cbc_hmac(&content_key, &digest::SHA256, jwe)
+            }
+            CoreJweContentEncryptionAlgorithm::Aes192CbcHmacSha384 => {
+                crypto::decrypt_aes_cbc_hmac(&content_key, &digest::SHA384, jwe)
+            }
+            CoreJweContentEncryptionAlgorithm::Aes256CbcHmacSha512 => {
+                crypto::decrypt_aes_cbc_hmac(&content_key, &digest::SHA512, jwe)
+            }
+            CoreJweContentEncryptionAlgorithm::Aes128Gcm
+            | CoreJweContentEncryptionAlgorithm::Aes192Gcm
+            | CoreJweContentEncryptionAlgorithm::Aes256Gcm => {
+                crypto::decrypt_aes_gcm(&content_key, jwe)
+            }
+        }
+    }
+}
+
+impl
+    PublicEncryptionKey<
+        CoreJweContentEncryptionAlgorithm,
+        CoreJweKeyManagementAlgorithm,
+        CoreJwsSigningAlgorithm,
+        CoreJsonWebKeyType,
+        CoreJsonWebKeyUse,
+    > for CoreJsonWebKey
+{
+    fn encrypt(
+        &self,
+        key_management_alg: &CoreJweKeyManagementAlgorithm,
+        content_encryption_alg: &CoreJweContentEncryptionAlgorithm,
+        additional_authenticated_data: &[u8],
+        plaintext: &[u8],
+    ) -> Result<EncryptedContent, EncryptionError> {
+        if let Some(key_use) = self.key_use() {
+            if *key_use != CoreJsonWebKeyUse::Encryption {
+                return Err(EncryptionError::InvalidKey(
+                    "key usage not permitted for encryption".to_string(),
+                ));
+            }
+        }
+
+        let content_key_len = content_encryption_alg.key_len();
+
+        let content_key = match *key_management_alg {
+            CoreJweKeyManagementAlgorithm::Direct => {
+                crypto::symmetric_encryption_key(self, content_key_len)?.to_vec()
+            }
+            _ => crypto::random_bytes(content_key_len)?,
+        };
+
+        let encrypted_key = match *key_management_alg {
+            #[cfg(feature = "rsa-oaep")]
+            CoreJweKeyManagementAlgorithm::RsaOaep => {
+                crypto::encrypt_rsa_oaep(self, rsa::Oaep::new::<sha1::Sha1>(), &content_key)?
+            }
+            #[cfg(feature = "rsa-oaep")]
+            CoreJweKeyManagementAlgorithm::RsaOaepSha256 => {
+                crypto::encrypt_rsa_oaep(self, rsa::Oaep::new::<sha2::Sha256>(), &content_key)?
+            }
+            CoreJweKeyManagementAlgorithm::AesKeyWrap128 => {
+                crypto::wrap_aes_key(self, 16, &content_key)?
+            }
+            CoreJweKeyManagementAlgorithm::AesKeyWrap192 => {
+                crypto::wrap_aes_key(self, 24, &content_key)?
+            }
+            CoreJweKeyManagementAlgorithm::AesKeyWrap256 => {
+                crypto::wrap_aes_key(self, 32, &content_key)?
+            }
+            // With direct encryption, the JWE Encrypted Key is empty.
+            CoreJweKeyManagementAlgorithm::Direct => Vec::new(),
+            ref other => {
+                return Err(EncryptionError::UnsupportedAlg(
+                    variant_name(other).to_string(),
+                ))
+            }
+        };
+
+        let content = match *content_encryption_alg {
+            CoreJweContentEncryptionAlgorithm::Aes128CbcHmacSha256 => crypto::encrypt_aes_cbc_hmac(
+                &content_key,
+                &digest::SHA256,
+                additional_authenticated_data,
+                plaintext,
+            ),
+            CoreJweContentEncryptionAlgorithm::Aes192CbcHmacSha384 => crypto::encrypt_aes_cbc_hmac(
+                &content_key,
+                &digest::SHA384,
+                additional_authenticated_data,
+                plaintext,
+            ),
+            CoreJweContentEncryptionAlgorithm::Aes256CbcHmacSha512 => crypto::encrypt_aes_cbc_hmac(
+                &content_key,
+                &digest::SHA512,
+                additional_authenticated_data,
+                plaintext,
+            ),
+            CoreJweContentEncryptionAlgorithm::Aes128Gcm
+            | CoreJweContentEncryptionAlgorithm::Aes192Gcm
+            | CoreJweContentEncryptionAlgorithm::Aes256Gcm => {
+                crypto::encrypt_aes_gcm(&content_key, additional_authenticated_data, plaintext)
+            }
+        }?;
+
+        Ok(EncryptedContent {
+            encrypted_key,
+            ..content
+        })
+    }
+}
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum CoreJsonWebKeyType {
     #[serde(rename = "EC")]
     EllipticCurve,
+    #[serde(rename = "OKP")]
+    OctetKeyPair,
     #[serde(rename = "RSA")]
     RSA,
     #[serde(rename = "oct")]
@@ -160,6 +668,36 @@ pub enum CoreJsonWebKeyType {
 }
 impl JsonWebKeyType for CoreJsonWebKeyType {}
 
+///
+/// Curve used by an elliptic curve or octet key pair JSON Web Key.
+///
+/// See [Section 6.2.1.1 of RFC 7518](https://tools.ietf.org/html/rfc7518#section-6.2.1.1) and
+/// [Section 2 of RFC 8037](https://tools.ietf.org/html/rfc8037#section-2).
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum CoreJsonCurveType {
+    ///
+    /// P-256 curve.
+    ///
+    #[serde(rename = "P-256")]
+    P256,
+    ///
+    /// P-384 curve.
+    ///
+    #[serde(rename = "P-384")]
+    P384,
+    ///
+    /// P-521 curve (currently unsupported for signature verification).
+    ///
+    #[serde(rename = "P-521")]
+    P521,
+    ///
+    /// Ed25519 signature algorithm key pairs (used with octet key pairs).
+    ///
+    #[serde(rename = "Ed25519")]
+    Ed25519,
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum CoreJsonWebKeyUse {
     #[serde(rename = "sig")]
@@ -190,9 +728,17 @@ mod tests {
     use oauth2::prelude::*;
     use serde_json;
 
+    use super::super::super::jwt::tests::TEST_RSA_PRIV_KEY;
     use super::super::super::verification::SignatureVerificationError;
-    use super::super::super::{Base64UrlEncodedBytes, JsonWebKey, JsonWebKeyId};
-    use super::{CoreJsonWebKey, CoreJsonWebKeyType, CoreJsonWebKeyUse, CoreJwsSigningAlgorithm};
+    use super::super::super::{
+        Base64UrlEncodedBytes, DecryptionError, EncryptionError, JsonWebEncryption, JsonWebKey,
+        JsonWebKeyId, JsonWebTokenContentType, JsonWebTokenError, PrivateDecryptionKey,
+        PrivateSigningKey, SigningError,
+    };
+    use super::{
+        CoreJsonCurveType, CoreJsonWebKey, CoreJsonWebKeyType, CoreJsonWebKeyUse,
+        CoreJweContentEncryptionAlgorithm, CoreJweKeyManagementAlgorithm, CoreJwsSigningAlgorithm,
+    };
 
     #[test]
     fn test_core_jwk_deserialization_rsa() {
@@ -236,6 +782,77 @@ mod tests {
         assert_eq!(key.k, None);
     }
 
+    #[test]
+    fn test_core_jwk_deserialization_ec() {
+        // Source: https://tools.ietf.org/html/rfc7515#appendix-A.3.1
+        let json = "{
+            \"kty\": \"EC\",
+            \"crv\": \"P-256\",
+            \"x\": \"f83OJ3D2xF1Bg8vub9tLe1gHMzV76e8Tus9uPHvRVEU\",
+            \"y\": \"x_FEzRu9m36HLN_tue659LNpXW6pCyStikYjKIWI5a0\",
+            \"d\": \"jpsQnnGQmL-YBIffH1136cspYG6-0iY7X1fCE9-E9LI\"
+        }";
+
+        let key: CoreJsonWebKey = serde_json::from_str(json).expect("deserialization failed");
+        assert_eq!(key.kty, CoreJsonWebKeyType::EllipticCurve);
+        assert_eq!(key.use_, None);
+        assert_eq!(key.kid, None);
+        assert_eq!(key.n, None);
+        assert_eq!(key.e, None);
+        assert_eq!(key.crv, Some(CoreJsonCurveType::P256));
+        assert_eq!(
+            key.x,
+            Some(Base64UrlEncodedBytes::new(vec![
+                127, 205, 206, 39, 112, 246, 196, 93, 65, 131, 203, 238, 111, 219, 75, 123, 88, 7,
+                51, 53, 123, 233, 239, 19, 186, 207, 110, 60, 123, 209, 84, 69,
+            ]))
+        );
+        assert_eq!(
+            key.y,
+            Some(Base64UrlEncodedBytes::new(vec![
+                199, 241, 68, 205, 27, 189, 155, 126, 135, 44, 223, 237, 185, 238, 185, 244, 179,
+                105, 93, 110, 169, 11, 36, 173, 138, 70, 35, 40, 133, 136, 229, 173,
+            ]))
+        );
+        assert_eq!(key.k, None);
+
+        // Unrecognized curves should be ignored during deserialization
+        let json = "{
+            \"kty\": \"EC\",
+            \"crv\": \"secp256k1\"
+        }";
+        let key: CoreJsonWebKey = serde_json::from_str(json).expect("deserialization failed");
+        assert_eq!(key.kty, CoreJsonWebKeyType::EllipticCurve);
+        assert_eq!(key.crv, None);
+    }
+
+    #[test]
+    fn test_core_jwk_deserialization_okp() {
+        // Source: https://tools.ietf.org/html/rfc8037#appendix-A.2
+        let json = "{
+            \"kty\": \"OKP\",
+            \"crv\": \"Ed25519\",
+            \"x\": \"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo\"
+        }";
+
+        let key: CoreJsonWebKey = serde_json::from_str(json).expect("deserialization failed");
+        assert_eq!(key.kty, CoreJsonWebKeyType::OctetKeyPair);
+        assert_eq!(key.use_, None);
+        assert_eq!(key.kid, None);
+        assert_eq!(key.n, None);
+        assert_eq!(key.e, None);
+        assert_eq!(key.crv, Some(CoreJsonCurveType::Ed25519));
+        assert_eq!(
+            key.x,
+            Some(Base64UrlEncodedBytes::new(vec![
+                215, 90, 152, 1, 130, 177, 10, 183, 213, 75, 254, 211, 201, 100, 7, 58, 14, 225,
+                114, 243, 218, 166, 35, 37, 175, 2, 26, 104, 247, 7, 81, 26,
+            ]))
+        );
+        assert_eq!(key.y, None);
+        assert_eq!(key.k, None);
+    }
+
     #[test]
     fn test_core_jwk_deserialization_symmetric() {
         let json = "{\
@@ -319,6 +936,19 @@ mod tests {
             .expect_err("signature verification should fail");
     }
 
+    #[test]
+    fn test_core_jwk_debug_redacts_private_key() {
+        let key: CoreJsonWebKey =
+            serde_json::from_str(TEST_RSA_PRIV_KEY).expect("deserialization failed");
+        let debug = format!("{:?}", key);
+        assert!(debug.contains("d: Some(\"[redacted]\")"));
+        assert!(debug.contains("qi: Some(\"[redacted]\")"));
+        assert!(debug.contains("k: None"));
+        assert!(!debug.contains(&format!("{:?}", key.d.as_ref().unwrap())));
+        // Public key parameters are still included.
+        assert!(debug.contains(&format!("{:?}", key.n.as_ref().unwrap())));
+    }
+
     #[test]
     fn test_rsa_verification() {
         let key_json = "{
@@ -491,6 +1121,149 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_ecdsa_verification() {
+        // Source: https://tools.ietf.org/html/rfc7515#appendix-A.3
+        let p256_key_json = "{
+            \"kty\": \"EC\",
+            \"crv\": \"P-256\",
+            \"x\": \"f83OJ3D2xF1Bg8vub9tLe1gHMzV76e8Tus9uPHvRVEU\",
+            \"y\": \"x_FEzRu9m36HLN_tue659LNpXW6pCyStikYjKIWI5a0\"
+        }";
+        let p256_key: CoreJsonWebKey =
+            serde_json::from_str(p256_key_json).expect("deserialization failed");
+        let p256_signing_input = "eyJhbGciOiJFUzI1NiJ9.\
+                                  eyJpc3MiOiJqb2UiLA0KICJleHAiOjEzMDA4MTkzODAsDQogImh0dHA6Ly9leGFt\
+                                  cGxlLmNvbS9pc19yb290Ijp0cnVlfQ";
+        verify_signature(
+            &p256_key,
+            &CoreJwsSigningAlgorithm::EcdsaP256Sha256,
+            p256_signing_input,
+            "DtEhU3ljbEg8L38VWAfUAqOyKAM6-Xx-F4GawxaepmXFCgfTjDxw5djxLa8ISlSApmWQxfKTUJqPP3-Kg6NU1Q",
+        );
+
+        // RFC 7515 and RFC 7518 don't include an ES384 example, so this one follows the format of
+        // the ES256 example above (with a freshly generated key).
+        let p384_key_json = "{
+            \"kty\": \"EC\",
+            \"kid\": \"ec384\",
+            \"use\": \"sig\",
+            \"crv\": \"P-384\",
+            \"x\": \"kgbx-aDJb_kl2H_knTJKzFoEJBG_KzMrsI02AeQJhfAyo2WonaMLDOsaTqcmEeRz\",
+            \"y\": \"E18_UxVup6M3y5y1RhY7Q3X96LRS8DzqjynzRX5KkyPWiD4XleI0QAr1pHf1jnUi\"
+        }";
+        let p384_key: CoreJsonWebKey =
+            serde_json::from_str(p384_key_json).expect("deserialization failed");
+        let p384_signing_input = "eyJhbGciOiJFUzM4NCIsImtpZCI6ImVjMzg0In0.\
+                                  eyJpc3MiOiJqb2UiLA0KICJleHAiOjEzMDA4MTkzODAsDQogImh0dHA6Ly9leGFt\
+                                  cGxlLmNvbS9pc19yb290Ijp0cnVlfQ";
+        verify_signature(
+            &p384_key,
+            &CoreJwsSigningAlgorithm::EcdsaP384Sha384,
+            p384_signing_input,
+            "rEKQ0IvpjjG-Uik_J900N8_PS7x9NS_7lx46FokwtZG6Vv4zIOxbkMhIucpl9phqxrrsAMzdDRUlgTs9gxwJi7\
+             Q-SD5aN-aEHEbzdF499A0SRMF-_z-DCfy-5lqji6Tl",
+        );
+
+        // Curve doesn't match the signature algorithm
+        match p256_key
+            .verify_signature(
+                &CoreJwsSigningAlgorithm::EcdsaP384Sha384,
+                p256_signing_input,
+                &Vec::new(),
+            ).expect_err("signature verification should fail")
+        {
+            SignatureVerificationError::InvalidKey(_) => {}
+            other => panic!(format!("unexpected error: {:?}", other)),
+        }
+
+        // Wrong key type
+        match p256_key
+            .verify_signature(
+                &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+                p256_signing_input,
+                &Vec::new(),
+            ).expect_err("signature verification should fail")
+        {
+            SignatureVerificationError::InvalidKey(_) => {}
+            other => panic!(format!("unexpected error: {:?}", other)),
+        }
+
+        // P-521 isn't supported by ring
+        let p521_key_json = "{
+            \"kty\": \"EC\",
+            \"crv\": \"P-521\",
+            \"x\": \"AG4bVob-GE_UOzlyglSVnAqLmve0FCoCoZ4KCR-_roXKigAGHRAHaB-sBBjTQ3Mtjcm6LPcPYSgWyA\
+                     JZ1CKp3oPe\",
+            \"y\": \"AYa-mZbzLKHwbuGOt7Ste_t-GwFGnEajEaeI1UOSq4EPX8drfsLsgQNDtGbhiddIXT5LQfNEUs5Jrx\
+                     dnetwW4q6A\"
+        }";
+        let p521_key: CoreJsonWebKey =
+            serde_json::from_str(p521_key_json).expect("deserialization failed");
+        assert_eq!(p521_key.crv, Some(CoreJsonCurveType::P521));
+        match p521_key
+            .verify_signature(
+                &CoreJwsSigningAlgorithm::EcdsaP521Sha512,
+                p256_signing_input,
+                &Vec::new(),
+            ).expect_err("signature verification should fail")
+        {
+            SignatureVerificationError::UnsupportedAlg(_) => {}
+            other => panic!(format!("unexpected error: {:?}", other)),
+        }
+    }
+
+    #[test]
+    fn test_eddsa_verification() {
+        // Source: https://tools.ietf.org/html/rfc8037#appendix-A.4
+        let key_json = "{
+            \"kty\": \"OKP\",
+            \"crv\": \"Ed25519\",
+            \"x\": \"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo\"
+        }";
+        let key: CoreJsonWebKey = serde_json::from_str(key_json).expect("deserialization failed");
+        let signing_input = "eyJhbGciOiJFZERTQSJ9.RXhhbXBsZSBvZiBFZDI1NTE5IHNpZ25pbmc";
+        verify_signature(
+            &key,
+            &CoreJwsSigningAlgorithm::EdDsa,
+            signing_input,
+            "hgyY0il_MGCjP0JzlnLWG1PPOt7-09PGcvMg3AIbQR6dWbhijcNR4ki4iylGjg5BhVsPt9g7sVvpAr_MuM0KAg",
+        );
+
+        // Octet key pairs on other curves (e.g., X25519) can't be used for signatures
+        let x25519_key_json = "{
+            \"kty\": \"OKP\",
+            \"crv\": \"X25519\",
+            \"x\": \"hSDwCYkwp1R0i33ctD73Wg2_Og0mOBr066SpjqqbTmo\"
+        }";
+        let x25519_key: CoreJsonWebKey =
+            serde_json::from_str(x25519_key_json).expect("deserialization failed");
+        match x25519_key
+            .verify_signature(&CoreJwsSigningAlgorithm::EdDsa, signing_input, &Vec::new())
+            .expect_err("signature verification should fail")
+        {
+            SignatureVerificationError::InvalidKey(_) => {}
+            other => panic!(format!("unexpected error: {:?}", other)),
+        }
+
+        // EdDSA requires an octet key pair
+        let ec_key_json = "{
+            \"kty\": \"EC\",
+            \"crv\": \"P-256\",
+            \"x\": \"f83OJ3D2xF1Bg8vub9tLe1gHMzV76e8Tus9uPHvRVEU\",
+            \"y\": \"x_FEzRu9m36HLN_tue659LNpXW6pCyStikYjKIWI5a0\"
+        }";
+        let ec_key: CoreJsonWebKey =
+            serde_json::from_str(ec_key_json).expect("deserialization failed");
+        match ec_key
+            .verify_signature(&CoreJwsSigningAlgorithm::EdDsa, signing_input, &Vec::new())
+            .expect_err("signature verification should fail")
+        {
+            SignatureVerificationError::InvalidKey(_) => {}
+            other => panic!(format!("unexpected error: {:?}", other)),
+        }
+    }
+
     #[test]
     fn test_hmac_sha256_verification() {
         let key_json = "{
@@ -532,4 +1305,522 @@ mod tests {
              YiyiM11MwIEAxzxBj6qw",
         );
     }
+
+    fn sign_and_verify(key: &CoreJsonWebKey, alg: &CoreJwsSigningAlgorithm, signing_input: &str) {
+        let signature = key
+            .sign(alg, signing_input.as_bytes())
+            .expect("signing failed");
+        verify_signature(
+            &key.as_verification_key(),
+            alg,
+            signing_input,
+            &base64::encode_config(&signature, base64::URL_SAFE_NO_PAD),
+        );
+    }
+
+    #[test]
+    fn test_rsa_signing() {
+        let key: CoreJsonWebKey =
+            serde_json::from_str(TEST_RSA_PRIV_KEY).expect("deserialization failed");
+        let signing_input = "eyJhbGciOiJSUzI1NiIsImtpZCI6ImJpbGJvLmJhZ2dpbnNAaG9iYml0b24uZXhhbXBsZS\
+                             J9.SXTigJlzIGEgZGFuZ2Vyb3VzIGJ1c2luZXNzLCBGcm9kbywgZ29pbmcgb3V0IHlvdXIg\
+                             ZG9vci4gWW91IHN0ZXAgb250byB0aGUgcm9hZCwgYW5kIGlmIHlvdSBkb24ndCBrZWVwIHlv\
+                             dXIgZmVldCwgdGhlcmXigJlzIG5vIGtub3dpbmcgd2hlcmUgeW91IG1pZ2h0IGJlIHN3ZXB0\
+                             IG9mZiB0by4";
+
+        // RSASSA-PKCS1-v1_5 signatures are deterministic.
+        let signature = key
+            .sign(
+                &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+                signing_input.as_bytes(),
+            ).expect("signing failed");
+        assert_eq!(
+            base64::encode_config(&signature, base64::URL_SAFE_NO_PAD),
+            "RzI1C9NnPMbMCZAvnhZsb9BjjxyzSrDExiLiOj3ygAuydVKRGtIMQgRkAJUCjDug658yZVf33pymZICCx5IQyN\
+             GuOkc58OBbWOvZ5yIRGj6YpJ5XbDNBN5SZl0lw2OPbzUVK4uMrRXxSH2nT2OMceB5TgZsysSVYMx7sjJAcg77ywz\
+             mnk7RoI30nWF69ztKx-V9nUy37H17Jv3e0mwyCTMicjo3p5zgE0hmeu_08hy9qPqm-SURZ8F52hBHTnPvagIW4Km\
+             JIVW6fEpaHj3YzdOAFyrLWeBggRqccvqfLQbr8QiKjzWnYehQYX4v6gtDKfbKzRMf23zFRVtCy0VYUyw"
+        );
+
+        sign_and_verify(
+            &key,
+            &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+            signing_input,
+        );
+        sign_and_verify(
+            &key,
+            &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha384,
+            signing_input,
+        );
+        sign_and_verify(
+            &key,
+            &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha512,
+            signing_input,
+        );
+        sign_and_verify(
+            &key,
+            &CoreJwsSigningAlgorithm::RsaSsaPssSha256,
+            signing_input,
+        );
+        sign_and_verify(
+            &key,
+            &CoreJwsSigningAlgorithm::RsaSsaPssSha384,
+            signing_input,
+        );
+        sign_and_verify(
+            &key,
+            &CoreJwsSigningAlgorithm::RsaSsaPssSha512,
+            signing_input,
+        );
+
+        // The verification key shouldn't include any private key parameters.
+        let public_key = key.as_verification_key();
+        assert_eq!(public_key.kid, key.kid);
+        assert_eq!(public_key.n, key.n);
+        assert_eq!(public_key.e, key.e);
+        assert_eq!(public_key.d, None);
+        assert_eq!(public_key.p, None);
+        assert_eq!(public_key.q, None);
+        assert_eq!(public_key.dp, None);
+        assert_eq!(public_key.dq, None);
+        assert_eq!(public_key.qi, None);
+
+        match public_key
+            .sign(
+                &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+                signing_input.as_bytes(),
+            ).expect_err("signing should fail")
+        {
+            SigningError::InvalidKey(_) => {}
+            other => panic!(format!("unexpected error: {:?}", other)),
+        }
+
+        match key
+            .sign(
+                &CoreJwsSigningAlgorithm::HmacSha256,
+                signing_input.as_bytes(),
+            ).expect_err("signing should fail")
+        {
+            SigningError::InvalidKey(_) => {}
+            other => panic!(format!("unexpected error: {:?}", other)),
+        }
+
+        match key
+            .sign(&CoreJwsSigningAlgorithm::None, signing_input.as_bytes())
+            .expect_err("signing should fail")
+        {
+            SigningError::Other(_) => {}
+            other => panic!(format!("unexpected error: {:?}", other)),
+        }
+    }
+
+    #[test]
+    fn test_ecdsa_signing() {
+        // Source: https://tools.ietf.org/html/rfc7515#appendix-A.3.1
+        let p256_key_json = "{
+            \"kty\": \"EC\",
+            \"crv\": \"P-256\",
+            \"x\": \"f83OJ3D2xF1Bg8vub9tLe1gHMzV76e8Tus9uPHvRVEU\",
+            \"y\": \"x_FEzRu9m36HLN_tue659LNpXW6pCyStikYjKIWI5a0\",
+            \"d\": \"jpsQnnGQmL-YBIffH1136cspYG6-0iY7X1fCE9-E9LI\"
+        }";
+        let p256_key: CoreJsonWebKey =
+            serde_json::from_str(p256_key_json).expect("deserialization failed");
+        let signing_input = "eyJhbGciOiJFUzI1NiJ9.\
+                             eyJpc3MiOiJqb2UiLA0KICJleHAiOjEzMDA4MTkzODAsDQogImh0dHA6Ly9leGFt\
+                             cGxlLmNvbS9pc19yb290Ijp0cnVlfQ";
+        sign_and_verify(
+            &p256_key,
+            &CoreJwsSigningAlgorithm::EcdsaP256Sha256,
+            signing_input,
+        );
+        assert_eq!(p256_key.as_verification_key().d, None);
+
+        let p384_key_json = "{
+            \"kty\": \"EC\",
+            \"crv\": \"P-384\",
+            \"x\": \"kgbx-aDJb_kl2H_knTJKzFoEJBG_KzMrsI02AeQJhfAyo2WonaMLDOsaTqcmEeRz\",
+            \"y\": \"E18_UxVup6M3y5y1RhY7Q3X96LRS8DzqjynzRX5KkyPWiD4XleI0QAr1pHf1jnUi\",
+            \"d\": \"Aqy_TKmE_EegaG6FL37FTHXqvgJ0Y-PS-AlQz7dtUkc9oLsvFsUy7gxdibs3qN54\"
+        }";
+        let p384_key: CoreJsonWebKey =
+            serde_json::from_str(p384_key_json).expect("deserialization failed");
+        sign_and_verify(
+            &p384_key,
+            &CoreJwsSigningAlgorithm::EcdsaP384Sha384,
+            signing_input,
+        );
+
+        // Curve doesn't match the signature algorithm
+        match p256_key
+            .sign(
+                &CoreJwsSigningAlgorithm::EcdsaP384Sha384,
+                signing_input.as_bytes(),
+            ).expect_err("signing should fail")
+        {
+            SigningError::InvalidKey(_) => {}
+            other => panic!(format!("unexpected error: {:?}", other)),
+        }
+    }
+
+    #[test]
+    fn test_eddsa_signing() {
+        // Source: https://tools.ietf.org/html/rfc8037#appendix-A
+        let key_json = "{
+            \"kty\": \"OKP\",
+            \"crv\": \"Ed25519\",
+            \"d\": \"nWGxne_9WmC6hEr0kuwsxERJxWl7MmkZcDusAxyuf2A\",
+            \"x\": \"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo\"
+        }";
+        let key: CoreJsonWebKey = serde_json::from_str(key_json).expect("deserialization failed");
+        let signing_input = "eyJhbGciOiJFZERTQSJ9.RXhhbXBsZSBvZiBFZDI1NTE5IHNpZ25pbmc";
+
+        // Ed25519 signatures are deterministic.
+        let signature = key
+            .sign(&CoreJwsSigningAlgorithm::EdDsa, signing_input.as_bytes())
+            .expect("signing failed");
+        assert_eq!(
+            base64::encode_config(&signature, base64::URL_SAFE_NO_PAD),
+            "hgyY0il_MGCjP0JzlnLWG1PPOt7-09PGcvMg3AIbQR6dWbhijcNR4ki4iylGjg5BhVsPt9g7sVvpAr_MuM0KAg"
+        );
+        sign_and_verify(&key, &CoreJwsSigningAlgorithm::EdDsa, signing_input);
+    }
+
+    #[test]
+    fn test_hmac_signing() {
+        // Source: https://tools.ietf.org/html/rfc7515#appendix-A.1
+        let key_json = "{
+            \"kty\": \"oct\",
+            \"k\": \"AyM1SysPpbyDfgZld3umj1qzKObwVMkoqQ-EstJQLr_T-1qS0gZH75aKtMN3Yj0iPS4hcgUuTwjAzZ\
+                     r1Z9CAow\"
+        }";
+        let key: CoreJsonWebKey = serde_json::from_str(key_json).expect("deserialization failed");
+        let signing_input = "eyJ0eXAiOiJKV1QiLA0KICJhbGciOiJIUzI1NiJ9.\
+                             eyJpc3MiOiJqb2UiLA0KICJleHAiOjEzMDA4MTkzODAsDQogImh0dHA6Ly9leGFt\
+                             cGxlLmNvbS9pc19yb290Ijp0cnVlfQ";
+
+        let signature = key
+            .sign(
+                &CoreJwsSigningAlgorithm::HmacSha256,
+                signing_input.as_bytes(),
+            ).expect("signing failed");
+        assert_eq!(
+            base64::encode_config(&signature, base64::URL_SAFE_NO_PAD),
+            "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"
+        );
+
+        // The verification key for a symmetric key is the key itself.
+        assert_eq!(key.as_verification_key(), key);
+        sign_and_verify(&key, &CoreJwsSigningAlgorithm::HmacSha256, signing_input);
+        sign_and_verify(&key, &CoreJwsSigningAlgorithm::HmacSha384, signing_input);
+        sign_and_verify(&key, &CoreJwsSigningAlgorithm::HmacSha512, signing_input);
+    }
+
+    fn decrypt(
+        key_json: &str,
+        key_management_alg: &CoreJweKeyManagementAlgorithm,
+        content_encryption_alg: &CoreJweContentEncryptionAlgorithm,
+        jwe: &str,
+    ) -> Result<Vec<u8>, DecryptionError> {
+        let key: CoreJsonWebKey = serde_json::from_str(key_json).expect("deserialization failed");
+        let jwe: JsonWebEncryption =
+            serde_json::from_value(serde_json::Value::String(jwe.to_string()))
+                .expect("failed to deserialize JWE");
+        assert!(key.allows_key_management_alg(key_management_alg));
+        key.decrypt(key_management_alg, content_encryption_alg, &jwe)
+    }
+
+    #[test]
+    fn test_aes_key_wrap_decryption() {
+        // Source: https://tools.ietf.org/html/rfc7516#appendix-A.3
+        let key_json = "{\"kty\": \"oct\", \"k\": \"GawgguFyGrWKav7AX4VKUg\"}";
+        let jwe = "eyJhbGciOiJBMTI4S1ciLCJlbmMiOiJBMTI4Q0JDLUhTMjU2In0.\
+                   6KB707dM9YTIgHtLvtgWQ8mKwboJW3of9locizkDTHzBC2IlrT1oOQ.\
+                   AxY8DCtDaGlsbGljb3RoZQ.\
+                   KDlTtXchhZTGufMYmOYGS4HffxPSUrfmqCHXaI9wOGY.\
+                   U0m_YmjN04DJvceFICbCVQ";
+        assert_eq!(
+            decrypt(
+                key_json,
+                &CoreJweKeyManagementAlgorithm::AesKeyWrap128,
+                &CoreJweContentEncryptionAlgorithm::Aes128CbcHmacSha256,
+                jwe,
+            ).expect("decryption failed"),
+            b"Live long and prosper.".to_vec()
+        );
+
+        // Tampered authentication tag.
+        match decrypt(
+            key_json,
+            &CoreJweKeyManagementAlgorithm::AesKeyWrap128,
+            &CoreJweContentEncryptionAlgorithm::Aes128CbcHmacSha256,
+            &format!("{}A", &jwe[..jwe.len() - 1]),
+        ) {
+            Err(DecryptionError::CryptoError(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Wrong key length for the key management algorithm.
+        match decrypt(
+            key_json,
+            &CoreJweKeyManagementAlgorithm::AesKeyWrap256,
+            &CoreJweContentEncryptionAlgorithm::Aes128CbcHmacSha256,
+            jwe,
+        ) {
+            Err(DecryptionError::InvalidKey(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // 192-bit key wrapping a content key for AES-192-CBC with HMAC SHA-384.
+        assert_eq!(
+            decrypt(
+                "{\"kty\": \"oct\", \"k\": \"AAECAwQFBgcICQoLDA0ODxAREhMUFRYX\"}",
+                &CoreJweKeyManagementAlgorithm::AesKeyWrap192,
+                &CoreJweContentEncryptionAlgorithm::Aes192CbcHmacSha384,
+                "eyJhbGciOiJBMTkyS1ciLCJlbmMiOiJBMTkyQ0JDLUhTMzg0In0.\
+                 zm_uk1T8w-IdT8p3V6d4nLMPjxxsNWa2s6cjyQU3SH2xeUH77xY8sX6T1fDYj-kGtYjaFNKdQyA.\
+                 LlEjdCq2X8rnhF0B-eurGQ.\
+                 14-D93Stq-y9V1Q4IClv88u6j5d61rTlE5zK3ogJcqs.\
+                 HNLCwHFav14SQEZIRxeDp3ElwlSxOhn-",
+            ).expect("decryption failed"),
+            b"Live long and prosper.".to_vec()
+        );
+    }
+
+    #[test]
+    fn test_direct_decryption() {
+        let key_json = "{\"kty\": \"oct\", \"k\": \"AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8\"}";
+        let jwe = "eyJhbGciOiJkaXIiLCJlbmMiOiJBMjU2R0NNIn0..\
+                   l14ziCITUeHbffv7.\
+                   Zk-nlIxtNaXcFOAbqNhnW23J1x_0Og.\
+                   4MzcP5txAfxEf4TA71XAHw";
+        assert_eq!(
+            decrypt(
+                key_json,
+                &CoreJweKeyManagementAlgorithm::Direct,
+                &CoreJweContentEncryptionAlgorithm::Aes256Gcm,
+                jwe,
+            ).expect("decryption failed"),
+            b"Live long and prosper.".to_vec()
+        );
+
+        // The key length must match the content encryption algorithm.
+        match decrypt(
+            key_json,
+            &CoreJweKeyManagementAlgorithm::Direct,
+            &CoreJweContentEncryptionAlgorithm::Aes128Gcm,
+            jwe,
+        ) {
+            Err(DecryptionError::InvalidKey(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "rsa-oaep")]
+    fn test_rsa_oaep_decryption() {
+        let key_json = TEST_RSA_PRIV_KEY.replace("\"sig\"", "\"enc\"");
+        let key: CoreJsonWebKey = serde_json::from_str(&key_json).expect("deserialization failed");
+        assert!(key.allows_key_management_alg(&CoreJweKeyManagementAlgorithm::RsaOaep));
+        assert!(key.allows_key_management_alg(&CoreJweKeyManagementAlgorithm::RsaOaepSha256));
+        assert!(!key.allows_key_management_alg(&CoreJweKeyManagementAlgorithm::RsaPkcs1V15));
+        assert!(!key.allows_key_management_alg(&CoreJweKeyManagementAlgorithm::Direct));
+
+        let jwe = "eyJhbGciOiJSU0EtT0FFUCIsImVuYyI6IkEyNTZHQ00iLCJraWQiOiJyc2EtZW5jcnlwdGlvbi1rZXki\
+                   LCJjdHkiOiJKV1QifQ.\
+                   XIiFq8IgOfY4BI4mviJsD87UcX0QrAytQ1vjqFqOaC_7XhFrQd2nlnCQmhHjVrJBT1yEH_1uPTGg8A_C\
+                   yotDLV9JJIrtd0T2Cr-bC1PsLj1NRaOhkXGUhQfj8K5pvBXlK-Z_8XCa8I1jUDyzrBU5wYHF4SS9cZzR\
+                   az51IZl5Vro4TzFKivnnUcRpO1bPnNL8kOH1daBMKpGjn5oiJjiDtB-uYGrAHVObvSAE36wvP78JpmnmZ\
+                   aG7cW5iZu-4ktJyBdqrxXnFuWVI-hnUkdNozDd9PVOjxz3HIC4PYgVRujaOvAOI6SX-zbyWEKYxYlridw\
+                   iikgIadrFSh1k2KCZCUg.\
+                   5rM5Ib4t3xdKH1Da.\
+                   vvzEywaS1XUjvmWLV65TuNyiQT-DT5sHqBXfnjFA8qpunXHhj3SsCNS9QmxhYVK1HV09mLMxQPy665s4J\
+                   D8pfDigXKBMSuAY0yumrr80xgcUZqGnOfx2qOgCsz11jCnlj7Ooo4XIYKPCKBVqFOZ3UlTEpWCt3BprVI\
+                   ZNtwgMVMk8tSx0qa1h_XQYQ6kZmzo6PRtzSADQjGJgbKIm9vJurpAPyK-8ozhBk9Ny8SzYIIopPBZzjUh\
+                   Vwyjl1_xmBe45ul0iVxvToIGUxN5mxFD2Z9BWST46zgISiRkEaOc7dGbn0MWyMJrxALuY-6qZQXe_OcXg\
+                   DQk-MUAnt9IBWilNZ0NtjOmFdVIU0cexGPRleYFFDOld-bZdyUUwTYSGdl1ZCZ8RrbGA8bLPr-QoHyYOT\
+                   whjHzbDluR4dY0HbtQSEKJWH7Rn9UFMoxSpRSwd4Y_SUxGX6koPqFSv77_9pKs9sJOvcCV-sG3sfdLmp4\
+                   mPOsOjJ9V1Tj-DVfw8A5xiVcd9usmk4gMrjRK7mBmDZkOaco2O9l1_9sTt9U5-dY84tO4DGiJYgiRtJVX\
+                   JXJQjF-Hn-VVvp_V5OuIg0TA5R4eVr9xn7wE7Q4jVP6_GrbPmuYvzZ1zbFB5kJK0oRGrU8TMyoGPASgNZ\
+                   X5ijFVdzk_HXLfj73N5idAYHCseq7s1TeGq0wmvdnAKS_4dOyQWSm5uD6WavgLYwiCcNVYtm-knk_XIEe\
+                   Qp-od4iSx9FqJKaXeNbErVYnl6FVBGxZq2ZuZtpvPwjzxLzlg.\
+                   HRte9CbknjtQNZHRqdi2dQ";
+        let plaintext = decrypt(
+            &key_json,
+            &CoreJweKeyManagementAlgorithm::RsaOaep,
+            &CoreJweContentEncryptionAlgorithm::Aes256Gcm,
+            jwe,
+        ).expect("decryption failed");
+        assert!(String::from_utf8(plaintext)
+            .unwrap()
+            .starts_with("eyJ0eXAiOiJKV1QiLCJhbGciOiJSUzI1NiIsImtpZCI6InJzYS1zaWduaW5nLWtleSJ9."));
+
+        // Failing to decrypt the content key (e.g., due to the wrong OAEP hash), decrypting a
+        // content key of the wrong length, and failing to decrypt the content itself all result
+        // in the same error, to avoid revealing which step failed (see Section 11.5 of RFC 7516).
+        let assert_decryption_failed = |result| match result {
+            Err(DecryptionError::CryptoError(ref msg)) if msg == "failed to decrypt content" => {}
+            other => panic!("unexpected result: {:?}", other),
+        };
+        assert_decryption_failed(decrypt(
+            &key_json,
+            &CoreJweKeyManagementAlgorithm::RsaOaepSha256,
+            &CoreJweContentEncryptionAlgorithm::Aes256Gcm,
+            jwe,
+        ));
+        assert_decryption_failed(decrypt(
+            &key_json,
+            &CoreJweKeyManagementAlgorithm::RsaOaep,
+            &CoreJweContentEncryptionAlgorithm::Aes128Gcm,
+            jwe,
+        ));
+        assert_decryption_failed(decrypt(
+            &key_json,
+            &CoreJweKeyManagementAlgorithm::RsaOaep,
+            &CoreJweContentEncryptionAlgorithm::Aes256Gcm,
+            &jwe.replace("HRte9CbknjtQNZHRqdi2dQ", "AAAAAAAAAAAAAAAAAAAAAA"),
+        ));
+
+        // Keys restricted to signatures can't be used for decryption.
+        match decrypt(
+            TEST_RSA_PRIV_KEY,
+            &CoreJweKeyManagementAlgorithm::RsaOaep,
+            &CoreJweContentEncryptionAlgorithm::Aes256Gcm,
+            jwe,
+        ) {
+            Err(DecryptionError::InvalidKey(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "rsa-oaep"))]
+    fn test_rsa_oaep_disabled() {
+        let key: CoreJsonWebKey =
+            serde_json::from_str(&TEST_RSA_PRIV_KEY.replace("\"sig\"", "\"enc\""))
+                .expect("deserialization failed");
+        match JsonWebEncryption::encrypt(
+            b"Live long and prosper.",
+            &key.as_verification_key(),
+            &CoreJweKeyManagementAlgorithm::RsaOaep,
+            &CoreJweContentEncryptionAlgorithm::Aes256Gcm,
+            None,
+        ) {
+            Err(JsonWebTokenError::Encryption(EncryptionError::UnsupportedAlg(ref alg))) => {
+                assert_eq!("RSA-OAEP", alg)
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_encryption_round_trip() {
+        let symmetric_key_json = |len: usize| {
+            format!(
+                "{{\"kty\": \"oct\", \"use\": \"enc\", \"k\": \"{}\"}}",
+                base64::encode_config(&vec![7; len], base64::URL_SAFE_NO_PAD)
+            )
+        };
+        #[cfg(feature = "rsa-oaep")]
+        let rsa_key_json = TEST_RSA_PRIV_KEY.replace("\"sig\"", "\"enc\"");
+        let content_encryption_algs = vec![
+            (CoreJweContentEncryptionAlgorithm::Aes128CbcHmacSha256, 32),
+            (CoreJweContentEncryptionAlgorithm::Aes192CbcHmacSha384, 48),
+            (CoreJweContentEncryptionAlgorithm::Aes256CbcHmacSha512, 64),
+            (CoreJweContentEncryptionAlgorithm::Aes128Gcm, 16),
+            (CoreJweContentEncryptionAlgorithm::Aes192Gcm, 24),
+            (CoreJweContentEncryptionAlgorithm::Aes256Gcm, 32),
+        ];
+
+        for &(ref content_encryption_alg, content_key_len) in &content_encryption_algs {
+            let mut keys = vec![
+                (
+                    CoreJweKeyManagementAlgorithm::AesKeyWrap128,
+                    symmetric_key_json(16),
+                ),
+                (
+                    CoreJweKeyManagementAlgorithm::AesKeyWrap192,
+                    symmetric_key_json(24),
+                ),
+                (
+                    CoreJweKeyManagementAlgorithm::AesKeyWrap256,
+                    symmetric_key_json(32),
+                ),
+                (
+                    CoreJweKeyManagementAlgorithm::Direct,
+                    symmetric_key_json(content_key_len),
+                ),
+            ];
+            #[cfg(feature = "rsa-oaep")]
+            keys.extend(vec![
+                (CoreJweKeyManagementAlgorithm::RsaOaep, rsa_key_json.clone()),
+                (
+                    CoreJweKeyManagementAlgorithm::RsaOaepSha256,
+                    rsa_key_json.clone(),
+                ),
+            ]);
+            for (key_management_alg, key_json) in keys {
+                let key: CoreJsonWebKey =
+                    serde_json::from_str(&key_json).expect("deserialization failed");
+                // Only the public key is needed for encryption.
+                let jwe = JsonWebEncryption::encrypt(
+                    b"Live long and prosper.",
+                    &key.as_verification_key(),
+                    &key_management_alg,
+                    content_encryption_alg,
+                    Some(JsonWebTokenContentType::new("JWT".to_string())),
+                ).expect("encryption failed");
+
+                let serialized = serde_json::to_value(&jwe).expect("failed to serialize");
+                assert_eq!(
+                    decrypt(
+                        &key_json,
+                        &key_management_alg,
+                        content_encryption_alg,
+                        serialized.as_str().unwrap(),
+                    ).expect("decryption failed"),
+                    b"Live long and prosper.".to_vec()
+                );
+            }
+        }
+
+        // Keys restricted to signatures can't be used for encryption.
+        let key: CoreJsonWebKey =
+            serde_json::from_str(TEST_RSA_PRIV_KEY).expect("deserialization failed");
+        match JsonWebEncryption::encrypt(
+            b"Live long and prosper.",
+            &key,
+            &CoreJweKeyManagementAlgorithm::RsaOaep,
+            &CoreJweContentEncryptionAlgorithm::Aes256Gcm,
+            None,
+        ) {
+            Err(JsonWebTokenError::Encryption(EncryptionError::InvalidKey(_))) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // The key type must match the key management algorithm.
+        let key: CoreJsonWebKey =
+            serde_json::from_str(&symmetric_key_json(16)).expect("deserialization failed");
+        #[cfg(feature = "rsa-oaep")]
+        match JsonWebEncryption::encrypt(
+            b"Live long and prosper.",
+            &key,
+            &CoreJweKeyManagementAlgorithm::RsaOaep,
+            &CoreJweContentEncryptionAlgorithm::Aes256Gcm,
+            None,
+        ) {
+            Err(JsonWebTokenError::Encryption(EncryptionError::InvalidKey(_))) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match JsonWebEncryption::encrypt(
+            b"Live long and prosper.",
+            &key,
+            &CoreJweKeyManagementAlgorithm::RsaPkcs1V15,
+            &CoreJweContentEncryptionAlgorithm::Aes256Gcm,
+            None,
+        ) {
+            Err(JsonWebTokenError::Encryption(EncryptionError::UnsupportedAlg(_))) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
 }
@@ -5,6 +5,7 @@ use oauth2::basic::{BasicErrorResponseType, BasicTokenType};
 use oauth2::helpers::variant_name;
 use oauth2::prelude::*;
 use oauth2::{ErrorResponseType, ResponseType as OAuth2ResponseType};
+use ring::digest;
 use serde::de::{Error as DeserializeError, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
@@ -14,18 +15,30 @@ use super::registration::{
     Registration10ClientRegistrationRequest, Registration10ClientRegistrationResponse,
 };
 use super::{
-    ApplicationType, AuthDisplay, AuthPrompt, ClaimName, ClaimType, Client, ClientAuthMethod,
-    EmptyAdditionalClaims, GenderClaim, GrantType, IdToken, IdTokenClaims, IdTokenVerifier,
-    JsonWebKeySet, JweContentEncryptionAlgorithm, JweKeyManagementAlgorithm, JwsSigningAlgorithm,
+    ApplicationType, AuthDisplay, AuthErrorResponseType, AuthPrompt, AuthorizationResponse,
+    ClaimName, ClaimType, Client, ClientAuthMethod, EmptyAdditionalClaims, GenderClaim, GrantType,
+    IdToken, IdTokenClaims, IdTokenVerifier, JsonWebKeySet, JweContentEncryptionAlgorithm,
+    JweKeyManagementAlgorithm, JwsSigningAlgorithm, RequestObjectEncryption, RequestObjectSigning,
     ResponseMode, ResponseType, SubjectIdentifierType, UserInfoClaims, UserInfoVerifier,
 };
 
-pub use self::jwk::{CoreJsonWebKey, CoreJsonWebKeyType, CoreJsonWebKeyUse};
+pub use self::jwk::{CoreJsonCurveType, CoreJsonWebKey, CoreJsonWebKeyType, CoreJsonWebKeyUse};
+pub use self::key_import::KeyImportError;
 
 mod crypto;
 
 // Private purely for organizational reasons; exported publicly above.
 mod jwk;
+mod key_import;
+
+pub type CoreAuthorizationResponse = AuthorizationResponse<
+    EmptyAdditionalClaims,
+    CoreAuthErrorResponseType,
+    CoreGenderClaim,
+    CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+>;
 
 pub type CoreClient = Client<
     EmptyAdditionalClaims,
@@ -124,6 +137,16 @@ pub type CoreProviderMetadata = Discovery10ProviderMetadata<
     CoreSubjectIdentifierType,
 >;
 
+pub type CoreRequestObjectEncryption<'a> = RequestObjectEncryption<
+    'a,
+    CoreJweContentEncryptionAlgorithm,
+    CoreJweKeyManagementAlgorithm,
+    CoreJsonWebKey,
+>;
+
+pub type CoreRequestObjectSigning<'a> =
+    RequestObjectSigning<'a, CoreJwsSigningAlgorithm, CoreJsonWebKey>;
+
 pub type CoreUserInfoClaims = UserInfoClaims<EmptyAdditionalClaims, CoreGenderClaim>;
 pub type CoreUserInfoVerifier = UserInfoVerifier<
     CoreJweContentEncryptionAlgorithm,
@@ -209,6 +232,203 @@ impl Display for CoreAuthDisplay {
     }
 }
 
+///
+/// Error codes returned from the authorization endpoint.
+///
+/// These values are defined in
+/// [Section 4.1.2.1 of RFC 6749](https://tools.ietf.org/html/rfc6749#section-4.1.2.1) and
+/// [Section 3.1.2.6 of OpenID Connect Core 1.0](
+///     http://openid.net/specs/openid-connect-core-1_0.html#AuthError).
+///
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum CoreAuthErrorResponseType {
+    ///
+    /// The request is missing a required parameter, includes an invalid parameter value,
+    /// includes a parameter more than once, or is otherwise malformed.
+    ///
+    InvalidRequest,
+    ///
+    /// The client is not authorized to request an authorization code using this method.
+    ///
+    UnauthorizedClient,
+    ///
+    /// The resource owner or authorization server denied the request.
+    ///
+    AccessDenied,
+    ///
+    /// The authorization server does not support obtaining an authorization code using this
+    /// method.
+    ///
+    UnsupportedResponseType,
+    ///
+    /// The requested scope is invalid, unknown, or malformed.
+    ///
+    InvalidScope,
+    ///
+    /// The authorization server encountered an unexpected condition that prevented it from
+    /// fulfilling the request.
+    ///
+    ServerError,
+    ///
+    /// The authorization server is currently unable to handle the request due to a temporary
+    /// overloading or maintenance of the server.
+    ///
+    TemporarilyUnavailable,
+    ///
+    /// The Authorization Server requires End-User interaction of some form to proceed. This
+    /// error may be returned when the `prompt` parameter value in the Authentication Request is
+    /// `none`, but the Authentication Request cannot be completed without displaying a user
+    /// interface for End-User interaction.
+    ///
+    InteractionRequired,
+    ///
+    /// The Authorization Server requires End-User authentication. This error may be returned
+    /// when the `prompt` parameter value in the Authentication Request is `none`, but the
+    /// Authentication Request cannot be completed without displaying a user interface for
+    /// End-User authentication.
+    ///
+    LoginRequired,
+    ///
+    /// The End-User is required to select a session at the Authorization Server. This error may
+    /// be returned when the `prompt` parameter value in the Authentication Request is `none`,
+    /// but the Authentication Request cannot be completed without displaying a user interface to
+    /// prompt for a session to use.
+    ///
+    AccountSelectionRequired,
+    ///
+    /// The Authorization Server requires End-User consent. This error may be returned when the
+    /// `prompt` parameter value in the Authentication Request is `none`, but the Authentication
+    /// Request cannot be completed without displaying a user interface for End-User consent.
+    ///
+    ConsentRequired,
+    ///
+    /// The `request_uri` in the Authorization Request returns an error or contains invalid data.
+    ///
+    InvalidRequestUri,
+    ///
+    /// The `request` parameter contains an invalid Request Object.
+    ///
+    InvalidRequestObject,
+    ///
+    /// The OP does not support use of the `request` parameter.
+    ///
+    RequestNotSupported,
+    ///
+    /// The OP does not support use of the `request_uri` parameter.
+    ///
+    RequestUriNotSupported,
+    ///
+    /// The OP does not support use of the `registration` parameter.
+    ///
+    RegistrationNotSupported,
+    ///
+    /// An error code not defined by either specification.
+    ///
+    Extension(String),
+}
+impl CoreAuthErrorResponseType {
+    ///
+    /// Returns whether the error indicates that the End-User must interact with the
+    /// Authorization Server (e.g., in response to a `prompt=none` request for silent
+    /// reauthentication). If so, the Client may retry the request without `prompt=none`.
+    ///
+    pub fn requires_interaction(&self) -> bool {
+        match *self {
+            CoreAuthErrorResponseType::InteractionRequired
+            | CoreAuthErrorResponseType::LoginRequired
+            | CoreAuthErrorResponseType::AccountSelectionRequired
+            | CoreAuthErrorResponseType::ConsentRequired => true,
+            _ => false,
+        }
+    }
+
+    fn to_str(&self) -> &str {
+        match *self {
+            CoreAuthErrorResponseType::InvalidRequest => "invalid_request",
+            CoreAuthErrorResponseType::UnauthorizedClient => "unauthorized_client",
+            CoreAuthErrorResponseType::AccessDenied => "access_denied",
+            CoreAuthErrorResponseType::UnsupportedResponseType => "unsupported_response_type",
+            CoreAuthErrorResponseType::InvalidScope => "invalid_scope",
+            CoreAuthErrorResponseType::ServerError => "server_error",
+            CoreAuthErrorResponseType::TemporarilyUnavailable => "temporarily_unavailable",
+            CoreAuthErrorResponseType::InteractionRequired => "interaction_required",
+            CoreAuthErrorResponseType::LoginRequired => "login_required",
+            CoreAuthErrorResponseType::AccountSelectionRequired => "account_selection_required",
+            CoreAuthErrorResponseType::ConsentRequired => "consent_required",
+            CoreAuthErrorResponseType::InvalidRequestUri => "invalid_request_uri",
+            CoreAuthErrorResponseType::InvalidRequestObject => "invalid_request_object",
+            CoreAuthErrorResponseType::RequestNotSupported => "request_not_supported",
+            CoreAuthErrorResponseType::RequestUriNotSupported => "request_uri_not_supported",
+            CoreAuthErrorResponseType::RegistrationNotSupported => "registration_not_supported",
+            CoreAuthErrorResponseType::Extension(ref extension) => extension,
+        }
+    }
+}
+impl ErrorResponseType for CoreAuthErrorResponseType {}
+impl AuthErrorResponseType for CoreAuthErrorResponseType {}
+impl<'de> Deserialize<'de> for CoreAuthErrorResponseType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct CoreAuthErrorResponseTypeVisitor;
+        impl<'de> Visitor<'de> for CoreAuthErrorResponseTypeVisitor {
+            type Value = CoreAuthErrorResponseType;
+
+            fn expecting(&self, formatter: &mut Formatter) -> FormatterResult {
+                formatter.write_str("CoreAuthErrorResponseType")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: DeserializeError,
+            {
+                Ok(match v {
+                    "invalid_request" => CoreAuthErrorResponseType::InvalidRequest,
+                    "unauthorized_client" => CoreAuthErrorResponseType::UnauthorizedClient,
+                    "access_denied" => CoreAuthErrorResponseType::AccessDenied,
+                    "unsupported_response_type" => {
+                        CoreAuthErrorResponseType::UnsupportedResponseType
+                    }
+                    "invalid_scope" => CoreAuthErrorResponseType::InvalidScope,
+                    "server_error" => CoreAuthErrorResponseType::ServerError,
+                    "temporarily_unavailable" => CoreAuthErrorResponseType::TemporarilyUnavailable,
+                    "interaction_required" => CoreAuthErrorResponseType::InteractionRequired,
+                    "login_required" => CoreAuthErrorResponseType::LoginRequired,
+                    "account_selection_required" => {
+                        CoreAuthErrorResponseType::AccountSelectionRequired
+                    }
+                    "consent_required" => CoreAuthErrorResponseType::ConsentRequired,
+                    "invalid_request_uri" => CoreAuthErrorResponseType::InvalidRequestUri,
+                    "invalid_request_object" => CoreAuthErrorResponseType::InvalidRequestObject,
+                    "request_not_supported" => CoreAuthErrorResponseType::RequestNotSupported,
+                    "request_uri_not_supported" => {
+                        CoreAuthErrorResponseType::RequestUriNotSupported
+                    }
+                    "registration_not_supported" => {
+                        CoreAuthErrorResponseType::RegistrationNotSupported
+                    }
+                    other => CoreAuthErrorResponseType::Extension(other.to_string()),
+                })
+            }
+        }
+        deserializer.deserialize_str(CoreAuthErrorResponseTypeVisitor {})
+    }
+}
+impl Serialize for CoreAuthErrorResponseType {
+    fn serialize<SE>(&self, serializer: SE) -> Result<SE::Ok, SE::Error>
+    where
+        SE: Serializer,
+    {
+        serializer.serialize_str(self.to_str())
+    }
+}
+impl Display for CoreAuthErrorResponseType {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        write!(f, "{}", self.to_str())
+    }
+}
+
 ///
 /// Whether the Authorization Server should prompt the End-User for reauthentication and
 /// consent.
@@ -427,7 +647,18 @@ pub enum CoreJweContentEncryptionAlgorithm {
     #[serde(rename = "A256GCM")]
     Aes256Gcm,
 }
-impl JweContentEncryptionAlgorithm for CoreJweContentEncryptionAlgorithm {}
+impl JweContentEncryptionAlgorithm for CoreJweContentEncryptionAlgorithm {
+    fn key_len(&self) -> usize {
+        match *self {
+            CoreJweContentEncryptionAlgorithm::Aes128CbcHmacSha256 => 32,
+            CoreJweContentEncryptionAlgorithm::Aes192CbcHmacSha384 => 48,
+            CoreJweContentEncryptionAlgorithm::Aes256CbcHmacSha512 => 64,
+            CoreJweContentEncryptionAlgorithm::Aes128Gcm => 16,
+            CoreJweContentEncryptionAlgorithm::Aes192Gcm => 24,
+            CoreJweContentEncryptionAlgorithm::Aes256Gcm => 32,
+        }
+    }
+}
 
 ///
 /// Core JWE key management algorithms.
@@ -525,7 +756,24 @@ pub enum CoreJweKeyManagementAlgorithm {
     #[serde(rename = "PBES2-HS512+A256KW")]
     PbEs2HmacSha512AesKeyWrap256,
 }
-impl JweKeyManagementAlgorithm for CoreJweKeyManagementAlgorithm {}
+impl JweKeyManagementAlgorithm for CoreJweKeyManagementAlgorithm {
+    fn symmetric_key_len<JE>(&self, content_encryption_alg: &JE) -> Option<usize>
+    where
+        JE: JweContentEncryptionAlgorithm,
+    {
+        match *self {
+            CoreJweKeyManagementAlgorithm::AesKeyWrap128
+            | CoreJweKeyManagementAlgorithm::Aes128Gcm => Some(16),
+            CoreJweKeyManagementAlgorithm::AesKeyWrap192
+            | CoreJweKeyManagementAlgorithm::Aes192Gcm => Some(24),
+            CoreJweKeyManagementAlgorithm::AesKeyWrap256
+            | CoreJweKeyManagementAlgorithm::Aes256Gcm => Some(32),
+            CoreJweKeyManagementAlgorithm::Direct => Some(content_encryption_alg.key_len()),
+            // PBES2 derives its key from a password of any length.
+            _ => None,
+        }
+    }
+}
 
 ///
 /// Core JWS signing algorithms.
@@ -568,12 +816,12 @@ pub enum CoreJwsSigningAlgorithm {
     #[serde(rename = "RS512")]
     RsaSsaPkcs1V15Sha512,
     ///
-    /// ECDSA using P-256 and SHA-256 (currently unsupported).
+    /// ECDSA using P-256 and SHA-256.
     ///
     #[serde(rename = "ES256")]
     EcdsaP256Sha256,
     ///
-    /// ECDSA using P-384 and SHA-384 (currently unsupported).
+    /// ECDSA using P-384 and SHA-384.
     ///
     #[serde(rename = "ES384")]
     EcdsaP384Sha384,
@@ -598,6 +846,13 @@ pub enum CoreJwsSigningAlgorithm {
     #[serde(rename = "PS512")]
     RsaSsaPssSha512,
     ///
+    /// EdDSA signature algorithms (currently only Ed25519 is supported).
+    ///
+    /// See [RFC 8037](https://tools.ietf.org/html/rfc8037).
+    ///
+    #[serde(rename = "EdDSA")]
+    EdDsa,
+    ///
     /// No digital signature or MAC performed.
     ///
     /// # Security Warning
@@ -626,6 +881,7 @@ impl JwsSigningAlgorithm<CoreJsonWebKeyType> for CoreJwsSigningAlgorithm {
             CoreJwsSigningAlgorithm::RsaSsaPssSha256 => CoreJsonWebKeyType::RSA,
             CoreJwsSigningAlgorithm::RsaSsaPssSha384 => CoreJsonWebKeyType::RSA,
             CoreJwsSigningAlgorithm::RsaSsaPssSha512 => CoreJsonWebKeyType::RSA,
+            CoreJwsSigningAlgorithm::EdDsa => CoreJsonWebKeyType::OctetKeyPair,
             CoreJwsSigningAlgorithm::None => {
                 return Err("signature algorithm `none` has no corresponding key type".to_string());
             }
@@ -643,6 +899,32 @@ impl JwsSigningAlgorithm<CoreJsonWebKeyType> for CoreJwsSigningAlgorithm {
     fn rsa_sha_256() -> Self {
         CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256
     }
+
+    fn hash_bytes(&self, bytes: &[u8]) -> Result<Vec<u8>, String> {
+        let digest_alg = match *self {
+            CoreJwsSigningAlgorithm::HmacSha256
+            | CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256
+            | CoreJwsSigningAlgorithm::RsaSsaPssSha256
+            | CoreJwsSigningAlgorithm::EcdsaP256Sha256 => &digest::SHA256,
+            CoreJwsSigningAlgorithm::HmacSha384
+            | CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha384
+            | CoreJwsSigningAlgorithm::RsaSsaPssSha384
+            | CoreJwsSigningAlgorithm::EcdsaP384Sha384 => &digest::SHA384,
+            // Ed25519 uses SHA-512 internally, which OpenID Connect adopts for EdDSA-signed
+            // ID tokens.
+            CoreJwsSigningAlgorithm::HmacSha512
+            | CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha512
+            | CoreJwsSigningAlgorithm::RsaSsaPssSha512
+            | CoreJwsSigningAlgorithm::EcdsaP521Sha512
+            | CoreJwsSigningAlgorithm::EdDsa => &digest::SHA512,
+            CoreJwsSigningAlgorithm::None => {
+                return Err(
+                    "signature algorithm `none` has no corresponding hash function".to_string(),
+                );
+            }
+        };
+        Ok(digest::digest(digest_alg, bytes).as_ref().to_vec())
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
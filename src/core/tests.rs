@@ -1,6 +1,6 @@
 use serde_json;
 
-use super::CoreGrantType;
+use super::{CoreAuthErrorResponseType, CoreGrantType};
 
 #[test]
 fn test_grant_type_serialize() {
@@ -22,3 +22,24 @@ fn test_grant_type_serialize() {
         serde_json::from_str::<CoreGrantType>(&serialized_ext).unwrap()
     );
 }
+
+#[test]
+fn test_auth_error_response_type_serialize() {
+    let serialized_login_required =
+        serde_json::to_string(&CoreAuthErrorResponseType::LoginRequired).unwrap();
+    assert_eq!("\"login_required\"", serialized_login_required);
+    let login_required =
+        serde_json::from_str::<CoreAuthErrorResponseType>(&serialized_login_required).unwrap();
+    assert_eq!(CoreAuthErrorResponseType::LoginRequired, login_required);
+    assert!(login_required.requires_interaction());
+    assert!(!CoreAuthErrorResponseType::AccessDenied.requires_interaction());
+
+    let ext = CoreAuthErrorResponseType::Extension("mfa_required".to_string());
+    let serialized_ext = serde_json::to_string(&ext).unwrap();
+    assert_eq!("\"mfa_required\"", serialized_ext);
+    assert_eq!(
+        ext,
+        serde_json::from_str::<CoreAuthErrorResponseType>(&serialized_ext).unwrap()
+    );
+    assert!(!ext.requires_interaction());
+}
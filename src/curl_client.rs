@@ -0,0 +1,79 @@
+use std::io::Read;
+
+use curl;
+use curl::easy::Easy;
+
+use super::http::{HttpClient, HttpRequest, HttpRequestMethod, HttpResponse};
+
+///
+/// Synchronous HTTP client backed by [libcurl](https://curl.haxx.se/libcurl/).
+///
+/// This client is only available when the `curl` feature is enabled (the default).
+///
+#[derive(Clone, Debug, Default)]
+pub struct CurlHttpClient;
+impl HttpClient for CurlHttpClient {
+    type Error = curl::Error;
+
+    fn request(&self, request: HttpRequest) -> Result<HttpResponse, curl::Error> {
+        let mut easy = Easy::new();
+        easy.url(&request.url.to_string()[..])?;
+        match request.method {
+            HttpRequestMethod::Get => {
+                // FIXME: make this a flag that gets passed in
+                trace!("GET {:?}", request.url);
+            }
+            HttpRequestMethod::Post => {
+                // FIXME: remove
+                trace!("POST {:?}", request.url);
+                easy.post(true)?;
+                easy.post_field_size(request.body.len() as u64)?;
+                // FIXME: remove
+                trace!("Body: {}", String::from_utf8_lossy(&request.body));
+            }
+        }
+
+        if !request.headers.is_empty() {
+            // FIXME: remove
+            trace!("Headers: {:?}", request.headers);
+            let mut headers = curl::easy::List::new();
+            request
+                .headers
+                .iter()
+                .map(|&(ref name, ref value)| headers.append(&format!("{}: {}", name, value)))
+                .skip_while(|res| res.is_ok())
+                .next()
+                .unwrap_or(Ok(()))?;
+            easy.http_headers(headers)?;
+        }
+
+        let mut response_body = Vec::new();
+        let mut post_body_slice = &request.body[..];
+        {
+            let mut transfer = easy.transfer();
+            transfer.read_function(|buf| Ok(post_body_slice.read(buf).unwrap_or(0)))?;
+
+            transfer.write_function(|new_data| {
+                response_body.extend_from_slice(new_data);
+                Ok(new_data.len())
+            })?;
+
+            transfer.perform()?;
+        }
+
+        let response = HttpResponse {
+            status_code: easy.response_code()?,
+            // Section 3.1.1.1 of RFC 7231 indicates that media types are case insensitive.
+            content_type: easy.content_type()?.map(|s| s.to_lowercase().to_string()),
+            body: response_body,
+        };
+        // FIXME: remove
+        trace!(
+            "Response: status_code={}, content_type=`{:?}`, body=`{}`",
+            response.status_code,
+            response.content_type,
+            String::from_utf8_lossy(&response.body)
+        );
+        Ok(response)
+    }
+}
@@ -2,7 +2,8 @@ use std::fmt::Debug;
 use std::marker::PhantomData;
 use std::ops::Deref;
 
-use curl;
+use failure::Fail;
+use futures::{future, Future};
 use oauth2::{AuthUrl, Scope, TokenUrl};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
@@ -10,21 +11,26 @@ use serde_json;
 use url;
 use url::Url;
 
-use super::http::{HttpRequest, HttpRequestMethod, ACCEPT_JSON, HTTP_STATUS_OK, MIME_TYPE_JSON};
+use super::http::{
+    accept_json, AsyncHttpClient, HttpClient, HttpRequest, HttpRequestMethod, HttpResponse,
+    HTTP_STATUS_OK, MIME_TYPE_JSON,
+};
 use super::macros::TraitStructExtract;
 use super::types::{
     AuthDisplay, AuthenticationContextClass, ClaimName, ClaimType, ClientAuthMethod, GrantType,
     IssuerUrl, JsonWebKey, JsonWebKeySet, JsonWebKeyType, JsonWebKeyUse,
     JweContentEncryptionAlgorithm, JweKeyManagementAlgorithm, JwsSigningAlgorithm, LanguageTag,
-    OpPolicyUrl, OpTosUrl, RegistrationUrl, ResponseMode, ResponseType, ResponseTypes,
-    ServiceDocUrl, SubjectIdentifierType,
+    OpPolicyUrl, OpTosUrl, PkceCodeChallengeMethod, RegistrationUrl, ResponseMode, ResponseType,
+    ResponseTypes, ServiceDocUrl, SubjectIdentifierType,
 };
 use super::{UserInfoUrl, CONFIG_URL_SUFFIX};
 
-pub fn get_provider_metadata<PM, AD, CA, CN, CT, G, JE, JK, JS, JT, RM, RT, S>(
+pub fn get_provider_metadata<HC, PM, AD, CA, CN, CT, G, JE, JK, JS, JT, RM, RT, S>(
     issuer_url: &IssuerUrl,
-) -> Result<PM, DiscoveryError>
+    http_client: &HC,
+) -> Result<PM, DiscoveryError<HC::Error>>
 where
+    HC: HttpClient,
     AD: AuthDisplay,
     CA: ClientAuthMethod,
     CN: ClaimName,
@@ -38,18 +44,90 @@ where
     RT: ResponseType,
     S: SubjectIdentifierType,
     PM: ProviderMetadata<AD, CA, CN, CT, G, JE, JK, JS, JT, RM, RT, S>,
+{
+    let discover_response = http_client
+        .request(discovery_request(issuer_url)?)
+        .map_err(DiscoveryError::Request)?;
+
+    provider_metadata_response(issuer_url, discover_response)
+}
+
+///
+/// Asynchronously fetches the OpenID Provider metadata for the specified issuer.
+///
+/// This is the asynchronous counterpart of `get_provider_metadata()`.
+///
+pub fn get_provider_metadata_async<'a, HC, PM, AD, CA, CN, CT, G, JE, JK, JS, JT, RM, RT, S>(
+    issuer_url: &IssuerUrl,
+    http_client: &HC,
+) -> Box<Future<Item = PM, Error = DiscoveryError<HC::Error>> + 'a>
+where
+    HC: AsyncHttpClient,
+    HC::Future: 'a,
+    AD: AuthDisplay,
+    CA: ClientAuthMethod,
+    CN: ClaimName,
+    CT: ClaimType,
+    G: GrantType,
+    JE: JweContentEncryptionAlgorithm,
+    JK: JweKeyManagementAlgorithm,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    RM: ResponseMode,
+    RT: ResponseType,
+    S: SubjectIdentifierType,
+    PM: ProviderMetadata<AD, CA, CN, CT, G, JE, JK, JS, JT, RM, RT, S> + 'a,
+{
+    let discover_request = match discovery_request(issuer_url) {
+        Ok(discover_request) => discover_request,
+        Err(err) => return Box::new(future::err(err)),
+    };
+    let issuer_url = issuer_url.clone();
+    Box::new(
+        http_client
+            .request(discover_request)
+            .map_err(DiscoveryError::Request)
+            .and_then(move |discover_response| {
+                provider_metadata_response(&issuer_url, discover_response)
+            }),
+    )
+}
+
+fn discovery_request<RE>(issuer_url: &IssuerUrl) -> Result<HttpRequest, DiscoveryError<RE>>
+where
+    RE: Fail,
 {
     let discover_url = issuer_url
         .join(CONFIG_URL_SUFFIX)
         .map_err(DiscoveryError::UrlParse)?;
-    let discover_response = HttpRequest {
-        url: &discover_url,
+    Ok(HttpRequest {
+        url: discover_url,
         method: HttpRequestMethod::Get,
-        headers: &vec![ACCEPT_JSON],
-        post_body: &vec![],
-    }.request()
-    .map_err(DiscoveryError::Request)?;
+        headers: vec![accept_json()],
+        body: vec![],
+    })
+}
 
+fn provider_metadata_response<PM, AD, CA, CN, CT, G, JE, JK, JS, JT, RM, RT, S, RE>(
+    issuer_url: &IssuerUrl,
+    discover_response: HttpResponse,
+) -> Result<PM, DiscoveryError<RE>>
+where
+    AD: AuthDisplay,
+    CA: ClientAuthMethod,
+    CN: ClaimName,
+    CT: ClaimType,
+    G: GrantType,
+    JE: JweContentEncryptionAlgorithm,
+    JK: JweKeyManagementAlgorithm,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    RM: ResponseMode,
+    RT: ResponseType,
+    S: SubjectIdentifierType,
+    PM: ProviderMetadata<AD, CA, CN, CT, G, JE, JK, JS, JT, RM, RT, S>,
+    RE: Fail,
+{
     // FIXME: improve error handling (i.e., is there a body response?)
     if discover_response.status_code != HTTP_STATUS_OK {
         return Err(DiscoveryError::Response(
@@ -85,7 +163,8 @@ trait_struct![
         S: SubjectIdentifierType,
     ] : [Clone + Debug + DeserializeOwned + PartialEq + Serialize] {
         // consumes self so that, if validation fails, it doesn't get used
-        fn validate(self, issuer_uri: &IssuerUrl) -> Result<Self, DiscoveryError> {
+        fn validate<RE>(self, issuer_uri: &IssuerUrl) -> Result<Self, DiscoveryError<RE>>
+        where RE: Fail {
             if self.issuer() != issuer_uri {
                 return Err(
                     DiscoveryError::Validation(
@@ -185,6 +264,9 @@ trait_struct![
         op_policy_uri(Option<&OpPolicyUrl>) <- Option<OpPolicyUrl>,
         #[serde(skip_serializing_if="Option::is_none")]
         op_tos_uri(Option<&OpTosUrl>) <- Option<OpTosUrl>,
+        #[serde(skip_serializing_if="Option::is_none")]
+        code_challenge_methods_supported(Option<&Vec<PkceCodeChallengeMethod>>)
+            <- Option<Vec<PkceCodeChallengeMethod>>,
         // FIXME: remove trait method
         #[serde(skip)]
         _phantom_jt(PhantomData<JT>) <- PhantomData<JT>,
@@ -208,11 +290,14 @@ trait_struct![
 
 // FIXME: clean up Display/Debug/cause for this and other Fail impls
 #[derive(Debug, Fail)]
-pub enum DiscoveryError {
+pub enum DiscoveryError<RE>
+where
+    RE: Fail,
+{
     #[fail(display = "URL parse error: {}", _0)]
     UrlParse(url::ParseError),
     #[fail(display = "Request error: {}", _0)]
-    Request(curl::Error),
+    Request(RE),
     #[fail(display = "Response error (status={}): {}", _0, _1)]
     Response(u32, String),
     #[fail(display = "JSON error: {}", _0)]
@@ -228,39 +313,79 @@ new_url_type![
     impl {
         // FIXME: don't depend on super::discovery in this module (factor this out into some kind
         // of HttpError?
-        pub fn get_keys<JS, JT, JU, K>(
-            &self
-        ) -> Result<JsonWebKeySet<JS, JT, JU, K>, DiscoveryError>
-        where JS: JwsSigningAlgorithm<JT>,
+        pub fn get_keys<HC, JS, JT, JU, K>(
+            &self,
+            http_client: &HC,
+        ) -> Result<JsonWebKeySet<JS, JT, JU, K>, DiscoveryError<HC::Error>>
+        where HC: HttpClient,
+                JS: JwsSigningAlgorithm<JT>,
                 JT: JsonWebKeyType,
                 JU: JsonWebKeyUse,
                 K: JsonWebKey<JS, JT, JU> {
             let key_response =
-                HttpRequest {
-                    url: &self.0,
-                    method: HttpRequestMethod::Get,
-                    headers: &vec![ACCEPT_JSON],
-                    post_body: &vec![],
-                }
-                .request()
-            .map_err(DiscoveryError::Request)?;
-
-            // FIXME: improve error handling (i.e., is there a body response?)
-            // possibly consolidate this error handling with discovery::get_provider_metadata().
-            if key_response.status_code != HTTP_STATUS_OK {
-                return Err(
-                    DiscoveryError::Response(
-                        key_response.status_code,
-                        "unexpected HTTP status code".to_string()
-                    )
-                );
-            }
+                http_client
+                    .request(jwks_request(self.url()))
+                    .map_err(DiscoveryError::Request)?;
 
-            key_response
-                .check_content_type(MIME_TYPE_JSON)
-                .map_err(|err_msg| DiscoveryError::Response(key_response.status_code, err_msg))?;
+            jwks_response(key_response)
+        }
 
-            serde_json::from_slice(&key_response.body).map_err(DiscoveryError::Json)
+        ///
+        /// Asynchronously fetches the JSON Web Key Set.
+        ///
+        /// This is the asynchronous counterpart of `get_keys()`.
+        ///
+        pub fn get_keys_async<'a, HC, JS, JT, JU, K>(
+            &self,
+            http_client: &HC,
+        ) -> Box<Future<Item = JsonWebKeySet<JS, JT, JU, K>, Error = DiscoveryError<HC::Error>> + 'a>
+        where HC: AsyncHttpClient,
+                HC::Future: 'a,
+                JS: JwsSigningAlgorithm<JT> + 'a,
+                JT: JsonWebKeyType + 'a,
+                JU: JsonWebKeyUse + 'a,
+                K: JsonWebKey<JS, JT, JU> + 'a {
+            Box::new(
+                http_client
+                    .request(jwks_request(self.url()))
+                    .map_err(DiscoveryError::Request)
+                    .and_then(jwks_response)
+            )
         }
     }
 ];
+
+fn jwks_request(jwks_url: &Url) -> HttpRequest {
+    HttpRequest {
+        url: jwks_url.clone(),
+        method: HttpRequestMethod::Get,
+        headers: vec![accept_json()],
+        body: vec![],
+    }
+}
+
+fn jwks_response<JS, JT, JU, K, RE>(
+    key_response: HttpResponse,
+) -> Result<JsonWebKeySet<JS, JT, JU, K>, DiscoveryError<RE>>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    RE: Fail,
+{
+    // FIXME: improve error handling (i.e., is there a body response?)
+    // possibly consolidate this error handling with discovery::get_provider_metadata().
+    if key_response.status_code != HTTP_STATUS_OK {
+        return Err(DiscoveryError::Response(
+            key_response.status_code,
+            "unexpected HTTP status code".to_string(),
+        ));
+    }
+
+    key_response
+        .check_content_type(MIME_TYPE_JSON)
+        .map_err(|err_msg| DiscoveryError::Response(key_response.status_code, err_msg))?;
+
+    serde_json::from_slice(&key_response.body).map_err(DiscoveryError::Json)
+}
@@ -1,38 +1,158 @@
-use std::io::Read;
-
-use curl;
-use curl::easy::Easy;
+use failure::Fail;
+use futures::Future;
 use oauth2::prelude::*;
 use oauth2::AccessToken;
 use url::Url;
 
+pub const MIME_TYPE_FORM_URLENCODED: &str = "application/x-www-form-urlencoded";
 pub const MIME_TYPE_JSON: &str = "application/json";
 pub const MIME_TYPE_JWT: &str = "application/jwt";
 
 // Request headers
-pub const ACCEPT_JSON: (&str, &str) = ("Accept", MIME_TYPE_JSON);
+pub const ACCEPT: &str = "Accept";
 pub const AUTHORIZATION: &str = "Authorization";
+pub const BASIC: &str = "Basic";
 pub const BEARER: &str = "Bearer";
-pub const CONTENT_TYPE_JSON: (&str, &str) = ("Content-Type", MIME_TYPE_JSON);
+pub const CONTENT_TYPE: &str = "Content-Type";
 
 // Response status codes
 pub const HTTP_STATUS_OK: u32 = 200;
 pub const HTTP_STATUS_CREATED: u32 = 201;
 pub const HTTP_STATUS_BAD_REQUEST: u32 = 400;
 
-#[derive(Debug)]
+///
+/// HTTP client used for communicating with the OpenID Connect Provider.
+///
+/// Implementing this trait allows applications to supply their own HTTP transport (e.g., to
+/// configure proxies, TLS settings, or timeouts). A [libcurl](https://curl.haxx.se/libcurl/)-based
+/// implementation is provided by `CurlHttpClient` when the `curl` feature is enabled (the
+/// default).
+///
+pub trait HttpClient {
+    ///
+    /// Error returned when the request could not be completed (e.g., due to a network failure).
+    ///
+    type Error: Fail;
+
+    ///
+    /// Sends the given request and returns the corresponding response.
+    ///
+    /// HTTP error status codes (4xx, 5xx) should be returned as successful responses; only
+    /// transport-level failures should be reported as errors.
+    ///
+    fn request(&self, request: HttpRequest) -> Result<HttpResponse, Self::Error>;
+}
+
+///
+/// Asynchronous HTTP client used for communicating with the OpenID Connect Provider.
+///
+/// This is the asynchronous counterpart of `HttpClient` and is used by the `*_async` methods
+/// throughout this crate. Implementations are typically thin wrappers around an asynchronous HTTP
+/// library such as `hyper`.
+///
+pub trait AsyncHttpClient {
+    ///
+    /// Error returned when the request could not be completed (e.g., due to a network failure).
+    ///
+    type Error: Fail;
+    ///
+    /// Future resolving to the HTTP response.
+    ///
+    type Future: Future<Item = HttpResponse, Error = Self::Error>;
+
+    ///
+    /// Sends the given request and returns a future resolving to the corresponding response.
+    ///
+    /// HTTP error status codes (4xx, 5xx) should be returned as successful responses; only
+    /// transport-level failures should be reported as errors.
+    ///
+    fn request(&self, request: HttpRequest) -> Self::Future;
+}
+
+///
+/// HTTP request method.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum HttpRequestMethod {
+    ///
+    /// HTTP `GET` method.
+    ///
+    Get,
+    ///
+    /// HTTP `POST` method.
+    ///
+    Post,
+}
+impl Default for HttpRequestMethod {
+    fn default() -> HttpRequestMethod {
+        HttpRequestMethod::Get
+    }
+}
+
+///
+/// HTTP request sent to the OpenID Connect Provider.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct HttpRequest {
+    ///
+    /// URL to which the request is sent.
+    ///
+    pub url: Url,
+    ///
+    /// HTTP request method.
+    ///
+    pub method: HttpRequestMethod,
+    ///
+    /// HTTP request headers as `(name, value)` pairs.
+    ///
+    pub headers: Vec<(String, String)>,
+    ///
+    /// HTTP request body (empty for `GET` requests).
+    ///
+    pub body: Vec<u8>,
+}
+
+///
+/// HTTP response returned by an `HttpClient`.
+///
+#[derive(Clone, Debug, PartialEq)]
 pub struct HttpResponse {
+    ///
+    /// HTTP status code.
+    ///
     pub status_code: u32,
+    ///
+    /// Value of the `Content-Type` response header, if present.
+    ///
     pub content_type: Option<String>,
+    ///
+    /// HTTP response body.
+    ///
     pub body: Vec<u8>,
 }
 impl HttpResponse {
-    pub fn check_content_type(&self, expected_content_type: &str) -> Result<(), String> {
+    // Returns the media type of the `Content-Type` header, normalized to lowercase and without
+    // any parameters (e.g., `application/json` for `Application/JSON; charset=utf-8`).
+    pub(crate) fn media_type(&self) -> Option<String> {
+        self.content_type.as_ref().map(|content_type| {
+            content_type
+                .split(';')
+                .next()
+                .unwrap_or("")
+                .trim()
+                .to_lowercase()
+        })
+    }
+
+    pub(crate) fn check_content_type(&self, expected_content_type: &str) -> Result<(), String> {
         if let Some(ref content_type) = self.content_type {
-            // Section 3.1.1.1 of RFC 7231 indicates that media types may be followed by
-            // optional whitespace and/or a parameter (e.g., charset).
+            // Section 3.1.1.1 of RFC 7231 indicates that media types are case insensitive and
+            // may be followed by optional whitespace and/or a parameter (e.g., charset).
             // See https://tools.ietf.org/html/rfc7231#section-3.1.1.1.
-            if !content_type.starts_with(expected_content_type) {
+            if !content_type
+                .to_lowercase()
+                .starts_with(expected_content_type)
+            {
                 Err(format!(
                     "Unexpected response Content-Type: `{}`, should be `{}`",
                     content_type, expected_content_type
@@ -46,93 +166,17 @@ impl HttpResponse {
     }
 }
 
-#[derive(Debug, PartialEq)]
-pub enum HttpRequestMethod {
-    Get,
-    Post,
+pub(crate) fn accept_json() -> (String, String) {
+    (ACCEPT.to_string(), MIME_TYPE_JSON.to_string())
 }
-impl Default for HttpRequestMethod {
-    fn default() -> HttpRequestMethod {
-        HttpRequestMethod::Get
-    }
+
+pub(crate) fn content_type_json() -> (String, String) {
+    (CONTENT_TYPE.to_string(), MIME_TYPE_JSON.to_string())
 }
 
-pub fn auth_bearer(access_token: &AccessToken) -> (&str, String) {
+pub(crate) fn auth_bearer(access_token: &AccessToken) -> (String, String) {
     (
-        AUTHORIZATION,
+        AUTHORIZATION.to_string(),
         format!("{} {}", BEARER, access_token.secret()),
     )
 }
-
-#[derive(Debug)]
-pub struct HttpRequest<'a> {
-    pub url: &'a Url,
-    pub method: HttpRequestMethod,
-    pub headers: &'a Vec<(&'a str, &'a str)>,
-    pub post_body: &'a Vec<u8>,
-}
-impl<'a> HttpRequest<'a> {
-    pub fn request(self) -> Result<HttpResponse, curl::Error> {
-        let mut easy = Easy::new();
-        easy.url(&self.url.to_string()[..])?;
-        match self.method {
-            HttpRequestMethod::Get => {
-                // FIXME: make this a flag that gets passed in
-                trace!("GET {:?}", self.url);
-            }
-            HttpRequestMethod::Post => {
-                // FIXME: remove
-                trace!("POST {:?}", self.url);
-                easy.post(true)?;
-                easy.post_field_size(self.post_body.len() as u64)?;
-                // FIXME: remove
-                trace!(
-                    "Body: {}",
-                    String::from_utf8(self.post_body.to_vec()).unwrap()
-                );
-            }
-        }
-
-        if !self.headers.is_empty() {
-            // FIXME: remove
-            trace!("Headers: {:?}", self.headers);
-            let mut headers = curl::easy::List::new();
-            self.headers
-                .iter()
-                .map(|&(name, value)| headers.append(&format!("{}: {}", name, value)))
-                .skip_while(|res| res.is_ok())
-                .next()
-                .unwrap_or(Ok(()))?;
-            easy.http_headers(headers)?;
-        }
-
-        let mut response_body = Vec::new();
-        let mut post_body_slice = &self.post_body[..];
-        {
-            let mut transfer = easy.transfer();
-            transfer.read_function(|buf| Ok(post_body_slice.read(buf).unwrap_or(0)))?;
-
-            transfer.write_function(|new_data| {
-                response_body.extend_from_slice(new_data);
-                Ok(new_data.len())
-            })?;
-
-            transfer.perform()?;
-        }
-
-        let response = HttpResponse {
-            status_code: easy.response_code()?,
-            // Section 3.1.1.1 of RFC 7231 indicates that media types are case insensitive.
-            content_type: easy.content_type()?.map(|s| s.to_lowercase().to_string()),
-            body: response_body,
-        };
-        // FIXME: remove
-        trace!(
-            "Response: status_code={}, content_type=`{:?}`, body=`{}`",
-            response.status_code,
-            response.content_type,
-            String::from_utf8(response.body.to_vec()).unwrap()
-        );
-        Ok(response)
-    }
-}
@@ -2,10 +2,13 @@ use std::collections::HashMap;
 use std::marker::PhantomData;
 
 use chrono::{DateTime, Utc};
-use oauth2::ClientId;
+use oauth2::{AccessToken, AuthorizationCode, ClientId};
+use serde::de::value::Error as DeserializeValueError;
+use serde::de::IntoDeserializer;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use super::claims::StandardClaimsImpl;
-use super::jwt::JsonWebTokenJsonPayloadDeserializer;
+use super::jwt::{JsonWebEncryption, JsonWebTokenJsonPayloadDeserializer};
 use super::types::helpers::{deserialize_string_or_vec, seconds_to_utc};
 use super::types::Seconds;
 use super::{
@@ -15,24 +18,39 @@ use super::{
     EndUserMiddleName, EndUserName, EndUserNickname, EndUserPhoneNumber, EndUserPictureUrl,
     EndUserProfileUrl, EndUserTimezone, EndUserUsername, EndUserWebsiteUrl, ExtraTokenFields,
     GenderClaim, IdTokenVerifier, IssuerClaim, IssuerUrl, JsonWebKey, JsonWebKeyType,
-    JsonWebKeyUse, JsonWebToken, JweContentEncryptionAlgorithm, JwsSigningAlgorithm, LanguageTag,
-    Nonce, StandardClaims, SubjectIdentifier,
+    JsonWebKeyUse, JsonWebToken, JsonWebTokenAccess, JsonWebTokenAlgorithm, JsonWebTokenBuilder,
+    JsonWebTokenError, JsonWebTokenId, JweContentEncryptionAlgorithm, JweKeyManagementAlgorithm,
+    JwsSigningAlgorithm, LanguageTag, Nonce, NonceVerifier, PrivateDecryptionKey,
+    PrivateSigningKey, StandardClaims, SubjectIdentifier,
 };
 
 // This wrapper layer exists instead of directly verifying the JWT and returning the claims so that
 // we can pass it around and easily access a serialized JWT representation of it (e.g., for passing
 // to the authorization endpoint as an id_token_hint).
-#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct IdToken<
     AC: AdditionalClaims,
     GC: GenderClaim,
     JE: JweContentEncryptionAlgorithm,
     JS: JwsSigningAlgorithm<JT>,
     JT: JsonWebKeyType,
->(
-    #[serde(bound = "AC: AdditionalClaims")]
-    JsonWebToken<IdTokenClaims<AC, GC>, JE, JS, JT, JsonWebTokenJsonPayloadDeserializer>,
-);
+>(IdTokenJwt<AC, GC, JE, JS, JT>);
+
+// ID tokens are signed JWTs, which may also be encrypted, producing a JWE that nests the signed
+// JWT (see Section 2 of OpenID Connect Core 1.0). We keep track of whether a signed JWT came from
+// decrypting a JWE so that verifiers expecting encrypted ID tokens can reject unencrypted ones.
+#[derive(Clone, Debug, PartialEq)]
+enum IdTokenJwt<
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+> {
+    Signed(JsonWebToken<IdTokenClaims<AC, GC>, JE, JS, JT, JsonWebTokenJsonPayloadDeserializer>),
+    Encrypted(JsonWebEncryption),
+    Decrypted(JsonWebToken<IdTokenClaims<AC, GC>, JE, JS, JT, JsonWebTokenJsonPayloadDeserializer>),
+}
 impl<AC, GC, JE, JS, JT> IdToken<AC, GC, JE, JS, JT>
 where
     AC: AdditionalClaims,
@@ -41,16 +59,377 @@ where
     JS: JwsSigningAlgorithm<JT>,
     JT: JsonWebKeyType,
 {
-    pub fn claims<JU, K>(
+    ///
+    /// Creates a new ID token by signing the given claims with the given key and signature
+    /// algorithm.
+    ///
+    pub fn new<JU, K, S>(
+        claims: IdTokenClaims<AC, GC>,
+        signing_key: &S,
+        signature_alg: &JS,
+    ) -> Result<Self, JsonWebTokenError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        S: PrivateSigningKey<JS, JT, JU, K>,
+    {
+        JsonWebTokenBuilder::new(claims)
+            .sign(signing_key, signature_alg)
+            .map(|jwt| IdToken(IdTokenJwt::Signed(jwt)))
+    }
+
+    ///
+    /// Returns the compact serialization of this ID token, as returned by the provider. For ID
+    /// tokens returned by [`decrypt`](#method.decrypt), this is the serialization of the signed
+    /// JWT nested within the encrypted ID token.
+    ///
+    pub fn raw_token(&self) -> &str {
+        match self.0 {
+            IdTokenJwt::Signed(ref jwt) | IdTokenJwt::Decrypted(ref jwt) => jwt.raw_token(),
+            IdTokenJwt::Encrypted(ref jwe) => jwe.raw_token(),
+        }
+    }
+
+    ///
+    /// Returns whether this ID token is encrypted and must be passed to
+    /// [`decrypt`](#method.decrypt) before its claims can be verified.
+    ///
+    pub fn is_encrypted(&self) -> bool {
+        if let IdTokenJwt::Encrypted(_) = self.0 {
+            true
+        } else {
+            false
+        }
+    }
+
+    ///
+    /// Decrypts an encrypted ID token using the verifier's decryption keys (see
+    /// [`IdTokenVerifier::set_decryption_keys`]) and returns the signed ID token nested within
+    /// it. The claims of the returned ID token may then be verified using
+    /// [`claims`](#method.claims).
+    ///
+    /// Unencrypted ID tokens are returned as is, unless the verifier has decryption keys and
+    /// therefore requires ID tokens to be encrypted.
+    ///
+    pub fn decrypt<JK, JU, K>(
+        &self,
+        verifier: &IdTokenVerifier<JS, JT, JU, K>,
+    ) -> Result<Self, ClaimsVerificationError>
+    where
+        JK: JweKeyManagementAlgorithm,
+        JU: JsonWebKeyUse,
+        K: PrivateDecryptionKey<JE, JK, JS, JT, JU>,
+    {
+        let jwe = match self.0 {
+            IdTokenJwt::Signed(_) => {
+                return if verifier.is_decryption_enabled() {
+                    Err(ClaimsVerificationError::NoEncryption)
+                } else {
+                    Ok(self.clone())
+                }
+            }
+            IdTokenJwt::Decrypted(_) => return Ok(self.clone()),
+            IdTokenJwt::Encrypted(ref jwe) => jwe,
+        };
+
+        let (jose_header, plaintext) = verifier.decrypt::<JE, JK>(jwe)?;
+        if let Some(ref content_type) = jose_header.cty {
+            if content_type.to_uppercase() != "JWT" {
+                return Err(ClaimsVerificationError::Unsupported(format!(
+                    "unexpected or unsupported JWE content type `{}`",
+                    **content_type
+                )));
+            }
+        }
+
+        let nested_jwt = String::from_utf8(plaintext).map_err(|_| {
+            ClaimsVerificationError::Other("decrypted ID token is not valid UTF-8".to_string())
+        })?;
+        JsonWebToken::deserialize(
+            IntoDeserializer::<DeserializeValueError>::into_deserializer(nested_jwt),
+        ).map(|jwt| IdToken(IdTokenJwt::Decrypted(jwt)))
+        .map_err(|err| {
+            ClaimsVerificationError::Other(format!("failed to parse decrypted ID token: {}", err))
+        })
+    }
+
+    ///
+    /// Verifies and returns the ID token claims.
+    ///
+    /// The `nonce` claim is verified using `nonce_verifier`, which is typically the `Nonce` sent
+    /// in the authentication request but may also be a custom verification function (see
+    /// [`NonceVerifier`]).
+    ///
+    /// Encrypted ID tokens must first be decrypted using [`decrypt`](#method.decrypt).
+    ///
+    pub fn claims<JU, K, N>(
+        &self,
+        verifier: &IdTokenVerifier<JS, JT, JU, K>,
+        nonce_verifier: N,
+    ) -> Result<&IdTokenClaims<AC, GC>, ClaimsVerificationError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        N: NonceVerifier,
+    {
+        self.verified_claims(verifier, nonce_verifier, None, None)
+    }
+
+    ///
+    /// Verifies and returns the ID token claims, and verifies that the access token hash
+    /// (`at_hash`) claim matches the given access token.
+    ///
+    /// This should be used whenever the access token is returned from the authorization endpoint
+    /// along with the ID token (i.e., the `id_token token` and `code id_token token` response
+    /// types), in which case the `at_hash` claim is required (see
+    /// [Section 3.2.2.9](
+    ///     https://openid.net/specs/openid-connect-core-1_0.html#ImplicitTokenValidation) and
+    /// [Section 3.3.2.11](
+    ///     https://openid.net/specs/openid-connect-core-1_0.html#HybridIDToken2) of OpenID
+    /// Connect Core 1.0). With the `code id_token token` response type, use
+    /// [`claims_with_code_and_access_token`](#method.claims_with_code_and_access_token) instead.
+    ///
+    pub fn claims_with_access_token<JU, K, N>(
+        &self,
+        verifier: &IdTokenVerifier<JS, JT, JU, K>,
+        nonce_verifier: N,
+        access_token: &AccessToken,
+    ) -> Result<&IdTokenClaims<AC, GC>, ClaimsVerificationError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        N: NonceVerifier,
+    {
+        self.verified_claims(verifier, nonce_verifier, None, Some(access_token))
+    }
+
+    ///
+    /// Verifies and returns the ID token claims, and verifies that the authorization code hash
+    /// (`c_hash`) claim matches the given authorization code.
+    ///
+    /// This should be used for ID tokens returned from the authorization endpoint along with an
+    /// authorization code (i.e., the `code id_token` response type of the hybrid flow), in which
+    /// case the `c_hash` claim is required (see
+    /// [Section 3.3.2.10 of OpenID Connect Core 1.0](
+    ///     https://openid.net/specs/openid-connect-core-1_0.html#CodeValidation)). With the
+    /// `code id_token token` response type, use
+    /// [`claims_with_code_and_access_token`](#method.claims_with_code_and_access_token) instead.
+    ///
+    pub fn claims_with_code<JU, K, N>(
+        &self,
+        verifier: &IdTokenVerifier<JS, JT, JU, K>,
+        nonce_verifier: N,
+        code: &AuthorizationCode,
+    ) -> Result<&IdTokenClaims<AC, GC>, ClaimsVerificationError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        N: NonceVerifier,
+    {
+        self.verified_claims(verifier, nonce_verifier, Some(code), None)
+    }
+
+    ///
+    /// Verifies and returns the ID token claims, and verifies that both the authorization code
+    /// hash (`c_hash`) and access token hash (`at_hash`) claims match the given authorization
+    /// code and access token, respectively.
+    ///
+    /// This should be used for ID tokens returned from the authorization endpoint with the
+    /// `code id_token token` response type of the hybrid flow, in which case both claims are
+    /// required.
+    ///
+    pub fn claims_with_code_and_access_token<JU, K, N>(
+        &self,
+        verifier: &IdTokenVerifier<JS, JT, JU, K>,
+        nonce_verifier: N,
+        code: &AuthorizationCode,
+        access_token: &AccessToken,
+    ) -> Result<&IdTokenClaims<AC, GC>, ClaimsVerificationError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        N: NonceVerifier,
+    {
+        self.verified_claims(verifier, nonce_verifier, Some(code), Some(access_token))
+    }
+
+    ///
+    /// Verifies and returns the claims of an ID token returned in response to a refresh request,
+    /// given the previously verified claims of the original ID token.
+    ///
+    /// In addition to the usual verification, the `iss`, `sub`, `aud`, and `azp` claims must
+    /// match the original ID token, as must any `auth_time` claim. The `nonce` claim is
+    /// optional, but must match the original nonce if present (see
+    /// [Section 12.2 of OpenID Connect Core 1.0](
+    ///     https://openid.net/specs/openid-connect-core-1_0.html#RefreshTokenResponse)). The
+    /// verifier's [maximum authentication age](struct.IdTokenVerifier.html#method.set_max_auth_age)
+    /// is not enforced, since it applies to the original authentication.
+    ///
+    pub fn claims_after_refresh<JU, K>(
+        &self,
+        verifier: &IdTokenVerifier<JS, JT, JU, K>,
+        original_claims: &IdTokenClaims<AC, GC>,
+    ) -> Result<&IdTokenClaims<AC, GC>, ClaimsVerificationError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+    {
+        verifier.verified_refreshed_claims(self.signed_jwt(verifier)?, original_claims)
+    }
+
+    fn signed_jwt<JU, K>(
+        &self,
+        verifier: &IdTokenVerifier<JS, JT, JU, K>,
+    ) -> Result<
+        &JsonWebToken<IdTokenClaims<AC, GC>, JE, JS, JT, JsonWebTokenJsonPayloadDeserializer>,
+        ClaimsVerificationError,
+    >
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+    {
+        match self.0 {
+            IdTokenJwt::Signed(ref jwt) => {
+                if verifier.is_decryption_enabled() {
+                    return Err(ClaimsVerificationError::NoEncryption);
+                }
+                Ok(jwt)
+            }
+            IdTokenJwt::Decrypted(ref jwt) => Ok(jwt),
+            IdTokenJwt::Encrypted(_) => Err(ClaimsVerificationError::Unsupported(
+                "encrypted ID tokens must be decrypted before verifying their claims".to_string(),
+            )),
+        }
+    }
+
+    // The token hashes are verified before the verifier consumes the ID token's nonce and JWT ID
+    // (if a replay cache is set), so that an ID token failing these checks isn't marked as used.
+    fn verified_claims<JU, K, N>(
         &self,
         verifier: &IdTokenVerifier<JS, JT, JU, K>,
-        nonce: &Nonce,
+        nonce_verifier: N,
+        code: Option<&AuthorizationCode>,
+        access_token: Option<&AccessToken>,
     ) -> Result<&IdTokenClaims<AC, GC>, ClaimsVerificationError>
     where
         JU: JsonWebKeyUse,
         K: JsonWebKey<JS, JT, JU>,
+        N: NonceVerifier,
+    {
+        let jwt = self.signed_jwt(verifier)?;
+        verifier.verified_claims(jwt, nonce_verifier, |claims| {
+            if let Some(code) = code {
+                Self::verify_code_hash(jwt, claims, code)?;
+            }
+            if let Some(access_token) = access_token {
+                Self::verify_access_token_hash(jwt, claims, access_token)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn verify_access_token_hash(
+        jwt: &JsonWebToken<IdTokenClaims<AC, GC>, JE, JS, JT, JsonWebTokenJsonPayloadDeserializer>,
+        claims: &IdTokenClaims<AC, GC>,
+        access_token: &AccessToken,
+    ) -> Result<(), ClaimsVerificationError> {
+        let expected_hash = claims.access_token_hash().ok_or_else(|| {
+            ClaimsVerificationError::InvalidAccessTokenHash("missing `at_hash` claim".to_string())
+        })?;
+        // The hash function is determined by the ID token's JWS signature algorithm.
+        let signature_alg = Self::signature_alg(jwt).ok_or_else(|| {
+            ClaimsVerificationError::InvalidAccessTokenHash("ID token must be signed".to_string())
+        })?;
+        let actual_hash = AccessTokenHash::from_token(access_token, signature_alg)
+            .map_err(|err| ClaimsVerificationError::InvalidAccessTokenHash(format!("{}", err)))?;
+        if actual_hash != *expected_hash {
+            return Err(ClaimsVerificationError::InvalidAccessTokenHash(
+                "access token hash does not match".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn verify_code_hash(
+        jwt: &JsonWebToken<IdTokenClaims<AC, GC>, JE, JS, JT, JsonWebTokenJsonPayloadDeserializer>,
+        claims: &IdTokenClaims<AC, GC>,
+        code: &AuthorizationCode,
+    ) -> Result<(), ClaimsVerificationError> {
+        let expected_hash = claims.code_hash().ok_or_else(|| {
+            ClaimsVerificationError::InvalidAuthorizationCodeHash(
+                "missing `c_hash` claim".to_string(),
+            )
+        })?;
+        // The hash function is determined by the ID token's JWS signature algorithm.
+        let signature_alg = Self::signature_alg(jwt).ok_or_else(|| {
+            ClaimsVerificationError::InvalidAuthorizationCodeHash(
+                "ID token must be signed".to_string(),
+            )
+        })?;
+        let actual_hash = AuthorizationCodeHash::from_code(code, signature_alg).map_err(|err| {
+            ClaimsVerificationError::InvalidAuthorizationCodeHash(format!("{}", err))
+        })?;
+        if actual_hash != *expected_hash {
+            return Err(ClaimsVerificationError::InvalidAuthorizationCodeHash(
+                "authorization code hash does not match".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn signature_alg(
+        jwt: &JsonWebToken<IdTokenClaims<AC, GC>, JE, JS, JT, JsonWebTokenJsonPayloadDeserializer>,
+    ) -> Option<&JS> {
+        if let JsonWebTokenAlgorithm::Signature(ref signature_alg, _) = jwt.unverified_header().alg
+        {
+            Some(signature_alg)
+        } else {
+            None
+        }
+    }
+}
+impl<'de, AC, GC, JE, JS, JT> Deserialize<'de> for IdToken<AC, GC, JE, JS, JT>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
     {
-        verifier.verified_claims(&self.0, Some(nonce))
+        let raw_token = String::deserialize(deserializer)?;
+        // The JWE compact serialization has five parts, while the JWS one has three.
+        if raw_token.split('.').count() == 5 {
+            JsonWebEncryption::deserialize(IntoDeserializer::<D::Error>::into_deserializer(
+                raw_token,
+            )).map(|jwe| IdToken(IdTokenJwt::Encrypted(jwe)))
+        } else {
+            JsonWebToken::deserialize(IntoDeserializer::<D::Error>::into_deserializer(raw_token))
+                .map(|jwt| IdToken(IdTokenJwt::Signed(jwt)))
+        }
+    }
+}
+impl<AC, GC, JE, JS, JT> Serialize for IdToken<AC, GC, JE, JS, JT>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    fn serialize<SE>(&self, serializer: SE) -> Result<SE::Ok, SE::Error>
+    where
+        SE: Serializer,
+    {
+        match self.0 {
+            IdTokenJwt::Signed(ref jwt) | IdTokenJwt::Decrypted(ref jwt) => {
+                jwt.serialize(serializer)
+            }
+            IdTokenJwt::Encrypted(ref jwe) => jwe.serialize(serializer),
+        }
     }
 }
 
@@ -70,6 +449,10 @@ where
     exp: Seconds,
     iat: Seconds,
     #[serde(skip_serializing_if = "Option::is_none")]
+    nbf: Option<Seconds>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    jti: Option<JsonWebTokenId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     auth_time: Option<Seconds>,
     #[serde(skip_serializing_if = "Option::is_none")]
     nonce: Option<Nonce>,
@@ -110,6 +493,12 @@ where
     pub fn issue_time(&self) -> Result<DateTime<Utc>, ()> {
         seconds_to_utc(&self.iat)
     }
+    pub fn not_before(&self) -> Option<Result<DateTime<Utc>, ()>> {
+        self.nbf.as_ref().map(seconds_to_utc)
+    }
+    pub fn jwt_id(&self) -> Option<&JsonWebTokenId> {
+        self.jti.as_ref()
+    }
     pub fn auth_time(&self) -> Option<Result<DateTime<Utc>, ()>> {
         self.auth_time.as_ref().map(seconds_to_utc)
     }
@@ -247,31 +636,114 @@ where
     JT: JsonWebKeyType,
 {}
 
+///
+/// Extra fields of a token response to a refresh request, which may omit the ID token.
+///
+/// Any ID token returned is subject to the additional rules of
+/// [Section 12.2](https://openid.net/specs/openid-connect-core-1_0.html#RefreshTokenResponse)
+/// and should be verified using [`verified_id_token_claims`](#method.verified_id_token_claims).
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RefreshedIdTokenFields<AC, GC, JE, JS, JT>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    #[serde(bound = "AC: AdditionalClaims")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id_token: Option<IdToken<AC, GC, JE, JS, JT>>,
+    #[serde(skip)]
+    _phantom_jt: PhantomData<JT>,
+}
+impl<AC, GC, JE, JS, JT> RefreshedIdTokenFields<AC, GC, JE, JS, JT>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    pub fn id_token(&self) -> Option<&IdToken<AC, GC, JE, JS, JT>> {
+        self.id_token.as_ref()
+    }
+
+    ///
+    /// Verifies the refreshed ID token, if any, against the claims of the original ID token (see
+    /// [`IdToken::claims_after_refresh`]) and returns its claims.
+    ///
+    /// Encrypted ID tokens must first be decrypted using [`IdToken::decrypt`] and then verified
+    /// using [`IdToken::claims_after_refresh`].
+    ///
+    pub fn verified_id_token_claims<JU, K>(
+        &self,
+        verifier: &IdTokenVerifier<JS, JT, JU, K>,
+        original_claims: &IdTokenClaims<AC, GC>,
+    ) -> Result<Option<&IdTokenClaims<AC, GC>>, ClaimsVerificationError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+    {
+        self.id_token
+            .as_ref()
+            .map(|id_token| id_token.claims_after_refresh(verifier, original_claims))
+            .map_or(Ok(None), |claims| claims.map(Some))
+    }
+}
+impl<AC, GC, JE, JS, JT> ExtraTokenFields for RefreshedIdTokenFields<AC, GC, JE, JS, JT>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+}
+
 #[cfg(test)]
-mod tests {
+pub mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
     use chrono::{TimeZone, Utc};
     use itertools::sorted;
     use oauth2::basic::BasicTokenType;
     use oauth2::prelude::{NewType, SecretNewType};
-    use oauth2::{AccessToken, ClientId, TokenResponse};
+    use oauth2::{AccessToken, AuthorizationCode, ClientId, ClientSecret, TokenResponse};
     use serde_json;
     use url::Url;
 
     use super::super::claims::{AdditionalClaims, EmptyAdditionalClaims, StandardClaims};
     use super::super::core::{
-        CoreGenderClaim, CoreIdToken, CoreIdTokenClaims, CoreJsonWebKeyType,
-        CoreJweContentEncryptionAlgorithm, CoreJwsSigningAlgorithm,
+        CoreGenderClaim, CoreIdToken, CoreIdTokenClaims, CoreIdTokenVerifier, CoreJsonWebKey,
+        CoreJsonWebKeySet, CoreJsonWebKeyType, CoreJweContentEncryptionAlgorithm,
+        CoreJweKeyManagementAlgorithm, CoreJwsSigningAlgorithm,
+    };
+    use super::super::jwt::tests::TEST_RSA_PRIV_KEY;
+    use super::super::jwt::{JsonWebEncryption, JsonWebTokenAccess, JsonWebTokenContentType};
+    use super::super::verification::{
+        AuthContextError, AuthContextPolicy, AuthorizedPartyPolicy, ClaimsVerificationError,
+        DecryptionError,
     };
-    use super::super::jwt::JsonWebTokenAccess;
+    use super::super::{ConfigurationError, JsonWebKey, PrivateSigningKey};
     use super::super::{
         AccessTokenHash, AddressCountry, AddressLocality, AddressPostalCode, AddressRegion,
         Audience, AuthenticationContextClass, AuthenticationMethodReference, AuthorizationCodeHash,
         EndUserBirthday, EndUserEmail, EndUserFamilyName, EndUserGivenName, EndUserMiddleName,
         EndUserName, EndUserNickname, EndUserPhoneNumber, EndUserPictureUrl, EndUserProfileUrl,
-        EndUserTimezone, EndUserUsername, EndUserWebsiteUrl, FormattedAddress, IssuerUrl,
-        LanguageTag, Nonce, StreetAddress, SubjectIdentifier,
+        EndUserTimezone, EndUserUsername, EndUserWebsiteUrl, FormattedAddress, InMemoryReplayCache,
+        IssuerUrl, JsonWebTokenId, LanguageTag, Nonce, StreetAddress, SubjectIdentifier,
     };
-    use super::{AudiencesClaim, IdTokenClaims, IdTokenFields, IssuerClaim};
+    use super::{AudiencesClaim, IdTokenClaims, IdTokenFields, IdTokenJwt, IssuerClaim};
+
+    fn unverified_claims(id_token: &CoreIdToken) -> &CoreIdTokenClaims {
+        match id_token.0 {
+            IdTokenJwt::Signed(ref jwt) => jwt.unverified_claims_ref(),
+            _ => panic!("expected a signed ID token"),
+        }
+    }
 
     #[test]
     fn test_id_token() {
@@ -282,7 +754,7 @@ mod tests {
         let id_token =
             serde_json::from_str::<CoreIdToken>(id_token_str).expect("failed to deserialize");
 
-        let claims = id_token.0.unverified_claims_ref();
+        let claims = unverified_claims(&id_token);
 
         assert_eq!(
             *claims.issuer().url(),
@@ -332,7 +804,7 @@ mod tests {
         assert_eq!(*response.token_type(), BasicTokenType::Bearer);
 
         let id_token = response.extra_fields().id_token();
-        let claims = id_token.0.unverified_claims_ref();
+        let claims = unverified_claims(&id_token);
 
         assert_eq!(
             *claims.issuer().url(),
@@ -844,4 +1316,813 @@ mod tests {
         verify_issuer(&claims);
         verify_issuer(&&claims);
     }
+
+    // ID token signed with TEST_RSA_PRIV_KEY (RS256) and then encrypted to the same key using
+    // RSA-OAEP-256 and A128CBC-HS256.
+    pub const TEST_ENCRYPTED_ID_TOKEN: &str =
+        "eyJhbGciOiJSU0EtT0FFUC0yNTYiLCJlbmMiOiJBMTI4Q0JDLUhTMjU2Iiwia2lkIjoicnNhLWVuY3J5cHRpb24ta\
+         2V5IiwiY3R5IjoiSldUIn0.ns0uFnFZFAudz2be-Y_gutzpNyc5oNm_zVoYMFxh7d0_IKnX6-JSJcJCFtfux_WNap5\
+         fqYRMAeRcozGYTuQnisJTjgG4JUmrovRdDDZwasqPG4-cvkQuzjweTQREwwNYmCpQ3swCnG6MrHw57g_0mJ8G1PIzR\
+         1TnzLYj-CtQWxotpR47K8r-8a4teHK2E5aKlwyH6O4ri_5S-PhwCgcnjyebbaNqePE3Z8klpbeiX_ua5Gr-1Je2vdn\
+         IB4bT0-GjWvTvHKeysAI5MUAahSBIxW-KhKphA88enUnsSNUfPzAT5Pi6l4vuklmPtGW_KBkzYVNCdk2OJ_Rr4_rpV\
+         aAL4w.qCkYA_PxvwIypdALawnMwA.d0pGjaV6fKbabBg4a_C0dTDF62qNwDUHMoggLwtKCE5-XLcfCHZ26-RP-DCWn\
+         4cd7oTsbr7bT2puxsnbWfbEj2apS1ANwhsIrIAHm6hQsbCPTc5uNdj5ufvehZorB7icxjgJKsaMgiN-Ac74sf5uuKq\
+         Yxsdz3NEzfcA_dbI6JhH3aEZQndlN4fPuDc_R4QrcZZiYLG6QoEwVgxJLBRiVwmfAEiJKy2pERfiim6BvyLmNMpYIo\
+         UQHhpWl_zOGY-CN5i1IVwzoWs1HuLb_AeroznSjIi0Lq8PJaHxhk4a6csVjXsGb51_DhfWEnNunB7O6HZRZdVGzzAy\
+         nGH0eEMp1VAD3UI2o7Bvfti4lsQzw2IVvCRsL3vv05rnD-3E6fG2_bWv0ov_YwH_NGJndPVjKa1JTeWYmoAIqu6uzO\
+         7vFQk693sInS6WMHpwkxBxunybBqpZ-YfU7xFK91YX-tFBNrSL9qTy9QqTySfcoTCsEKkaecOAOvD4AxB3TVyo4k-c\
+         ojW71Ftf3Ggw9DWzimREw3yyt8jTAYYp37A9tJnQpQe6IYxjDiEXldtKCb4MLOKXsDfxpWLABJDnN7Hvhl1DKZ3IRr\
+         SHEHTA26KaHpglEg_11bQeq6wLJcbARBTtSLEUJz0uPbMXGN9IJjST5JL7yfJ_X0hCWT1T9eD_td0gega5bbmVMhA0\
+         fRVq5Y3V-tIXpUUebwFoQLhagPzCa51Gl_HwTFFbrQ-YuUaajGzHZeojnZIAHCyF4ogNr0b8HCUE89RAlgbrQe_SQS\
+         73WPlOnhQ.PCm9JqYLoMXyG2y5lhwVcg";
+
+    #[test]
+    #[cfg(feature = "rsa-oaep")]
+    fn test_encrypted_id_token() {
+        let signing_key: CoreJsonWebKey =
+            serde_json::from_str(TEST_RSA_PRIV_KEY).expect("deserialization failed");
+        let decryption_key: CoreJsonWebKey = serde_json::from_str(
+            &TEST_RSA_PRIV_KEY
+                .replace("\"rsa-signing-key\"", "\"rsa-encryption-key\"")
+                .replace("\"sig\"", "\"enc\""),
+        ).expect("deserialization failed");
+        let new_verifier = || {
+            CoreIdTokenVerifier::new_public_client(
+                ClientId::new("s6BhdRkqt3".to_string()),
+                IssuerUrl::new("https://server.example.com".to_string()).unwrap(),
+                CoreJsonWebKeySet::new(vec![signing_key.as_verification_key()]),
+            ).set_time_fn(Box::new(|| Utc.timestamp(1311281000, 0)))
+        };
+        let verifier = new_verifier().set_decryption_keys(vec![decryption_key.clone()]);
+        let nonce = Nonce::new("the_nonce".to_string());
+
+        let id_token = serde_json::from_value::<CoreIdToken>(serde_json::Value::String(
+            TEST_ENCRYPTED_ID_TOKEN.to_string(),
+        )).expect("failed to deserialize");
+        assert!(id_token.is_encrypted());
+        assert_eq!(
+            serde_json::to_value(&id_token).expect("failed to serialize"),
+            serde_json::Value::String(TEST_ENCRYPTED_ID_TOKEN.to_string())
+        );
+        match id_token.claims(&verifier, &nonce) {
+            Err(ClaimsVerificationError::Unsupported(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let decrypted_id_token = id_token.decrypt(&verifier).expect("decryption failed");
+        assert!(!decrypted_id_token.is_encrypted());
+        let claims = decrypted_id_token
+            .claims(&verifier, &nonce)
+            .expect("verification failed");
+        assert_eq!(
+            *claims.sub(),
+            SubjectIdentifier::new("24400320".to_string())
+        );
+        // Decrypting a decrypted ID token is a no-op.
+        assert_eq!(
+            decrypted_id_token
+                .decrypt(&verifier)
+                .expect("decryption failed"),
+            decrypted_id_token
+        );
+
+        // The serialized form of a decrypted ID token is the nested signed JWT, which is what
+        // should be passed as an `id_token_hint`.
+        let signed_id_token = serde_json::from_value::<CoreIdToken>(
+            serde_json::to_value(&decrypted_id_token).expect("failed to serialize"),
+        ).expect("failed to deserialize");
+        assert!(!signed_id_token.is_encrypted());
+
+        // Once decryption keys are set, unencrypted ID tokens are rejected.
+        match signed_id_token.claims(&verifier, &nonce) {
+            Err(ClaimsVerificationError::NoEncryption) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match signed_id_token.decrypt(&verifier) {
+            Err(ClaimsVerificationError::NoEncryption) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        // Verifiers without decryption keys accept them.
+        signed_id_token
+            .claims(&new_verifier(), &nonce)
+            .expect("verification failed");
+
+        // Encrypted ID tokens can't be decrypted without a matching key.
+        match id_token.decrypt(&new_verifier()) {
+            Err(ClaimsVerificationError::Decryption(DecryptionError::NoMatchingKey)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        // The JWE header's key ID must match the key's.
+        let other_key = CoreJsonWebKey {
+            kid: None,
+            ..decryption_key.clone()
+        };
+        match id_token.decrypt(&new_verifier().set_decryption_keys(vec![other_key])) {
+            Err(ClaimsVerificationError::Decryption(DecryptionError::NoMatchingKey)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match id_token.decrypt(
+            &new_verifier().set_decryption_keys(vec![decryption_key.clone(), decryption_key]),
+        ) {
+            Err(ClaimsVerificationError::Decryption(DecryptionError::AmbiguousKeyId(_))) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_client_secret_encrypted_id_token() {
+        let signing_key: CoreJsonWebKey =
+            serde_json::from_str(TEST_RSA_PRIV_KEY).expect("deserialization failed");
+        let new_verifier = |client_secret: &str| {
+            CoreIdTokenVerifier::new_private_client(
+                ClientId::new("s6BhdRkqt3".to_string()),
+                ClientSecret::new(client_secret.to_string()),
+                IssuerUrl::new("https://server.example.com".to_string()).unwrap(),
+                CoreJsonWebKeySet::new(vec![signing_key.as_verification_key()]),
+            ).set_time_fn(Box::new(|| Utc.timestamp(1311281000, 0)))
+        };
+        let nonce = Nonce::new("the_nonce".to_string());
+        let signed_id_token = serde_json::to_value(&new_signed_id_token("{}"))
+            .expect("failed to serialize")
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        // Keys derived from the client secret `my_secret` as described in Section 10.2 of OpenID
+        // Connect Core 1.0: the left-most 128 bits of its SHA-256 hash for A128KW, and its
+        // SHA-512 hash for direct encryption using A256CBC-HS512.
+        let key_wrap_key = CoreJsonWebKey::new_symmetric(vec![
+            31, 146, 51, 161, 33, 5, 125, 206, 220, 11, 142, 109, 50, 251, 96, 93,
+        ]);
+        let direct_key = CoreJsonWebKey::new_symmetric(vec![
+            81, 124, 194, 234, 205, 13, 48, 170, 251, 43, 241, 73, 186, 19, 230, 24, 23, 112, 246,
+            63, 248, 68, 114, 74, 216, 187, 211, 128, 11, 188, 93, 78, 119, 0, 117, 130, 10, 199,
+            5, 126, 16, 188, 117, 213, 240, 120, 114, 77, 32, 11, 42, 229, 231, 189, 98, 11, 207,
+            3, 0, 43, 230, 141, 151, 105,
+        ]);
+        for &(ref key, ref key_management_alg, ref content_encryption_alg) in &[
+            (
+                &key_wrap_key,
+                CoreJweKeyManagementAlgorithm::AesKeyWrap128,
+                CoreJweContentEncryptionAlgorithm::Aes128Gcm,
+            ),
+            (
+                &direct_key,
+                CoreJweKeyManagementAlgorithm::Direct,
+                CoreJweContentEncryptionAlgorithm::Aes256CbcHmacSha512,
+            ),
+        ] {
+            let jwe = JsonWebEncryption::encrypt(
+                signed_id_token.as_bytes(),
+                *key,
+                key_management_alg,
+                content_encryption_alg,
+                Some(JsonWebTokenContentType::new("JWT".to_string())),
+            ).expect("encryption failed");
+            let id_token = serde_json::from_value::<CoreIdToken>(serde_json::Value::String(
+                jwe.raw_token().to_string(),
+            )).expect("failed to deserialize");
+
+            let verifier = new_verifier("my_secret");
+            let claims = id_token
+                .decrypt(&verifier)
+                .expect("decryption failed")
+                .claims(&verifier, &nonce)
+                .expect("verification failed")
+                .clone();
+            assert_eq!(
+                *claims.sub(),
+                SubjectIdentifier::new("24400320".to_string())
+            );
+
+            // Keys derived from a different client secret fail to decrypt the ID token.
+            match id_token.decrypt(&new_verifier("other_secret")) {
+                Err(ClaimsVerificationError::Decryption(DecryptionError::CryptoError(_))) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+            // Public clients have no client secret to derive a key from.
+            match id_token.decrypt(&new_id_token_verifier()) {
+                Err(ClaimsVerificationError::Decryption(DecryptionError::NoMatchingKey)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+    }
+
+    // Returns an ID token signed using TEST_RSA_PRIV_KEY, with the given JSON claims overriding
+    // or adding to the minimal claims below.
+    pub fn new_signed_id_token(claims_json: &str) -> CoreIdToken {
+        let mut claims = serde_json::json!({
+            "iss": "https://server.example.com",
+            "aud": "s6BhdRkqt3",
+            "exp": 1311281970,
+            "iat": 1311280970,
+            "sub": "24400320",
+            "nonce": "the_nonce",
+        });
+        let overrides =
+            serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(claims_json)
+                .expect("failed to deserialize");
+        for (name, value) in overrides {
+            claims[name] = value;
+        }
+
+        let signing_key: CoreJsonWebKey =
+            serde_json::from_str(TEST_RSA_PRIV_KEY).expect("deserialization failed");
+        CoreIdToken::new(
+            serde_json::from_value::<CoreIdTokenClaims>(claims).expect("failed to deserialize"),
+            &signing_key,
+            &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+        ).expect("failed to sign ID token")
+    }
+
+    // Returns a verifier for the ID tokens returned by new_signed_id_token.
+    pub fn new_id_token_verifier() -> CoreIdTokenVerifier {
+        let signing_key: CoreJsonWebKey =
+            serde_json::from_str(TEST_RSA_PRIV_KEY).expect("deserialization failed");
+        CoreIdTokenVerifier::new_public_client(
+            ClientId::new("s6BhdRkqt3".to_string()),
+            IssuerUrl::new("https://server.example.com".to_string()).unwrap(),
+            CoreJsonWebKeySet::new(vec![signing_key.as_verification_key()]),
+        ).set_time_fn(Box::new(|| Utc.timestamp(1311281000, 0)))
+    }
+
+    #[test]
+    fn test_token_hash_verification() {
+        let verifier = new_id_token_verifier();
+        let nonce = Nonce::new("the_nonce".to_string());
+        let access_token =
+            AccessToken::new("jHkWEdUXMU1BwAsC4vtUsZwnNvTIxEl0z9K3vx5KF0Y".to_string());
+
+        let id_token = new_signed_id_token("{\"at_hash\": \"77QmUPtjPfzWtF2AnpK9RQ\"}");
+        let claims = id_token
+            .claims_with_access_token(&verifier, &nonce, &access_token)
+            .expect("verification failed");
+        assert_eq!(
+            claims.access_token_hash(),
+            Some(&AccessTokenHash::new("77QmUPtjPfzWtF2AnpK9RQ".to_string()))
+        );
+
+        match id_token.claims_with_access_token(
+            &verifier,
+            &nonce,
+            &AccessToken::new("another_access_token".to_string()),
+        ) {
+            Err(ClaimsVerificationError::InvalidAccessTokenHash(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // The `at_hash` claim is required.
+        let id_token = new_signed_id_token("{}");
+        id_token
+            .claims(&verifier, &nonce)
+            .expect("verification failed");
+        match id_token.claims_with_access_token(&verifier, &nonce, &access_token) {
+            Err(ClaimsVerificationError::InvalidAccessTokenHash(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Authorization code hash (c_hash).
+        let code = AuthorizationCode::new(
+            "Qcb0Orv1zh30vL1MPRsbm-diHiMwcLyZvn1arpZv-Jxf_11jnpEX3Tgfvk".to_string(),
+        );
+        match id_token.claims_with_code(&verifier, &nonce, &code) {
+            Err(ClaimsVerificationError::InvalidAuthorizationCodeHash(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let id_token = new_signed_id_token("{\"c_hash\": \"LDktKdoQak3Pk0cnXxCltA\"}");
+        let claims = id_token
+            .claims_with_code(&verifier, &nonce, &code)
+            .expect("verification failed");
+        assert_eq!(
+            claims.code_hash(),
+            Some(&AuthorizationCodeHash::new(
+                "LDktKdoQak3Pk0cnXxCltA".to_string()
+            ))
+        );
+        match id_token.claims_with_code(
+            &verifier,
+            &nonce,
+            &AuthorizationCode::new("another_code".to_string()),
+        ) {
+            Err(ClaimsVerificationError::InvalidAuthorizationCodeHash(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_token_hash_verification_with_replay_cache() {
+        let replay_cache = Arc::new(InMemoryReplayCache::new());
+        let verifier = new_id_token_verifier().set_replay_cache(replay_cache.clone());
+        let nonce = Nonce::new("the_nonce".to_string());
+        let access_token =
+            AccessToken::new("jHkWEdUXMU1BwAsC4vtUsZwnNvTIxEl0z9K3vx5KF0Y".to_string());
+        let code = AuthorizationCode::new(
+            "Qcb0Orv1zh30vL1MPRsbm-diHiMwcLyZvn1arpZv-Jxf_11jnpEX3Tgfvk".to_string(),
+        );
+        let id_token = new_signed_id_token(
+            "{\"at_hash\": \"77QmUPtjPfzWtF2AnpK9RQ\", \"c_hash\": \"LDktKdoQak3Pk0cnXxCltA\"}",
+        );
+
+        // ID tokens failing the token hash verification don't consume their nonce.
+        match id_token.claims_with_code_and_access_token(
+            &verifier,
+            &nonce,
+            &code,
+            &AccessToken::new("another_access_token".to_string()),
+        ) {
+            Err(ClaimsVerificationError::InvalidAccessTokenHash(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match id_token.claims_with_code_and_access_token(
+            &verifier,
+            &nonce,
+            &AuthorizationCode::new("another_code".to_string()),
+            &access_token,
+        ) {
+            Err(ClaimsVerificationError::InvalidAuthorizationCodeHash(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(replay_cache.is_empty());
+
+        let claims = id_token
+            .claims_with_code_and_access_token(&verifier, &nonce, &code, &access_token)
+            .expect("verification failed");
+        assert_eq!(
+            claims.access_token_hash(),
+            Some(&AccessTokenHash::new("77QmUPtjPfzWtF2AnpK9RQ".to_string()))
+        );
+        assert_eq!(
+            claims.code_hash(),
+            Some(&AuthorizationCodeHash::new(
+                "LDktKdoQak3Pk0cnXxCltA".to_string()
+            ))
+        );
+        assert_eq!(replay_cache.len(), 1);
+
+        match id_token.claims_with_code_and_access_token(&verifier, &nonce, &code, &access_token) {
+            Err(ClaimsVerificationError::Replayed(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_authorized_party_verification() {
+        let nonce = Nonce::new("the_nonce".to_string());
+        let single_aud = new_signed_id_token("{}");
+        let multi_aud = new_signed_id_token("{\"aud\": [\"s6BhdRkqt3\", \"other_client\"]}");
+        let multi_aud_azp = new_signed_id_token(
+            "{\"aud\": [\"s6BhdRkqt3\", \"other_client\"], \"azp\": \"s6BhdRkqt3\"}",
+        );
+        let other_azp = new_signed_id_token("{\"azp\": \"other_client\"}");
+
+        let assert_invalid_audience = |result: Result<_, ClaimsVerificationError>| match result {
+            Err(ClaimsVerificationError::InvalidAudience(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        };
+
+        // By default, the azp claim is ignored.
+        let verifier = new_id_token_verifier();
+        for id_token in &[&single_aud, &multi_aud, &multi_aud_azp, &other_azp] {
+            id_token
+                .claims(&verifier, &nonce)
+                .expect("verification failed");
+        }
+
+        let verifier = new_id_token_verifier()
+            .set_authorized_party_policy(AuthorizedPartyPolicy::RequireForMultipleAudiences);
+        single_aud
+            .claims(&verifier, &nonce)
+            .expect("verification failed");
+        multi_aud_azp
+            .claims(&verifier, &nonce)
+            .expect("verification failed");
+        assert_invalid_audience(multi_aud.claims(&verifier, &nonce));
+        assert_invalid_audience(other_azp.claims(&verifier, &nonce));
+
+        let verifier = new_id_token_verifier()
+            .set_authorized_party_policy(AuthorizedPartyPolicy::RequireClientId);
+        multi_aud_azp
+            .claims(&verifier, &nonce)
+            .expect("verification failed");
+        assert_invalid_audience(single_aud.claims(&verifier, &nonce));
+        assert_invalid_audience(multi_aud.claims(&verifier, &nonce));
+        assert_invalid_audience(other_azp.claims(&verifier, &nonce));
+
+        // Custom policy trusting another first-party client as the authorized party.
+        let verifier = new_id_token_verifier().set_authorized_party_policy(
+            AuthorizedPartyPolicy::Custom(Box::new(|azp, audiences| match azp {
+                Some(azp) if **azp == "other_client" => {
+                    assert_eq!(audiences, &[Audience::new("s6BhdRkqt3".to_string())]);
+                    Ok(())
+                }
+                _ => Err("unexpected authorized party".to_string()),
+            })),
+        );
+        other_azp
+            .claims(&verifier, &nonce)
+            .expect("verification failed");
+        assert_invalid_audience(single_aud.claims(&verifier, &nonce));
+
+        // Trusted additional audiences.
+        let verifier = new_id_token_verifier()
+            .set_trusted_audiences(vec![Audience::new("other_client".to_string())]);
+        multi_aud
+            .claims(&verifier, &nonce)
+            .expect("verification failed");
+        let verifier = new_id_token_verifier()
+            .set_trusted_audiences(vec![Audience::new("another_client".to_string())]);
+        assert_invalid_audience(multi_aud.claims(&verifier, &nonce));
+        single_aud
+            .claims(&verifier, &nonce)
+            .expect("verification failed");
+    }
+
+    #[test]
+    fn test_validity_period_verification() {
+        // The verifier's current time is 1311281000.
+        let nonce = Nonce::new("the_nonce".to_string());
+        let expired = new_signed_id_token("{\"exp\": 1311280990}");
+        let not_yet_valid = new_signed_id_token("{\"nbf\": 1311281010}");
+        let issued_in_future = new_signed_id_token("{\"iat\": 1311281010}");
+        let authenticated_in_future = new_signed_id_token("{\"auth_time\": 1311281010}");
+
+        fn assert_expired(result: Result<&CoreIdTokenClaims, ClaimsVerificationError>) {
+            match result {
+                Err(ClaimsVerificationError::Expired(_)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+
+        let verifier = new_id_token_verifier();
+        assert_expired(expired.claims(&verifier, &nonce));
+        assert_expired(not_yet_valid.claims(&verifier, &nonce));
+        match authenticated_in_future.claims(&verifier, &nonce) {
+            Err(ClaimsVerificationError::InvalidAuthTime(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        // The issue time is only checked once a maximum issue age is set.
+        issued_in_future
+            .claims(&verifier, &nonce)
+            .expect("verification failed");
+        let verifier = new_id_token_verifier().set_max_issue_age(Duration::from_secs(60));
+        assert_expired(issued_in_future.claims(&verifier, &nonce));
+        // Issued 30 seconds ago.
+        new_signed_id_token("{}")
+            .claims(&verifier, &nonce)
+            .expect("verification failed");
+        let verifier = new_id_token_verifier().set_max_issue_age(Duration::from_secs(20));
+        assert_expired(new_signed_id_token("{}").claims(&verifier, &nonce));
+
+        // Each of the above is within 10 seconds of the current time.
+        let verifier = new_id_token_verifier()
+            .set_leeway(Duration::from_secs(10))
+            .set_max_issue_age(Duration::from_secs(20));
+        for id_token in &[
+            expired,
+            not_yet_valid,
+            issued_in_future,
+            authenticated_in_future,
+            new_signed_id_token("{}"),
+        ] {
+            id_token
+                .claims(&verifier, &nonce)
+                .expect("verification failed");
+        }
+        let verifier = new_id_token_verifier().set_leeway(Duration::from_secs(9));
+        assert_expired(new_signed_id_token("{\"exp\": 1311280990}").claims(&verifier, &nonce));
+    }
+
+    #[test]
+    fn test_nonce_verification() {
+        let verifier = new_id_token_verifier();
+        let id_token = new_signed_id_token("{}");
+        let no_nonce = new_signed_id_token("{\"nonce\": null}");
+
+        fn assert_invalid_nonce(result: Result<&CoreIdTokenClaims, ClaimsVerificationError>) {
+            match result {
+                Err(ClaimsVerificationError::InvalidNonce(_)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+
+        // Exact match.
+        id_token
+            .claims(&verifier, &Nonce::new("the_nonce".to_string()))
+            .expect("verification failed");
+        assert_invalid_nonce(id_token.claims(&verifier, &Nonce::new("other_nonce".to_string())));
+        assert_invalid_nonce(no_nonce.claims(&verifier, &Nonce::new("the_nonce".to_string())));
+
+        // Custom verification function (e.g., for nonces derived from a session ID).
+        let verify_nonce = |nonce: Option<&Nonce>| match nonce {
+            Some(nonce) if nonce.secret().starts_with("the_") => Ok(()),
+            _ => Err("invalid nonce".to_string()),
+        };
+        let claims = id_token
+            .claims(&verifier, verify_nonce)
+            .expect("verification failed");
+        assert_eq!(claims.nonce(), Some(&Nonce::new("the_nonce".to_string())));
+        assert_invalid_nonce(no_nonce.claims(&verifier, verify_nonce));
+        match new_signed_id_token("{\"nonce\": \"other_nonce\"}").claims(&verifier, verify_nonce) {
+            Err(ClaimsVerificationError::InvalidNonce(ref msg)) if msg == "invalid nonce" => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Accepting a missing nonce claim, which is optional for the authorization code flow.
+        no_nonce
+            .claims(&verifier, |_: Option<&Nonce>| Ok(()))
+            .expect("verification failed");
+    }
+
+    #[test]
+    fn test_replay_detection() {
+        let replay_cache = Arc::new(InMemoryReplayCache::new());
+        let verifier = new_id_token_verifier().set_replay_cache(replay_cache.clone());
+        let nonce = Nonce::new("the_nonce".to_string());
+
+        fn assert_replayed(result: Result<&CoreIdTokenClaims, ClaimsVerificationError>) {
+            match result {
+                Err(ClaimsVerificationError::Replayed(_)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+
+        // Tokens failing verification don't consume their nonce.
+        match new_signed_id_token("{}").claims(&verifier, &Nonce::new("other_nonce".to_string())) {
+            Err(ClaimsVerificationError::InvalidNonce(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(replay_cache.is_empty());
+
+        let id_token = new_signed_id_token("{\"jti\": \"the_jti\"}");
+        let claims = id_token
+            .claims(&verifier, &nonce)
+            .expect("verification failed");
+        assert_eq!(
+            claims.jwt_id(),
+            Some(&JsonWebTokenId::new("the_jti".to_string()))
+        );
+        assert_eq!(replay_cache.len(), 2);
+        assert_replayed(id_token.claims(&verifier, &nonce));
+
+        // A new verifier sharing the same cache also detects the replay.
+        let other_verifier = new_id_token_verifier().set_replay_cache(replay_cache.clone());
+        assert_replayed(id_token.claims(&other_verifier, &nonce));
+        // Reused JWT ID with a fresh nonce.
+        assert_replayed(
+            new_signed_id_token("{\"nonce\": \"another_nonce\", \"jti\": \"the_jti\"}")
+                .claims(&other_verifier, &Nonce::new("another_nonce".to_string())),
+        );
+
+        // JWT IDs are only unique per issuer, so the same JWT ID from another issuer is accepted.
+        let signing_key: CoreJsonWebKey =
+            serde_json::from_str(TEST_RSA_PRIV_KEY).expect("deserialization failed");
+        let other_issuer_verifier = CoreIdTokenVerifier::new_public_client(
+            ClientId::new("s6BhdRkqt3".to_string()),
+            IssuerUrl::new("https://other.example.com".to_string()).unwrap(),
+            CoreJsonWebKeySet::new(vec![signing_key.as_verification_key()]),
+        ).set_time_fn(Box::new(|| Utc.timestamp(1311281000, 0)))
+        .set_replay_cache(replay_cache.clone());
+        new_signed_id_token(
+            "{\"iss\": \"https://other.example.com\", \"nonce\": \"other_nonce\", \
+             \"jti\": \"the_jti\"}",
+        ).claims(
+            &other_issuer_verifier,
+            &Nonce::new("other_nonce".to_string()),
+        ).expect("verification failed");
+
+        // Once the ID token expires, its nonce and JWT ID are no longer retained.
+        let later_verifier = new_id_token_verifier()
+            .set_replay_cache(replay_cache.clone())
+            .set_time_fn(Box::new(|| Utc.timestamp(1311281971, 0)));
+        new_signed_id_token("{\"exp\": 1311282970, \"jti\": \"the_jti\"}")
+            .claims(&later_verifier, &nonce)
+            .expect("verification failed");
+    }
+
+    #[test]
+    fn test_auth_time_verification() {
+        // The verifier's current time is 1311281000.
+        let nonce = Nonce::new("the_nonce".to_string());
+        let no_auth_time = new_signed_id_token("{}");
+        let recent_auth_time = new_signed_id_token("{\"auth_time\": 1311280900}");
+        let old_auth_time = new_signed_id_token("{\"auth_time\": 1311279000}");
+
+        fn assert_invalid_auth_time(result: Result<&CoreIdTokenClaims, ClaimsVerificationError>) {
+            match result {
+                Err(ClaimsVerificationError::InvalidAuthTime(_)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+
+        // By default, the auth_time claim is optional and may be arbitrarily old.
+        let verifier = new_id_token_verifier();
+        for id_token in &[&no_auth_time, &recent_auth_time, &old_auth_time] {
+            id_token
+                .claims(&verifier, &nonce)
+                .expect("verification failed");
+        }
+
+        let verifier = new_id_token_verifier().require_auth_time(true);
+        assert_invalid_auth_time(no_auth_time.claims(&verifier, &nonce));
+        old_auth_time
+            .claims(&verifier, &nonce)
+            .expect("verification failed");
+
+        let verifier = new_id_token_verifier().set_max_auth_age(Duration::from_secs(600));
+        assert_invalid_auth_time(no_auth_time.claims(&verifier, &nonce));
+        assert_invalid_auth_time(old_auth_time.claims(&verifier, &nonce));
+        recent_auth_time
+            .claims(&verifier, &nonce)
+            .expect("verification failed");
+
+        // Authenticated 2000 seconds ago.
+        let verifier = new_id_token_verifier()
+            .set_max_auth_age(Duration::from_secs(1800))
+            .set_leeway(Duration::from_secs(200));
+        old_auth_time
+            .claims(&verifier, &nonce)
+            .expect("verification failed");
+        let verifier = new_id_token_verifier()
+            .set_max_auth_age(Duration::from_secs(1800))
+            .set_leeway(Duration::from_secs(199));
+        assert_invalid_auth_time(old_auth_time.claims(&verifier, &nonce));
+    }
+
+    #[test]
+    fn test_auth_context_verification() {
+        let nonce = Nonce::new("the_nonce".to_string());
+        let acr = |value: &str| AuthenticationContextClass::new(value.to_string());
+        let no_acr = new_signed_id_token("{}");
+        let bronze = new_signed_id_token("{\"acr\": \"bronze\"}");
+        let silver = new_signed_id_token("{\"acr\": \"silver\"}");
+        let gold = new_signed_id_token("{\"acr\": \"gold\"}");
+
+        fn auth_context_error(
+            result: Result<&CoreIdTokenClaims, ClaimsVerificationError>,
+        ) -> AuthContextError {
+            match result {
+                Err(ClaimsVerificationError::InvalidAuthContext(err)) => err,
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+
+        let verifier = new_id_token_verifier()
+            .set_auth_context_policy(AuthContextPolicy::AnyOf(vec![acr("silver"), acr("gold")]));
+        for id_token in &[&no_acr, &silver, &gold] {
+            id_token
+                .claims(&verifier, &nonce)
+                .expect("verification failed");
+        }
+        let err = auth_context_error(bronze.claims(&verifier, &nonce));
+        assert_eq!(err.requested(), &[acr("silver"), acr("gold")]);
+        assert_eq!(err.received(), Some(&acr("bronze")));
+        assert_eq!(
+            format!("{}", err),
+            "unexpected authentication context class reference (requested: `silver`, `gold`; \
+             received: `bronze`)"
+        );
+
+        let verifier = new_id_token_verifier()
+            .set_auth_context_policy(AuthContextPolicy::Essential(vec![acr("silver")]));
+        silver
+            .claims(&verifier, &nonce)
+            .expect("verification failed");
+        assert_eq!(
+            auth_context_error(no_acr.claims(&verifier, &nonce)).received(),
+            None
+        );
+        auth_context_error(gold.claims(&verifier, &nonce));
+
+        let levels = vec![acr("bronze"), acr("silver"), acr("gold")];
+        match AuthContextPolicy::at_least(levels.clone(), acr("platinum")) {
+            Err(ConfigurationError::UnrecognizedAuthContextClass(ref class))
+                if class == "platinum" => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let verifier = new_id_token_verifier().set_auth_context_policy(
+            AuthContextPolicy::at_least(levels, acr("silver")).expect("invalid policy"),
+        );
+        for id_token in &[&silver, &gold] {
+            id_token
+                .claims(&verifier, &nonce)
+                .expect("verification failed");
+        }
+        let err = auth_context_error(bronze.claims(&verifier, &nonce));
+        assert_eq!(err.requested(), &[acr("silver"), acr("gold")]);
+        auth_context_error(no_acr.claims(&verifier, &nonce));
+        auth_context_error(
+            new_signed_id_token("{\"acr\": \"platinum\"}").claims(&verifier, &nonce),
+        );
+
+        // Custom verification functions run after the policy.
+        let verifier = new_id_token_verifier()
+            .set_auth_context_policy(AuthContextPolicy::AnyOf(vec![acr("silver"), acr("gold")]))
+            .set_auth_context_verifier_fn(Box::new(|acr| match acr {
+                Some(acr) if **acr == "gold" => Err("gold is not allowed here".to_string()),
+                _ => Ok(()),
+            }));
+        silver
+            .claims(&verifier, &nonce)
+            .expect("verification failed");
+        let err = auth_context_error(gold.claims(&verifier, &nonce));
+        assert_eq!(err.message(), "gold is not allowed here");
+        assert_eq!(err.received(), Some(&acr("gold")));
+        auth_context_error(bronze.claims(&verifier, &nonce));
+    }
+
+    #[test]
+    fn test_refreshed_id_token_verification() {
+        let original_id_token = new_signed_id_token(
+            "{\"auth_time\": 1311280900, \"azp\": \"s6BhdRkqt3\", \"jti\": \"jti1\"}",
+        );
+        let replay_cache = Arc::new(InMemoryReplayCache::new());
+        let new_verifier = || {
+            new_id_token_verifier()
+                .set_max_auth_age(Duration::from_secs(600))
+                .set_replay_cache(replay_cache.clone())
+        };
+        let original_claims = original_id_token
+            .claims(&new_verifier(), &Nonce::new("the_nonce".to_string()))
+            .expect("verification failed")
+            .clone();
+
+        // The refreshed ID token is verified an hour later, when the original authentication
+        // exceeds the maximum authentication age.
+        let refresh_verifier =
+            || new_verifier().set_time_fn(Box::new(|| Utc.timestamp(1311284600, 0)));
+        let refreshed_claims = |claims_json: &str| {
+            let mut claims = serde_json::from_str::<serde_json::Value>(claims_json)
+                .expect("failed to deserialize");
+            claims["exp"] = serde_json::json!(1311285570);
+            claims["iat"] = serde_json::json!(1311284570);
+            claims.to_string()
+        };
+
+        // The original nonce may be repeated or omitted.
+        for claims_json in &[
+            "{\"auth_time\": 1311280900, \"azp\": \"s6BhdRkqt3\"}",
+            "{\"auth_time\": 1311280900, \"azp\": \"s6BhdRkqt3\", \"nonce\": null}",
+        ] {
+            new_signed_id_token(&refreshed_claims(claims_json))
+                .claims_after_refresh(&refresh_verifier(), &original_claims)
+                .expect("verification failed");
+        }
+        // The maximum authentication age doesn't require refreshed ID tokens to include an
+        // auth_time claim, but require_auth_time does.
+        new_signed_id_token(&refreshed_claims("{\"azp\": \"s6BhdRkqt3\"}"))
+            .claims_after_refresh(&refresh_verifier(), &original_claims)
+            .expect("verification failed");
+        match new_signed_id_token(&refreshed_claims("{\"azp\": \"s6BhdRkqt3\"}"))
+            .claims_after_refresh(
+                &refresh_verifier().require_auth_time(true),
+                &original_claims,
+            ) {
+            Err(ClaimsVerificationError::InvalidAuthTime(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        new_signed_id_token(&refreshed_claims("{\"azp\": \"s6BhdRkqt3\"}"))
+            .claims_after_refresh(
+                &new_id_token_verifier().set_time_fn(Box::new(|| Utc.timestamp(1311284600, 0))),
+                &original_claims,
+            ).expect("verification failed");
+
+        macro_rules! assert_refresh_error {
+            ($claims_json:expr, $err:pat) => {
+                match new_signed_id_token(&refreshed_claims($claims_json))
+                    .claims_after_refresh(&refresh_verifier(), &original_claims)
+                {
+                    Err($err) => {}
+                    other => panic!("unexpected result: {:?}", other),
+                }
+            };
+        }
+        assert_refresh_error!(
+            "{\"auth_time\": 1311280900, \"azp\": \"s6BhdRkqt3\", \"nonce\": \"other_nonce\"}",
+            ClaimsVerificationError::InvalidNonce(_)
+        );
+        assert_refresh_error!(
+            "{\"auth_time\": 1311280900, \"azp\": \"s6BhdRkqt3\", \"sub\": \"other_sub\"}",
+            ClaimsVerificationError::InvalidSubject(_)
+        );
+        assert_refresh_error!(
+            "{\"auth_time\": 1311280900, \"azp\": \"s6BhdRkqt3\", \
+             \"aud\": [\"s6BhdRkqt3\", \"other_client\"]}",
+            ClaimsVerificationError::InvalidAudience(_)
+        );
+        assert_refresh_error!(
+            "{\"auth_time\": 1311280900}",
+            ClaimsVerificationError::InvalidAudience(_)
+        );
+        assert_refresh_error!(
+            "{\"auth_time\": 1311284500, \"azp\": \"s6BhdRkqt3\"}",
+            ClaimsVerificationError::InvalidAuthTime(_)
+        );
+        // JWT IDs must still be unique, even though the nonce may be repeated.
+        let claims_json =
+            "{\"auth_time\": 1311280900, \"azp\": \"s6BhdRkqt3\", \"jti\": \"jti_refresh\"}";
+        new_signed_id_token(&refreshed_claims(claims_json))
+            .claims_after_refresh(&refresh_verifier(), &original_claims)
+            .expect("verification failed");
+        assert_refresh_error!(claims_json, ClaimsVerificationError::Replayed(_));
+    }
 }
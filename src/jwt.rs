@@ -4,16 +4,77 @@ use std::ops::Deref;
 use std::str;
 
 use base64;
-use oauth2::prelude::NewType;
+use oauth2::prelude::*;
+use oauth2::ClientSecret;
+use ring::digest;
 use serde::de::{DeserializeOwned, Error as DeserializeError, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json;
 
 use super::{
     JsonWebKey, JsonWebKeyId, JsonWebKeyType, JsonWebKeyUse, JweContentEncryptionAlgorithm,
-    JwsSigningAlgorithm, SignatureVerificationError,
+    JweKeyManagementAlgorithm, JwsSigningAlgorithm, PrivateSigningKey, PublicEncryptionKey,
+    SignatureVerificationError,
 };
 
+///
+/// Error signing a message.
+///
+#[derive(Clone, Debug, Fail, PartialEq)]
+pub enum SigningError {
+    /// Failed to sign the message using the given key and parameters.
+    #[fail(display = "Crypto error: {}", _0)]
+    CryptoError(String),
+    /// The supplied key cannot be used in this context. This may occur if the key type does not
+    /// match the signature type (e.g., an RSA key used to compute an HMAC), the key is missing
+    /// private key parameters, or the JWK usage disallows signatures.
+    #[fail(display = "Invalid cryptographic key: {}", _0)]
+    InvalidKey(String),
+    /// Unsupported signature algorithm.
+    #[fail(display = "Unsupported signature algorithm: {}", _0)]
+    UnsupportedAlg(String),
+    /// An unexpected error occurred.
+    #[fail(display = "Other error: {}", _0)]
+    Other(String),
+}
+
+///
+/// Error encrypting a message.
+///
+#[derive(Clone, Debug, Fail, PartialEq)]
+pub enum EncryptionError {
+    /// Failed to encrypt the message using the given key and parameters.
+    #[fail(display = "Crypto error: {}", _0)]
+    CryptoError(String),
+    /// The supplied key cannot be used in this context. This may occur if the key type does not
+    /// match the key management algorithm (e.g., a symmetric key used with RSA-OAEP) or the JWK
+    /// usage disallows encryption.
+    #[fail(display = "Invalid cryptographic key: {}", _0)]
+    InvalidKey(String),
+    /// Unsupported key management or content encryption algorithm.
+    #[fail(display = "Unsupported encryption algorithm: {}", _0)]
+    UnsupportedAlg(String),
+    /// An unexpected error occurred.
+    #[fail(display = "Other error: {}", _0)]
+    Other(String),
+}
+
+///
+/// Error building a [JSON Web Token](https://tools.ietf.org/html/rfc7519).
+///
+#[derive(Debug, Fail)]
+pub enum JsonWebTokenError {
+    /// Failed to serialize the JOSE header or claims.
+    #[fail(display = "Failed to serialize JSON Web Token: {}", _0)]
+    Serialization(#[cause] serde_json::Error),
+    /// Failed to sign the JSON Web Token.
+    #[fail(display = "Failed to sign JSON Web Token: {}", _0)]
+    Signing(#[cause] SigningError),
+    /// Failed to encrypt the JSON Web Token.
+    #[fail(display = "Failed to encrypt JSON Web Token: {}", _0)]
+    Encryption(#[cause] EncryptionError),
+}
+
 new_type![#[derive(
     Deserialize, Eq, Hash, Ord, PartialOrd, Serialize,
 )]
@@ -32,7 +93,7 @@ where
     JT: JsonWebKeyType,
 {
     Encryption(JE),
-    // This is ugly, but we don't expose this module via the public API, so it's fine.
+    // This is ugly, but we don't expose this enum via the public API, so it's fine.
     Signature(JS, PhantomData<JT>),
     ///
     /// No digital signature or MAC performed.
@@ -171,6 +232,13 @@ where
         JW: JsonWebKey<JS, JT, JU>;
 }
 
+///
+/// [JSON Web Token](https://tools.ietf.org/html/rfc7519) in its compact serialization.
+///
+/// Tokens are deserialized from their compact serialization (e.g., as part of a token response)
+/// and serialize back to the same string. New signed tokens are created using
+/// [`JsonWebTokenBuilder`].
+///
 #[derive(Clone, Debug, PartialEq)]
 pub struct JsonWebToken<C, JE, JS, JT, P>
 where
@@ -187,6 +255,21 @@ where
     raw_token: String,
     _phantom: PhantomData<P>,
 }
+impl<C, JE, JS, JT, P> JsonWebToken<C, JE, JS, JT, P>
+where
+    C: Debug + DeserializeOwned + Serialize,
+    JE: JweContentEncryptionAlgorithm,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    P: JsonWebTokenPayloadDeserialize<C>,
+{
+    ///
+    /// Returns the JWS compact serialization of this token.
+    ///
+    pub fn raw_token(&self) -> &str {
+        &self.raw_token
+    }
+}
 // Owned JWT.
 impl<C, JE, JS, JT, P> JsonWebTokenAccess<C, JE, JS, JT> for JsonWebToken<C, JE, JS, JT, P>
 where
@@ -251,6 +334,178 @@ where
         Ok(&self.claims)
     }
 }
+
+///
+/// Builder for signed [JSON Web Tokens](https://tools.ietf.org/html/rfc7519).
+///
+/// The claims are serialized as JSON, and the resulting token uses the JWS compact serialization
+/// described in [Section 7.1 of RFC 7515](https://tools.ietf.org/html/rfc7515#section-7.1).
+///
+#[derive(Clone, Debug)]
+pub struct JsonWebTokenBuilder<C, JE, JS, JT>
+where
+    C: Debug + DeserializeOwned + Serialize,
+    JE: JweContentEncryptionAlgorithm,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    claims: C,
+    content_type: Option<JsonWebTokenContentType>,
+    key_id: Option<JsonWebKeyId>,
+    type_: Option<JsonWebTokenType>,
+    _phantom_je: PhantomData<JE>,
+    _phantom_js: PhantomData<JS>,
+    _phantom_jt: PhantomData<JT>,
+}
+impl<C, JE, JS, JT> JsonWebTokenBuilder<C, JE, JS, JT>
+where
+    C: Debug + DeserializeOwned + Serialize,
+    JE: JweContentEncryptionAlgorithm,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    ///
+    /// Creates a new builder for a token containing the given claims.
+    ///
+    pub fn new(claims: C) -> Self {
+        JsonWebTokenBuilder {
+            claims,
+            content_type: None,
+            key_id: None,
+            type_: None,
+            _phantom_je: PhantomData,
+            _phantom_js: PhantomData,
+            _phantom_jt: PhantomData,
+        }
+    }
+
+    ///
+    /// Sets the `cty` (content type) JOSE header parameter.
+    ///
+    pub fn set_content_type(mut self, content_type: Option<JsonWebTokenContentType>) -> Self {
+        self.content_type = content_type;
+        self
+    }
+
+    ///
+    /// Sets the `kid` (key ID) JOSE header parameter.
+    ///
+    /// If no key ID is set, the ID of the signing key's verification key (if any) is used.
+    ///
+    pub fn set_key_id(mut self, key_id: Option<JsonWebKeyId>) -> Self {
+        self.key_id = key_id;
+        self
+    }
+
+    ///
+    /// Sets the `typ` (type) JOSE header parameter.
+    ///
+    pub fn set_type(mut self, type_: Option<JsonWebTokenType>) -> Self {
+        self.type_ = type_;
+        self
+    }
+
+    ///
+    /// Serializes the JOSE header and claims and signs them using the given key and signature
+    /// algorithm.
+    ///
+    pub fn sign<JU, K, S>(
+        self,
+        signing_key: &S,
+        signature_alg: &JS,
+    ) -> Result<JsonWebToken<C, JE, JS, JT, JsonWebTokenJsonPayloadDeserializer>, JsonWebTokenError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        S: PrivateSigningKey<JS, JT, JU, K>,
+    {
+        let key_id = match self.key_id {
+            Some(key_id) => Some(key_id),
+            None => signing_key.as_verification_key().key_id().cloned(),
+        };
+        let header = JsonWebTokenHeader {
+            alg: JsonWebTokenAlgorithm::Signature(signature_alg.clone(), PhantomData),
+            crit: None,
+            cty: self.content_type,
+            kid: key_id,
+            typ: self.type_,
+            _phantom_jt: PhantomData,
+        };
+        let signing_input = signing_input(&header, &self.claims)?;
+
+        let signature = signing_key
+            .sign(signature_alg, signing_input.as_bytes())
+            .map_err(JsonWebTokenError::Signing)?;
+        let raw_token = format!(
+            "{}.{}",
+            signing_input,
+            base64::encode_config(&signature, base64::URL_SAFE_NO_PAD)
+        );
+
+        Ok(JsonWebToken {
+            header,
+            claims: self.claims,
+            signature,
+            signing_input,
+            raw_token,
+            _phantom: PhantomData,
+        })
+    }
+
+    ///
+    /// Serializes the JOSE header and claims without signing them, using the `none` algorithm.
+    ///
+    /// # Security Warning
+    ///
+    /// Unsigned tokens provide no integrity protection. They should only be sent over channels
+    /// that otherwise protect the token from tampering, or be encrypted to the recipient.
+    ///
+    pub fn build_unsigned(
+        self,
+    ) -> Result<JsonWebToken<C, JE, JS, JT, JsonWebTokenJsonPayloadDeserializer>, JsonWebTokenError>
+    {
+        let header = JsonWebTokenHeader {
+            alg: JsonWebTokenAlgorithm::None,
+            crit: None,
+            cty: self.content_type,
+            kid: self.key_id,
+            typ: self.type_,
+            _phantom_jt: PhantomData,
+        };
+        let signing_input = signing_input(&header, &self.claims)?;
+        // Unsecured JWTs end with an empty signature (see Section 6.1 of RFC 7519).
+        let raw_token = format!("{}.", signing_input);
+
+        Ok(JsonWebToken {
+            header,
+            claims: self.claims,
+            signature: Vec::new(),
+            signing_input,
+            raw_token,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+fn signing_input<C, JE, JS, JT>(
+    header: &JsonWebTokenHeader<JE, JS, JT>,
+    claims: &C,
+) -> Result<String, JsonWebTokenError>
+where
+    C: Serialize,
+    JE: JweContentEncryptionAlgorithm,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    let header_json = serde_json::to_string(header).map_err(JsonWebTokenError::Serialization)?;
+    let claims_json = serde_json::to_string(claims).map_err(JsonWebTokenError::Serialization)?;
+    Ok(format!(
+        "{}.{}",
+        base64::encode_config(&header_json, base64::URL_SAFE_NO_PAD),
+        base64::encode_config(&claims_json, base64::URL_SAFE_NO_PAD)
+    ))
+}
+
 impl<'de, C, JE, JS, JT, P> Deserialize<'de> for JsonWebToken<C, JE, JS, JT, P>
 where
     C: Debug + DeserializeOwned + Serialize,
@@ -388,12 +643,277 @@ where
     }
 }
 
+// JOSE header of a JWE. Unlike the JWS header above, the 'alg' field always contains a key
+// management algorithm, and the 'enc' field specifies how the content itself is encrypted.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct JsonWebEncryptionHeader<JE, JK>
+where
+    JE: JweContentEncryptionAlgorithm,
+    JK: JweKeyManagementAlgorithm,
+{
+    #[serde(bound = "JE: JweContentEncryptionAlgorithm, JK: JweKeyManagementAlgorithm")]
+    pub alg: JK,
+    #[serde(bound = "JE: JweContentEncryptionAlgorithm, JK: JweKeyManagementAlgorithm")]
+    pub enc: JE,
+    // See the comment in JsonWebTokenHeader.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crit: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cty: Option<JsonWebTokenContentType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kid: Option<JsonWebKeyId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub typ: Option<JsonWebTokenType>,
+    // Compression algorithm applied to the plaintext before encryption.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zip: Option<String>,
+}
+
+///
+/// Output of encrypting content using a
+/// [`PublicEncryptionKey`](trait.PublicEncryptionKey.html), from which
+/// [`JsonWebEncryption::encrypt`](struct.JsonWebEncryption.html#method.encrypt) assembles the
+/// JWE compact serialization.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct EncryptedContent {
+    /// JWE Encrypted Key, which must be empty when using direct encryption.
+    pub encrypted_key: Vec<u8>,
+    /// JWE Initialization Vector.
+    pub initialization_vector: Vec<u8>,
+    /// JWE Ciphertext.
+    pub ciphertext: Vec<u8>,
+    /// JWE Authentication Tag.
+    pub authentication_tag: Vec<u8>,
+}
+
+///
+/// [JSON Web Encryption](https://tools.ietf.org/html/rfc7516) (JWE) content in its compact
+/// serialization.
+///
+/// The JOSE header is only parsed when the content is decrypted, since the key management
+/// algorithm types aren't known until then.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct JsonWebEncryption {
+    encoded_header: String,
+    header_json: String,
+    encrypted_key: Vec<u8>,
+    initialization_vector: Vec<u8>,
+    ciphertext: Vec<u8>,
+    authentication_tag: Vec<u8>,
+    raw_token: String,
+}
+impl JsonWebEncryption {
+    ///
+    /// Encrypts `plaintext` to the given key using the given key management and content
+    /// encryption algorithms.
+    ///
+    /// The JOSE header identifies the key by its key ID (if any) and specifies the given content
+    /// type, which should be `JWT` when encrypting a nested JSON Web Token.
+    ///
+    pub fn encrypt<JE, JK, JS, JT, JU, K>(
+        plaintext: &[u8],
+        key: &K,
+        key_management_alg: &JK,
+        content_encryption_alg: &JE,
+        content_type: Option<JsonWebTokenContentType>,
+    ) -> Result<Self, JsonWebTokenError>
+    where
+        JE: JweContentEncryptionAlgorithm,
+        JK: JweKeyManagementAlgorithm,
+        JS: JwsSigningAlgorithm<JT>,
+        JT: JsonWebKeyType,
+        JU: JsonWebKeyUse,
+        K: PublicEncryptionKey<JE, JK, JS, JT, JU>,
+    {
+        let header = JsonWebEncryptionHeader {
+            alg: key_management_alg.clone(),
+            enc: content_encryption_alg.clone(),
+            crit: None,
+            cty: content_type,
+            kid: key.key_id().cloned(),
+            typ: None,
+            zip: None,
+        };
+        let header_json =
+            serde_json::to_string(&header).map_err(JsonWebTokenError::Serialization)?;
+        let encoded_header = base64::encode_config(&header_json, base64::URL_SAFE_NO_PAD);
+
+        let content = key
+            .encrypt(
+                key_management_alg,
+                content_encryption_alg,
+                encoded_header.as_bytes(),
+                plaintext,
+            ).map_err(JsonWebTokenError::Encryption)?;
+        let raw_token = [
+            encoded_header.clone(),
+            base64::encode_config(&content.encrypted_key, base64::URL_SAFE_NO_PAD),
+            base64::encode_config(&content.initialization_vector, base64::URL_SAFE_NO_PAD),
+            base64::encode_config(&content.ciphertext, base64::URL_SAFE_NO_PAD),
+            base64::encode_config(&content.authentication_tag, base64::URL_SAFE_NO_PAD),
+        ]
+        .join(".");
+
+        Ok(JsonWebEncryption {
+            encoded_header,
+            header_json,
+            encrypted_key: content.encrypted_key,
+            initialization_vector: content.initialization_vector,
+            ciphertext: content.ciphertext,
+            authentication_tag: content.authentication_tag,
+            raw_token,
+        })
+    }
+
+    pub(crate) fn unverified_header<JE, JK>(
+        &self,
+    ) -> Result<JsonWebEncryptionHeader<JE, JK>, serde_json::Error>
+    where
+        JE: JweContentEncryptionAlgorithm,
+        JK: JweKeyManagementAlgorithm,
+    {
+        serde_json::from_str(&self.header_json)
+    }
+
+    ///
+    /// Returns the JWE Encrypted Key, which is empty when using direct encryption.
+    ///
+    pub fn encrypted_key(&self) -> &[u8] {
+        &self.encrypted_key
+    }
+
+    ///
+    /// Returns the JWE Initialization Vector.
+    ///
+    pub fn initialization_vector(&self) -> &[u8] {
+        &self.initialization_vector
+    }
+
+    ///
+    /// Returns the JWE Ciphertext.
+    ///
+    pub fn ciphertext(&self) -> &[u8] {
+        &self.ciphertext
+    }
+
+    ///
+    /// Returns the JWE Authentication Tag.
+    ///
+    pub fn authentication_tag(&self) -> &[u8] {
+        &self.authentication_tag
+    }
+
+    ///
+    /// Returns the Additional Authenticated Data used by the content encryption algorithm, which
+    /// is the ASCII encoding of the base64url-encoded JWE Protected Header (see
+    /// [Section 5.2 of RFC 7516](https://tools.ietf.org/html/rfc7516#section-5.2)).
+    ///
+    pub fn additional_authenticated_data(&self) -> &[u8] {
+        self.encoded_header.as_bytes()
+    }
+
+    ///
+    /// Returns the JWE compact serialization of this token.
+    ///
+    pub fn raw_token(&self) -> &str {
+        &self.raw_token
+    }
+}
+impl<'de> Deserialize<'de> for JsonWebEncryption {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct JsonWebEncryptionVisitor;
+        impl<'de> Visitor<'de> for JsonWebEncryptionVisitor {
+            type Value = JsonWebEncryption;
+
+            fn expecting(&self, formatter: &mut Formatter) -> FormatterResult {
+                formatter.write_str("JsonWebEncryption")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: DeserializeError,
+            {
+                let parts = v.split('.').collect::<Vec<_>>();
+                if parts.len() != 5 {
+                    return Err(DeserializeError::custom(format!(
+                        "Invalid JSON web encryption: found {} parts (expected 5)",
+                        parts.len()
+                    )));
+                }
+
+                let decode_part = |part: &str, name: &str| {
+                    base64::decode_config(part, base64::URL_SAFE_NO_PAD).map_err(|err| {
+                        DeserializeError::custom(format!(
+                            "Invalid base64url {} encoding: {:?}",
+                            name, err
+                        ))
+                    })
+                };
+
+                let header_json =
+                    String::from_utf8(decode_part(parts[0], "header")?).map_err(|err| {
+                        DeserializeError::custom(format!(
+                            "Invalid UTF-8 header encoding: {:?}",
+                            err
+                        ))
+                    })?;
+
+                Ok(JsonWebEncryption {
+                    encoded_header: parts[0].to_string(),
+                    header_json,
+                    encrypted_key: decode_part(parts[1], "encrypted key")?,
+                    initialization_vector: decode_part(parts[2], "initialization vector")?,
+                    ciphertext: decode_part(parts[3], "ciphertext")?,
+                    authentication_tag: decode_part(parts[4], "authentication tag")?,
+                    raw_token: v.to_string(),
+                })
+            }
+        }
+        deserializer.deserialize_str(JsonWebEncryptionVisitor)
+    }
+}
+impl Serialize for JsonWebEncryption {
+    fn serialize<SE>(&self, serializer: SE) -> Result<SE::Ok, SE::Error>
+    where
+        SE: Serializer,
+    {
+        serializer.serialize_str(&self.raw_token)
+    }
+}
+
+// Derives a symmetric encryption key of `key_len` bytes from the client secret, as described in
+// Section 10.2 of OpenID Connect Core 1.0: the key consists of the left-most bits of the SHA-256,
+// SHA-384, or SHA-512 hash (the shortest one that's long enough) of the octets of the UTF-8
+// representation of the client secret. Returns `None` if no hash is long enough.
+pub(crate) fn client_secret_encryption_key(
+    client_secret: &ClientSecret,
+    key_len: usize,
+) -> Option<Vec<u8>> {
+    let digest_alg = if key_len <= 32 {
+        &digest::SHA256
+    } else if key_len <= 48 {
+        &digest::SHA384
+    } else if key_len <= 64 {
+        &digest::SHA512
+    } else {
+        return None;
+    };
+    let hash = digest::digest(digest_alg, client_secret.secret().as_bytes());
+    Some(hash.as_ref()[..key_len].to_vec())
+}
+
 #[cfg(test)]
 pub mod tests {
     use std::marker::PhantomData;
     use std::string::ToString;
 
-    use oauth2::prelude::NewType;
+    use oauth2::prelude::{NewType, SecretNewType};
+    use oauth2::ClientSecret;
     use serde::de::Error as DeserializeError;
     use serde_json;
 
@@ -401,10 +921,12 @@ pub mod tests {
         CoreJsonWebKey, CoreJsonWebKeyType, CoreJweContentEncryptionAlgorithm,
         CoreJwsSigningAlgorithm,
     };
-    use super::super::JsonWebKeyId;
+    use super::super::{JsonWebKeyId, PrivateSigningKey};
     use super::{
-        JsonWebToken, JsonWebTokenAccess, JsonWebTokenAlgorithm,
-        JsonWebTokenJsonPayloadDeserializer, JsonWebTokenPayloadDeserialize,
+        client_secret_encryption_key, JsonWebToken, JsonWebTokenAccess, JsonWebTokenAlgorithm,
+        JsonWebTokenBuilder, JsonWebTokenContentType, JsonWebTokenError,
+        JsonWebTokenJsonPayloadDeserializer, JsonWebTokenPayloadDeserialize, JsonWebTokenType,
+        SigningError,
     };
 
     type CoreAlgorithm = JsonWebTokenAlgorithm<
@@ -437,6 +959,48 @@ pub mod tests {
             \"e\": \"AQAB\"
         }";
 
+    // RSA key pair generated for testing signing; not from any RFC.
+    pub const TEST_RSA_PRIV_KEY: &str = "{
+            \"kty\": \"RSA\",
+            \"kid\": \"rsa-signing-key\",
+            \"use\": \"sig\",
+            \"n\": \"qYLWi987hmXvV2w6OKv0qnvlgnDbQGz3Gl9XlG8RDUQbxd6A-LgqecNA\
+                   SL1ZOXQPlpryEwLfsO3HMe3fQdJGmlmbfzAL76fkouT_vdC8DgtlPEKR\
+                   Tn9mKPfHTRl8uvjbv1Ned9rPaCVacvfdXsbX7f4Nu0buGAjhDQfzplSd\
+                   rO8pZ3orD2xg52tQWlVH3BTpXWvONeU5AdwH4zE6e67TZdBEgVlnRqLc\
+                   ll2gL7H6TOJDNVNhL8ra5jdsvCG34VYCV25ffgU5AGENm_vszwhI-WgA\
+                   2tVYY_wFLgJouNfT9Oy6QqbWx2MMAhRsfo5fvL6B2C0bflvw_eikeqH8\
+                   esPOGw\",
+            \"e\": \"AQAB\",
+            \"d\": \"DgsaZjUk09IfHZGIU0ifuP4RuhhKedBtOmCau1_mPECfwmlYKwLq9fKA\
+                   oWYafcGJRxNoKb4LtTdF0POhkuzuT3xL_yIGYyD4NqydKWchJVK5XOAw\
+                   9J1mY89_UfpiFIVogzXPdkOI-lpZ33t8eg0kN4HcNl8-e3xE1NULUQTi\
+                   mUCGhdsvaAQxIdXQ_yDH2iHCK3svpBP9MOYlZc1bKCy0z1bdKGJgVluA\
+                   P1KWLHg6XUFqTvnzOhyiJRe9UMovJK7-hygtSeZ9e5uWx38oWulnCzDf\
+                   KfHuLr1w_Ts7I7bierKspp0ZAnyyCrtb6sS80-JEdEf2ixPN0-_nm3A_\
+                   bD_dzQ\",
+            \"p\": \"34nkTXXI5W_jpyRAFdljf5_M2CAZIKHeOWMe0XRQQZHRIUm5ROGzqjoI\
+                   xYjhHImVBNrUVvim6BEHKftRXhiSvk8wWBW-sQkcVtaSLUgyKnBKUouW\
+                   6mebiI-Zf2D8lQRPTHtC-lOWuR2OJ7sj8UifheGCz4mSrdlEmS3XIoR7\
+                   AYc\",
+            \"q\": \"wiB1J6OzUVBNvs76A7Cfn5MTNBMgjSudm2RuHl2W3gUkYtWTasIcLDdH\
+                   jtsJ-PgYqyIdzq-RUvXqe7t8Z1KNONm-L0rdTFce4axnL3sPdKB1hyL9\
+                   QzaBvX0ingAV_EQiNbpLVmC3Y03irN05FaosgBM35Vsz627eA0SwgE6J\
+                   A80\",
+            \"dp\": \"bvZwEdNqChpkk093UGE89tlIQfCNCJnRSgNeBPML88CCY1fJU9wF53Yv\
+                    bH7p39Sb2HEX_qQnlgL932UVmvPvw_BjOxZwNkkjfCe_ICM6Y76h5h_M\
+                    pkcP-dXuiECIoSAmjPyS3tgndXSqYCaRTPCT3012NbdAzHORjcgnhsbk\
+                    mHk\",
+            \"dq\": \"JQFk89p721tQhrgT5tIri5LQzVN8GA0hbTbW2wkCMm6OghyujmJUVoVv\
+                    KadNnl1E7pKBVh4XuNXSDiYuT8_nxdeULpW528ybwW9I-D8gMh5IaStd\
+                    Vgu07bdF-xKPHliqoM1TgTLMoqx9MnC1ahDXjM6dECazngSzY7IbZ0oR\
+                    Sl0\",
+            \"qi\": \"niOx7TRy3U2Ct-3liieCq-FuLRZPStrryHZdE7ulaYvnjxtPdQYyloTB\
+                    MBxqGH7NJT6d9OdF4QoRauVn-N7FKVdEgJgk63EVSVHZoHCddUz0g-sD\
+                    St_e-mW7u10rUitmUn56xBWzXleIbXnz0OwGU64IjZoOBrF2MNvGmKHG\
+                    rgc\"
+        }";
+
     #[test]
     fn test_jwt_algorithm_deserialization() {
         assert_eq!(
@@ -689,4 +1253,150 @@ pub mod tests {
         ))).expect("failed to deserialize");
         assert_eq!(deserialized.unverified_claims().foo, "bar");
     }
+
+    #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+    struct TestClaims {
+        iss: String,
+        exp: u64,
+    }
+
+    type TestJsonWebToken = JsonWebToken<
+        TestClaims,
+        CoreJweContentEncryptionAlgorithm,
+        CoreJwsSigningAlgorithm,
+        CoreJsonWebKeyType,
+        JsonWebTokenJsonPayloadDeserializer,
+    >;
+
+    #[test]
+    fn test_jwt_builder() {
+        let private_key: CoreJsonWebKey =
+            serde_json::from_str(TEST_RSA_PRIV_KEY).expect("deserialization failed");
+        let claims = TestClaims {
+            iss: "joe".to_string(),
+            exp: 1300819380,
+        };
+
+        let jwt: TestJsonWebToken = JsonWebTokenBuilder::new(claims.clone())
+            .sign(&private_key, &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256)
+            .expect("failed to sign JWT");
+
+        {
+            let header = jwt.unverified_header();
+            assert_eq!(
+                header.alg,
+                JsonWebTokenAlgorithm::Signature(
+                    CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+                    PhantomData,
+                )
+            );
+            assert_eq!(header.crit, None);
+            assert_eq!(header.cty, None);
+            // The key ID defaults to that of the signing key.
+            assert_eq!(
+                header.kid,
+                Some(JsonWebKeyId::new("rsa-signing-key".to_string()))
+            );
+            assert_eq!(header.typ, None);
+        }
+
+        // The serialized token should round trip through deserialization and verify using the
+        // public key.
+        let serialized = serde_json::to_value(&jwt).expect("failed to serialize");
+        let deserialized: TestJsonWebToken =
+            serde_json::from_value(serialized).expect("failed to deserialize");
+        assert_eq!(deserialized, jwt);
+        assert_eq!(
+            deserialized
+                .claims(
+                    &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+                    &private_key.as_verification_key(),
+                ).expect("failed to verify JWT"),
+            claims
+        );
+
+        let jwt: TestJsonWebToken = JsonWebTokenBuilder::new(claims.clone())
+            .set_content_type(Some(JsonWebTokenContentType::new("JWT".to_string())))
+            .set_key_id(Some(JsonWebKeyId::new("other-key-id".to_string())))
+            .set_type(Some(JsonWebTokenType::new("JWT".to_string())))
+            .sign(&private_key, &CoreJwsSigningAlgorithm::RsaSsaPssSha256)
+            .expect("failed to sign JWT");
+        {
+            let header = jwt.unverified_header();
+            assert_eq!(
+                header.cty,
+                Some(JsonWebTokenContentType::new("JWT".to_string()))
+            );
+            assert_eq!(
+                header.kid,
+                Some(JsonWebKeyId::new("other-key-id".to_string()))
+            );
+            assert_eq!(header.typ, Some(JsonWebTokenType::new("JWT".to_string())));
+        }
+        assert_eq!(
+            jwt.claims(
+                &CoreJwsSigningAlgorithm::RsaSsaPssSha256,
+                &private_key.as_verification_key(),
+            ).expect("failed to verify JWT"),
+            claims
+        );
+
+        let jwt: TestJsonWebToken = JsonWebTokenBuilder::new(claims.clone())
+            .build_unsigned()
+            .expect("failed to build unsigned JWT");
+        assert_eq!(jwt.unverified_header().alg, JsonWebTokenAlgorithm::None);
+        assert_eq!(jwt.unverified_header().kid, None);
+        assert!(jwt.raw_token().ends_with('.'));
+        let serialized = serde_json::to_value(&jwt).expect("failed to serialize");
+        let deserialized: TestJsonWebToken =
+            serde_json::from_value(serialized).expect("failed to deserialize");
+        assert_eq!(deserialized, jwt);
+
+        // Public keys can't sign.
+        match JsonWebTokenBuilder::<
+            TestClaims,
+            CoreJweContentEncryptionAlgorithm,
+            CoreJwsSigningAlgorithm,
+            CoreJsonWebKeyType,
+        >::new(claims)
+        .sign(
+            &private_key.as_verification_key(),
+            &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+        ) {
+            Err(JsonWebTokenError::Signing(SigningError::InvalidKey(_))) => {}
+            other => panic!(format!("unexpected result: {:?}", other)),
+        }
+    }
+
+    #[test]
+    fn test_client_secret_encryption_key() {
+        let client_secret = ClientSecret::new("my_secret".to_string());
+
+        // Keys of up to 256 bits are the left-most bits of the SHA-256 hash.
+        assert_eq!(
+            Some(vec![
+                31, 146, 51, 161, 33, 5, 125, 206, 220, 11, 142, 109, 50, 251, 96, 93,
+            ]),
+            client_secret_encryption_key(&client_secret, 16)
+        );
+        // Longer keys use SHA-384 and SHA-512.
+        assert_eq!(
+            Some(vec![
+                0, 213, 169, 39, 50, 131, 220, 85, 18, 72, 109, 55, 168, 224, 142, 179, 28, 227,
+                21, 224, 251, 4, 232, 234, 112, 87, 51, 20, 123, 143, 45, 88, 182, 142, 34, 253,
+                243, 96, 2, 19, 24, 48, 208, 226, 92, 12, 25, 107,
+            ]),
+            client_secret_encryption_key(&client_secret, 48)
+        );
+        assert_eq!(
+            Some(vec![
+                81, 124, 194, 234, 205, 13, 48, 170, 251, 43, 241, 73, 186, 19, 230, 24, 23, 112,
+                246, 63, 248, 68, 114, 74, 216, 187, 211, 128, 11, 188, 93, 78, 119, 0, 117, 130,
+                10, 199, 5, 126, 16, 188, 117, 213, 240, 120, 114, 77, 32, 11, 42, 229, 231, 189,
+                98, 11, 207, 3, 0, 43, 230, 141, 151, 105,
+            ]),
+            client_secret_encryption_key(&client_secret, 64)
+        );
+        assert_eq!(None, client_secret_encryption_key(&client_secret, 65));
+    }
 }
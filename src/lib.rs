@@ -12,21 +12,32 @@
 // serializations will continue to deserialize; fields may be reordered, so assuming a particular
 // order is undefined behavior).
 
+extern crate aes;
+extern crate aes_gcm;
+extern crate aes_kw;
 extern crate base64;
+extern crate cbc;
 extern crate chrono;
+#[cfg(feature = "curl")]
 extern crate curl;
 extern crate failure;
 #[macro_use]
 extern crate failure_derive;
+extern crate futures;
 #[macro_use]
 extern crate log;
 extern crate oauth2;
 extern crate rand;
 extern crate ring;
+#[cfg(feature = "rsa-oaep")]
+extern crate rsa;
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
 extern crate serde_json;
+#[cfg(feature = "rsa-oaep")]
+extern crate sha1;
+extern crate sha2;
 extern crate untrusted;
 extern crate url;
 
@@ -37,23 +48,41 @@ use std::marker::PhantomData;
 use std::str;
 use std::time::Duration;
 
+use failure::Fail;
+use futures::{future, Future};
 use oauth2::helpers::variant_name;
 use oauth2::prelude::*;
 use oauth2::{
     AuthType, AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken, ErrorResponseType,
-    ExtraTokenFields, RedirectUrl, RequestTokenError, ResponseType as OAuth2ResponseType, Scope,
+    ExtraTokenFields, RedirectUrl, RefreshToken, ResponseType as OAuth2ResponseType, Scope,
     TokenResponse, TokenType, TokenUrl,
 };
+use serde_json::Value;
 use url::Url;
 
+pub use authorization::{
+    AuthErrorResponseType, AuthorizationErrorResponse, AuthorizationResponse,
+    AuthorizationResponseError,
+};
 pub use claims::{
     AdditionalClaims, AddressClaim, EmptyAdditionalClaims, GenderClaim, StandardClaims,
 };
+pub use claims_request::{ClaimsRequest, IndividualClaimRequest};
+#[cfg(feature = "curl")]
+pub use curl_client::CurlHttpClient;
 pub use discovery::{DiscoveryError, ProviderMetadata};
-pub use id_token::IdTokenFields;
+pub use http::{AsyncHttpClient, HttpClient, HttpRequest, HttpRequestMethod, HttpResponse};
+pub use id_token::{IdTokenFields, RefreshedIdTokenFields};
 pub use id_token::{IdToken, IdTokenClaims};
-use jwt::{JsonWebToken, JsonWebTokenAccess, JsonWebTokenAlgorithm, JsonWebTokenHeader};
+pub use jwt::{
+    EncryptedContent, EncryptionError, JsonWebEncryption, JsonWebToken, JsonWebTokenBuilder,
+    JsonWebTokenContentType, JsonWebTokenError, JsonWebTokenJsonPayloadDeserializer,
+    JsonWebTokenType, SigningError,
+};
+use jwt::{JsonWebTokenAccess, JsonWebTokenAlgorithm, JsonWebTokenHeader};
 use registration::ClientRegistrationResponse;
+pub use replay::{InMemoryReplayCache, ReplayCache};
+pub use request_object::{RequestObjectEncryption, RequestObjectError, RequestObjectSigning};
 // Flatten the module hierarchy involving types. They're only separated to improve code
 // organization.
 pub use types::{
@@ -65,16 +94,21 @@ pub use types::{
     EndUserName, EndUserNickname, EndUserPhoneNumber, EndUserPictureUrl, EndUserProfileUrl,
     EndUserTimezone, EndUserUsername, EndUserWebsiteUrl, FormattedAddress, GrantType,
     InitiateLoginUrl, IssuerUrl, JsonWebKey, JsonWebKeyId, JsonWebKeySet, JsonWebKeyType,
-    JsonWebKeyUse, JweContentEncryptionAlgorithm, JweKeyManagementAlgorithm, JwsSigningAlgorithm,
-    LanguageTag, LoginHint, LogoUrl, Nonce, OpPolicyUrl, OpTosUrl, PolicyUrl,
-    RegistrationAccessToken, RegistrationUrl, RequestUrl, ResponseMode, ResponseType,
-    ResponseTypes, SectorIdentifierUrl, ServiceDocUrl, StreetAddress, SubjectIdentifier,
-    SubjectIdentifierType, ToSUrl,
+    JsonWebKeyUse, JsonWebTokenId, JweContentEncryptionAlgorithm, JweKeyManagementAlgorithm,
+    JwsSigningAlgorithm, LanguageTag, LoginHint, LogoUrl, Nonce, OpPolicyUrl, OpTosUrl,
+    PkceCodeChallenge, PkceCodeChallengeMethod, PkceCodeVerifier, PolicyUrl, PrivateDecryptionKey,
+    PrivateSigningKey, PublicEncryptionKey, RegistrationAccessToken, RegistrationUrl, RequestUrl,
+    ResponseMode, ResponseType, ResponseTypes, SectorIdentifierUrl, ServiceDocUrl, StreetAddress,
+    SubjectIdentifier, SubjectIdentifierType, ToSUrl,
 };
+pub use token::RequestTokenError;
+use token::TokenRequestAuth;
 pub use user_info::{UserInfoClaims, UserInfoError, UserInfoUrl};
 use verification::{AudiencesClaim, IssuerClaim};
 pub use verification::{
-    ClaimsVerificationError, IdTokenVerifier, SignatureVerificationError, UserInfoVerifier,
+    AuthContextError, AuthContextLevels, AuthContextPolicy, AuthorizedPartyPolicy,
+    ClaimsVerificationError, DecryptionError, IdTokenVerifier, NonceVerifier,
+    SignatureVerificationError, UserInfoVerifier,
 };
 
 // Defined first since other modules need the macros, and definition order is significant for
@@ -88,18 +122,31 @@ pub mod registration;
 
 // Private modules since we may move types between different modules; these are exported publicly
 // via the pub use above.
+mod authorization;
 mod claims;
+mod claims_request;
 mod id_token;
+mod replay;
+mod request_object;
 mod types;
 mod user_info;
 mod verification;
 
-// Private module for HTTP(S) utilities.
+// Private module for HTTP(S) utilities. The HTTP client types are exported publicly via the pub use
+// above.
 mod http;
 
-// Private module for JWT utilities.
+// Private module for the libcurl-based HTTP client.
+#[cfg(feature = "curl")]
+mod curl_client;
+
+// Private module for JWT utilities. The public types are exported via the pub use above.
 mod jwt;
 
+// Private module for token endpoint requests. The error type is exported publicly via the pub use
+// above.
+mod token;
+
 const CONFIG_URL_SUFFIX: &str = ".well-known/openid-configuration";
 const OPENID_SCOPE: &str = "openid";
 
@@ -137,6 +184,50 @@ pub enum AuthenticationFlow<RT: ResponseType> {
     Hybrid(Vec<RT>),
 }
 
+///
+/// Error indicating that an authorization request parameter isn't supported by the OpenID
+/// Provider, according to its metadata.
+///
+#[derive(Debug, Fail)]
+pub enum ConfigurationError {
+    ///
+    /// The provider doesn't support the PKCE code challenge method.
+    ///
+    #[fail(display = "Unsupported PKCE code challenge method: {}", _0)]
+    UnsupportedPkceMethod(String),
+    ///
+    /// The provider doesn't support the response mode.
+    ///
+    #[fail(display = "Unsupported response mode: {}", _0)]
+    UnsupportedResponseMode(String),
+    ///
+    /// The provider doesn't support the `claims` request parameter.
+    ///
+    #[fail(display = "Unsupported `claims` request parameter")]
+    UnsupportedClaimsParameter,
+    ///
+    /// The provider doesn't support the `request` parameter.
+    ///
+    #[fail(display = "Unsupported `request` parameter")]
+    UnsupportedRequestParameter,
+    ///
+    /// The provider doesn't support the request object signing algorithm.
+    ///
+    #[fail(display = "Unsupported request object signing algorithm: {}", _0)]
+    UnsupportedRequestObjectSigningAlg(String),
+    ///
+    /// The provider doesn't support the request object key management or content encryption
+    /// algorithm.
+    ///
+    #[fail(display = "Unsupported request object encryption algorithm: {}", _0)]
+    UnsupportedRequestObjectEncryptionAlg(String),
+    ///
+    /// The minimum authentication context class reference isn't one of the recognized levels.
+    ///
+    #[fail(display = "Unrecognized authentication context class: {}", _0)]
+    UnrecognizedAuthContextClass(String),
+}
+
 pub struct Client<AC, AD, CA, CN, CT, G, GC, JE, JK, JS, JT, P, PM, RM, RT, S, TE, TT>
 where
     AC: AdditionalClaims,
@@ -160,13 +251,20 @@ where
 {
     oauth2_client: oauth2::Client<IdTokenFields<AC, GC, JE, JS, JT>, TT, TE>,
     acr_values: Option<Vec<AuthenticationContextClass>>,
+    auth_type: AuthType,
     claims_locales: Option<Vec<LanguageTag>>,
+    claims_request: Option<ClaimsRequest>,
     client_id: ClientId,
     client_secret: Option<ClientSecret>,
+    default_max_age: Option<Duration>,
     display: Option<AD>,
     max_age: Option<Duration>,
     prompts: Option<Vec<P>>,
     provider_metadata: Option<PM>,
+    redirect_uri: Option<RedirectUrl>,
+    require_auth_time: bool,
+    response_mode: Option<RM>,
+    token_url: Option<TokenUrl>,
     ui_locales: Option<Vec<LanguageTag>>,
     _phantom_ca: PhantomData<CA>,
     _phantom_cn: PhantomData<CN>,
@@ -174,7 +272,6 @@ where
     _phantom_g: PhantomData<G>,
     _phantom_jk: PhantomData<JK>,
     _phantom_jt: PhantomData<JT>,
-    _phantom_rm: PhantomData<RM>,
     _phantom_rt: PhantomData<RT>,
     _phantom_s: PhantomData<S>,
     // FIXME: Other parameters MAY be sent. See Sections 3.2.2, 3.3.2, 5.2, 5.5, 6, and 7.2.1 for
@@ -213,18 +310,25 @@ where
             client_id.clone(),
             client_secret.clone(),
             auth_url,
-            token_url,
+            token_url.clone(),
         ).add_scope(Scope::new(OPENID_SCOPE.to_string()));
         Client {
             oauth2_client,
             acr_values: None,
+            auth_type: AuthType::BasicAuth,
             claims_locales: None,
+            claims_request: None,
             client_id,
             client_secret,
+            default_max_age: None,
             display: None,
             max_age: None,
             prompts: None,
             provider_metadata: None,
+            redirect_uri: None,
+            require_auth_time: false,
+            response_mode: None,
+            token_url,
             ui_locales: None,
             _phantom_ca: PhantomData,
             _phantom_cn: PhantomData,
@@ -232,47 +336,53 @@ where
             _phantom_g: PhantomData,
             _phantom_jk: PhantomData,
             _phantom_jt: PhantomData,
-            _phantom_rm: PhantomData,
             _phantom_rt: PhantomData,
             _phantom_s: PhantomData,
         }
     }
 
-    pub fn discover(
+    pub fn discover<HC>(
         client_id: ClientId,
         client_secret: Option<ClientSecret>,
         issuer_url: &IssuerUrl,
-    ) -> Result<Self, DiscoveryError> {
-        let provider_metadata: PM = discovery::get_provider_metadata(issuer_url)?;
-
-        let oauth2_client = oauth2::Client::new(
-            client_id.clone(),
-            client_secret.clone(),
-            provider_metadata.authorization_endpoint().clone(),
-            provider_metadata.token_endpoint().cloned(),
-        ).add_scope(Scope::new(OPENID_SCOPE.to_string()));
-        Ok(Client {
-            oauth2_client,
-            acr_values: None,
-            claims_locales: None,
+        http_client: &HC,
+    ) -> Result<Self, DiscoveryError<HC::Error>>
+    where
+        HC: HttpClient,
+    {
+        let provider_metadata: PM = discovery::get_provider_metadata(issuer_url, http_client)?;
+        Ok(Self::from_provider_metadata(
             client_id,
             client_secret,
-            display: None,
-            max_age: None,
-            prompts: None,
-            provider_metadata: Some(provider_metadata),
-            ui_locales: None,
-            _phantom_ca: PhantomData,
-            _phantom_cn: PhantomData,
-            _phantom_ct: PhantomData,
-            _phantom_g: PhantomData,
-            _phantom_jk: PhantomData,
-            _phantom_jt: PhantomData,
-            _phantom_rm: PhantomData,
-            _phantom_rt: PhantomData,
-            _phantom_s: PhantomData,
-        })
+            provider_metadata,
+        ))
     }
+
+    ///
+    /// Asynchronously discovers the OpenID Provider metadata and instantiates a new client.
+    ///
+    /// This is the asynchronous counterpart of `discover()`.
+    ///
+    pub fn discover_async<'a, HC>(
+        client_id: ClientId,
+        client_secret: Option<ClientSecret>,
+        issuer_url: &IssuerUrl,
+        http_client: &HC,
+    ) -> Box<Future<Item = Self, Error = DiscoveryError<HC::Error>> + 'a>
+    where
+        HC: AsyncHttpClient,
+        HC::Future: 'a,
+        Self: 'a,
+    {
+        Box::new(
+            discovery::get_provider_metadata_async(issuer_url, http_client).map(
+                move |provider_metadata: PM| {
+                    Self::from_provider_metadata(client_id, client_secret, provider_metadata)
+                },
+            ),
+        )
+    }
+
     pub fn from_dynamic_registration<AT, CR, JU, K>(
         provider_metadata: &PM,
         registration_response: &CR,
@@ -283,33 +393,29 @@ where
         JU: JsonWebKeyUse,
         K: JsonWebKey<JS, JT, JU>,
     {
-        let oauth2_client = oauth2::Client::new(
+        let mut client = Self::from_provider_metadata(
             registration_response.client_id().clone(),
             registration_response.client_secret().cloned(),
+            provider_metadata.clone(),
+        );
+        client.default_max_age = registration_response.default_max_age().cloned();
+        client.require_auth_time = registration_response.require_auth_time().unwrap_or(false);
+        client
+    }
+
+    fn from_provider_metadata(
+        client_id: ClientId,
+        client_secret: Option<ClientSecret>,
+        provider_metadata: PM,
+    ) -> Self {
+        let mut client = Self::new(
+            client_id,
+            client_secret,
             provider_metadata.authorization_endpoint().clone(),
             provider_metadata.token_endpoint().cloned(),
-        ).add_scope(Scope::new(OPENID_SCOPE.to_string()));
-        Client {
-            oauth2_client,
-            acr_values: None,
-            claims_locales: None,
-            client_id: registration_response.client_id().clone(),
-            client_secret: registration_response.client_secret().cloned(),
-            display: None,
-            max_age: None,
-            prompts: None,
-            provider_metadata: Some(provider_metadata.clone()),
-            ui_locales: None,
-            _phantom_ca: PhantomData,
-            _phantom_cn: PhantomData,
-            _phantom_ct: PhantomData,
-            _phantom_g: PhantomData,
-            _phantom_jk: PhantomData,
-            _phantom_jt: PhantomData,
-            _phantom_rm: PhantomData,
-            _phantom_rt: PhantomData,
-            _phantom_s: PhantomData,
-        }
+        );
+        client.provider_metadata = Some(provider_metadata);
+        client
     }
 
     ///
@@ -328,7 +434,7 @@ where
     /// [Section 2.3.1 of RFC 6749](https://tools.ietf.org/html/rfc6749#section-2.3.1).
     ///
     pub fn set_auth_type(mut self, auth_type: AuthType) -> Self {
-        self.oauth2_client = self.oauth2_client.set_auth_type(auth_type);
+        self.auth_type = auth_type;
         self
     }
 
@@ -336,7 +442,8 @@ where
     /// Sets the the redirect URL used by the authorization endpoint.
     ///
     pub fn set_redirect_uri(mut self, redirect_uri: RedirectUrl) -> Self {
-        self.oauth2_client = self.oauth2_client.set_redirect_url(redirect_uri);
+        self.oauth2_client = self.oauth2_client.set_redirect_url(redirect_uri.clone());
+        self.redirect_uri = Some(redirect_uri);
         self
     }
 
@@ -359,6 +466,37 @@ where
         self
     }
 
+    pub fn claims_request(&self) -> Option<&ClaimsRequest> {
+        self.claims_request.as_ref()
+    }
+    ///
+    /// Sets the `claims` authorization request parameter, which requests individual claims in
+    /// the ID token and UserInfo response.
+    ///
+    /// If the provider metadata indicates that the `claims` parameter isn't supported, an error
+    /// is returned. If the provider metadata omits `claims_parameter_supported`, a warning is
+    /// logged since the provider may ignore the parameter.
+    ///
+    pub fn set_claims_request(
+        mut self,
+        claims_request: Option<ClaimsRequest>,
+    ) -> Result<Self, ConfigurationError> {
+        if let (Some(_), Some(provider_metadata)) =
+            (claims_request.as_ref(), self.provider_metadata.as_ref())
+        {
+            match provider_metadata.claims_parameter_supported() {
+                Some(true) => {}
+                Some(false) => return Err(ConfigurationError::UnsupportedClaimsParameter),
+                None => warn!(
+                    "provider metadata does not advertise `claims_parameter_supported`; the \
+                     provider may ignore the `claims` parameter"
+                ),
+            }
+        }
+        self.claims_request = claims_request;
+        Ok(self)
+    }
+
     pub fn display(&self) -> Option<&AD> {
         self.display.as_ref()
     }
@@ -375,6 +513,24 @@ where
         self
     }
 
+    ///
+    /// Returns the default maximum authentication age registered by the client, which the
+    /// provider applies when the authentication request doesn't specify
+    /// [`max_age`](#method.set_max_age).
+    ///
+    pub fn default_max_age(&self) -> Option<&Duration> {
+        self.default_max_age.as_ref()
+    }
+    ///
+    /// Sets the default maximum authentication age registered by the client. Clients created
+    /// using [`from_dynamic_registration`](#method.from_dynamic_registration) use the registered
+    /// `default_max_age`.
+    ///
+    pub fn set_default_max_age(mut self, default_max_age: Option<Duration>) -> Self {
+        self.default_max_age = default_max_age;
+        self
+    }
+
     pub fn prompts(&self) -> Option<&Vec<P>> {
         self.prompts.as_ref()
     }
@@ -383,6 +539,59 @@ where
         self
     }
 
+    ///
+    /// Returns whether the client registered with `require_auth_time`, requiring ID tokens to
+    /// contain an `auth_time` claim.
+    ///
+    pub fn require_auth_time(&self) -> bool {
+        self.require_auth_time
+    }
+    ///
+    /// Sets whether the client registered with `require_auth_time`. Clients created using
+    /// [`from_dynamic_registration`](#method.from_dynamic_registration) use the registered value.
+    ///
+    pub fn set_require_auth_time(mut self, require_auth_time: bool) -> Self {
+        self.require_auth_time = require_auth_time;
+        self
+    }
+
+    pub fn response_mode(&self) -> Option<&RM> {
+        self.response_mode.as_ref()
+    }
+    ///
+    /// Sets the `response_mode` authorization request parameter, which determines how the
+    /// authorization response is returned to the redirect URI (e.g., `form_post`).
+    ///
+    /// If the provider metadata advertises `response_modes_supported`, an error is returned when
+    /// the response mode isn't one of them. Otherwise, a warning is logged since the provider may
+    /// not support the response mode.
+    ///
+    pub fn set_response_mode(
+        mut self,
+        response_mode: Option<RM>,
+    ) -> Result<Self, ConfigurationError> {
+        if let (Some(response_mode), Some(provider_metadata)) =
+            (response_mode.as_ref(), self.provider_metadata.as_ref())
+        {
+            match provider_metadata.response_modes_supported() {
+                Some(supported_modes) => {
+                    if !supported_modes.contains(response_mode) {
+                        return Err(ConfigurationError::UnsupportedResponseMode(
+                            variant_name(response_mode).to_string(),
+                        ));
+                    }
+                }
+                None => warn!(
+                    "provider metadata does not advertise `response_modes_supported`; the \
+                     provider may not support response mode `{}`",
+                    variant_name(response_mode)
+                ),
+            }
+        }
+        self.response_mode = response_mode;
+        Ok(self)
+    }
+
     pub fn ui_locales(&self) -> Option<&Vec<LanguageTag>> {
         self.ui_locales.as_ref()
     }
@@ -391,8 +600,25 @@ where
         self
     }
 
-    pub fn id_token_verifier<JU, K>(&self) -> Result<IdTokenVerifier<JS, JT, JU, K>, DiscoveryError>
+    ///
+    /// Returns a verifier for ID tokens issued to this client, using the signing keys published
+    /// by the provider.
+    ///
+    /// The verifier requires the `auth_time` claim if the client registered with
+    /// `require_auth_time`, and enforces the [`max_age`](#method.set_max_age) authentication
+    /// request parameter or, if not set, the registered
+    /// [`default_max_age`](#method.set_default_max_age). If the client requests
+    /// [`acr_values`](#method.set_auth_context_values), any `acr` claim must be one of the
+    /// requested values (see [`AuthContextPolicy::AnyOf`]). If the
+    /// [`claims` request](#method.set_claims_request) marks specific `acr` values as essential,
+    /// the `acr` claim is required and must be one of them instead.
+    ///
+    pub fn id_token_verifier<HC, JU, K>(
+        &self,
+        http_client: &HC,
+    ) -> Result<IdTokenVerifier<JS, JT, JU, K>, DiscoveryError<HC::Error>>
     where
+        HC: HttpClient,
         JU: JsonWebKeyUse,
         K: JsonWebKey<JS, JT, JU>,
     {
@@ -403,20 +629,56 @@ where
         let jwks_uri = provider_metadata.jwks_uri().ok_or_else(|| {
             DiscoveryError::Other("provider metadata contains no `jwks_uri`".to_string())
         })?;
-        let signature_keys = jwks_uri.get_keys()?;
-        if let Some(ref client_secret) = self.client_secret {
-            Ok(IdTokenVerifier::new_private_client(
+        let signature_keys = jwks_uri.get_keys(http_client)?;
+        let verifier = if let Some(ref client_secret) = self.client_secret {
+            IdTokenVerifier::new_private_client(
                 self.client_id.clone(),
                 client_secret.clone(),
                 provider_metadata.issuer().clone(),
                 signature_keys,
-            ))
+            )
         } else {
-            Ok(IdTokenVerifier::new_public_client(
+            IdTokenVerifier::new_public_client(
                 self.client_id.clone(),
                 provider_metadata.issuer().clone(),
                 signature_keys,
-            ))
+            )
+        };
+        Ok(self.configure_id_token_verifier(verifier))
+    }
+
+    // Configures an ID token verifier to enforce the parameters of this client's authentication
+    // requests and its registered metadata.
+    fn configure_id_token_verifier<JU, K>(
+        &self,
+        verifier: IdTokenVerifier<JS, JT, JU, K>,
+    ) -> IdTokenVerifier<JS, JT, JU, K>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+    {
+        let verifier = verifier.require_auth_time(self.require_auth_time);
+        // Specific acr values requested via the claims parameter take precedence over the
+        // acr_values parameter.
+        let auth_context_policy = self
+            .claims_request
+            .as_ref()
+            .and_then(ClaimsRequest::auth_context_policy)
+            .or_else(|| {
+                self.acr_values
+                    .as_ref()
+                    .map(|acr_values| AuthContextPolicy::AnyOf(acr_values.clone()))
+            });
+        let verifier = if let Some(auth_context_policy) = auth_context_policy {
+            verifier.set_auth_context_policy(auth_context_policy)
+        } else {
+            verifier
+        };
+        // The max_age request parameter overrides the registered default_max_age.
+        if let Some(max_age) = self.max_age.or(self.default_max_age) {
+            verifier.set_max_auth_age(max_age)
+        } else {
+            verifier
         }
     }
 
@@ -430,9 +692,19 @@ where
         NF: Fn() -> Nonce,
         SF: Fn() -> CsrfToken,
     {
-        self.authorize_url_with_hint(authentication_flow, state_fn, nonce_fn, None, None)
+        self.authorize_url_impl(authentication_flow, state_fn, nonce_fn, None, None, None)
     }
 
+    ///
+    /// Returns an authorization URL that includes the given `id_token_hint` and `login_hint`
+    /// parameters.
+    ///
+    /// Encrypted ID tokens must be decrypted using [`IdToken::decrypt`] before being used as an
+    /// `id_token_hint`, since the provider can't decrypt an ID token encrypted to the client (see
+    /// [Section 3.1.2.1 of OpenID Connect Core 1.0](
+    ///     https://openid.net/specs/openid-connect-core-1_0.html#AuthRequest)). An
+    /// `id_token_hint` that is still encrypted is omitted from the URL, and a warning is logged.
+    ///
     pub fn authorize_url_with_hint<NF, SF>(
         &self,
         authentication_flow: &AuthenticationFlow<RT>,
@@ -441,6 +713,324 @@ where
         id_token_hint: Option<&IdToken<AC, GC, JE, JS, JT>>,
         login_hint: Option<&LoginHint>,
     ) -> (Url, CsrfToken, Nonce)
+    where
+        NF: Fn() -> Nonce,
+        SF: Fn() -> CsrfToken,
+    {
+        self.authorize_url_impl(
+            authentication_flow,
+            state_fn,
+            nonce_fn,
+            id_token_hint,
+            login_hint,
+            None,
+        )
+    }
+
+    ///
+    /// Returns an authorization URL protected by the given
+    /// [PKCE](https://tools.ietf.org/html/rfc7636) code challenge, along with any
+    /// `id_token_hint` and `login_hint` parameters (see
+    /// [`authorize_url_with_hint`](#method.authorize_url_with_hint)). The corresponding code
+    /// verifier must be passed to [`exchange_code_with_pkce`](#method.exchange_code_with_pkce)
+    /// when exchanging the resulting authorization code.
+    ///
+    /// A new code verifier must be generated for every authorization request (see
+    /// [Section 4.1 of RFC 7636](https://tools.ietf.org/html/rfc7636#section-4.1)).
+    ///
+    /// If the provider metadata advertises `code_challenge_methods_supported`, an error is
+    /// returned when the code challenge method isn't one of them. Otherwise, a warning is logged
+    /// since the provider may silently ignore the code challenge.
+    ///
+    pub fn authorize_url_with_pkce<NF, SF>(
+        &self,
+        authentication_flow: &AuthenticationFlow<RT>,
+        state_fn: SF,
+        nonce_fn: NF,
+        pkce_challenge: &PkceCodeChallenge,
+        id_token_hint: Option<&IdToken<AC, GC, JE, JS, JT>>,
+        login_hint: Option<&LoginHint>,
+    ) -> Result<(Url, CsrfToken, Nonce), ConfigurationError>
+    where
+        NF: Fn() -> Nonce,
+        SF: Fn() -> CsrfToken,
+    {
+        self.check_pkce_method(pkce_challenge.method())?;
+        Ok(self.authorize_url_impl(
+            authentication_flow,
+            state_fn,
+            nonce_fn,
+            id_token_hint,
+            login_hint,
+            Some(pkce_challenge),
+        ))
+    }
+
+    ///
+    /// Returns an authorization URL that passes the authorization request parameters in a
+    /// request object (see [Section 6.1 of OpenID Connect Core 1.0](
+    ///     https://openid.net/specs/openid-connect-core-1_0.html#RequestObject)).
+    ///
+    /// The request object contains the same parameters that
+    /// [`authorize_url`](#method.authorize_url) would produce from this client's current
+    /// settings, along with the `iss` (the client ID) and `aud` (the provider's issuer, if
+    /// known) claims. Only the `response_type`, `client_id`, `redirect_uri`, and `scope`
+    /// parameters required by OAuth 2.0 are repeated in the URL itself. Any
+    /// [PKCE](https://tools.ietf.org/html/rfc7636) code challenge is included in the request
+    /// object and checked against the provider metadata as described in
+    /// [`authorize_url_with_pkce`](#method.authorize_url_with_pkce).
+    ///
+    /// If the provider metadata advertises `request_parameter_supported` or the supported request
+    /// object algorithms, an error is returned when the request object isn't supported.
+    /// Otherwise, a warning is logged since the provider may ignore the request object.
+    ///
+    pub fn authorize_url_with_request_object<NF, SF, JU, K>(
+        &self,
+        authentication_flow: &AuthenticationFlow<RT>,
+        state_fn: SF,
+        nonce_fn: NF,
+        signing: &RequestObjectSigning<JS, K>,
+        encryption: Option<&RequestObjectEncryption<JE, JK, K>>,
+        pkce_challenge: Option<&PkceCodeChallenge>,
+    ) -> Result<(Url, CsrfToken, Nonce), RequestObjectError>
+    where
+        NF: Fn() -> Nonce,
+        SF: Fn() -> CsrfToken,
+        JU: JsonWebKeyUse,
+        K: PrivateSigningKey<JS, JT, JU, K> + PublicEncryptionKey<JE, JK, JS, JT, JU>,
+    {
+        self.check_request_object_support(signing, encryption)
+            .map_err(RequestObjectError::Configuration)?;
+        if let Some(pkce_challenge) = pkce_challenge {
+            self.check_pkce_method(pkce_challenge.method())
+                .map_err(RequestObjectError::Configuration)?;
+        }
+
+        let (url, state, nonce) = self.authorize_url_impl(
+            authentication_flow,
+            state_fn,
+            nonce_fn,
+            None,
+            None,
+            pkce_challenge,
+        );
+
+        let mut claims = serde_json::Map::new();
+        for (name, value) in url.query_pairs() {
+            let value = match name.as_ref() {
+                // Unlike the corresponding URL parameters, these request object members are a
+                // JSON object and a number, respectively.
+                "claims" => serde_json::to_value(&self.claims_request)
+                    .expect("failed to serialize claims request"),
+                "max_age" => Value::from(self.max_age().map(|max_age| max_age.as_secs())),
+                _ => Value::String(value.into_owned()),
+            };
+            claims.insert(name.into_owned(), value);
+        }
+        claims.insert("iss".to_string(), Value::String(self.client_id.to_string()));
+        if let Some(ref provider_metadata) = self.provider_metadata {
+            claims.insert(
+                "aud".to_string(),
+                Value::String(provider_metadata.issuer().to_string()),
+            );
+        }
+
+        let builder = JsonWebTokenBuilder::<_, JE, JS, JT>::new(Value::Object(claims));
+        let request_jwt = match *signing {
+            RequestObjectSigning::Unsigned => builder.build_unsigned(),
+            RequestObjectSigning::ClientSecret(ref signing_alg) => {
+                let client_secret = self
+                    .client_secret
+                    .as_ref()
+                    .ok_or(RequestObjectError::MissingClientSecret)?;
+                builder.sign(
+                    &K::new_symmetric(client_secret.secret().as_bytes().to_vec()),
+                    signing_alg,
+                )
+            }
+            RequestObjectSigning::PrivateKey(signing_key, ref signing_alg) => {
+                builder.sign(signing_key, signing_alg)
+            }
+        }.map_err(RequestObjectError::JsonWebToken)?;
+
+        let request = match encryption {
+            Some(encryption) => {
+                let client_secret_key;
+                let key = match encryption.key() {
+                    Some(key) => key,
+                    None => {
+                        client_secret_key = self.client_secret_encryption_key(encryption)?;
+                        &client_secret_key
+                    }
+                };
+                JsonWebEncryption::encrypt(
+                    request_jwt.raw_token().as_bytes(),
+                    key,
+                    encryption.key_management_alg(),
+                    encryption.content_encryption_alg(),
+                    Some(JsonWebTokenContentType::new("JWT".to_string())),
+                ).map_err(RequestObjectError::JsonWebToken)?
+                .raw_token()
+                .to_string()
+            }
+            None => request_jwt.raw_token().to_string(),
+        };
+
+        let url = self.oauth2_client.authorize_url_extension(
+            &oauth2_response_type(authentication_flow),
+            &[("request", request.as_str())],
+        );
+        Ok((url, state, nonce))
+    }
+
+    // Derives the symmetric key for encrypting request objects from the client secret (see
+    // Section 10.2 of OpenID Connect Core 1.0).
+    fn client_secret_encryption_key<JU, K>(
+        &self,
+        encryption: &RequestObjectEncryption<JE, JK, K>,
+    ) -> Result<K, RequestObjectError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+    {
+        let client_secret = self
+            .client_secret
+            .as_ref()
+            .ok_or(RequestObjectError::MissingClientSecret)?;
+        let key_management_alg = encryption.key_management_alg();
+        key_management_alg
+            .symmetric_key_len(encryption.content_encryption_alg())
+            .and_then(|key_len| jwt::client_secret_encryption_key(client_secret, key_len))
+            .map(K::new_symmetric)
+            .ok_or_else(|| {
+                RequestObjectError::JsonWebToken(JsonWebTokenError::Encryption(
+                    EncryptionError::InvalidKey(format!(
+                        "key management algorithm `{}` can't use a key derived from the client \
+                         secret",
+                        variant_name(key_management_alg)
+                    )),
+                ))
+            })
+    }
+
+    fn check_request_object_support<K>(
+        &self,
+        signing: &RequestObjectSigning<JS, K>,
+        encryption: Option<&RequestObjectEncryption<JE, JK, K>>,
+    ) -> Result<(), ConfigurationError> {
+        let provider_metadata = match self.provider_metadata {
+            Some(ref provider_metadata) => provider_metadata,
+            None => return Ok(()),
+        };
+
+        match provider_metadata.request_parameter_supported() {
+            Some(true) => {}
+            Some(false) => return Err(ConfigurationError::UnsupportedRequestParameter),
+            None => warn!(
+                "provider metadata does not advertise `request_parameter_supported`; the \
+                 provider may not support request objects"
+            ),
+        }
+
+        let signing_alg_name = match *signing {
+            RequestObjectSigning::Unsigned => "none",
+            RequestObjectSigning::ClientSecret(ref signing_alg)
+            | RequestObjectSigning::PrivateKey(_, ref signing_alg) => variant_name(signing_alg),
+        };
+        match provider_metadata.request_object_signing_alg_values_supported() {
+            Some(supported_algs) => {
+                if !supported_algs
+                    .iter()
+                    .any(|alg| variant_name(alg) == signing_alg_name)
+                {
+                    return Err(ConfigurationError::UnsupportedRequestObjectSigningAlg(
+                        signing_alg_name.to_string(),
+                    ));
+                }
+            }
+            None => warn!(
+                "provider metadata does not advertise \
+                 `request_object_signing_alg_values_supported`; the provider may not support \
+                 request object signing algorithm `{}`",
+                signing_alg_name
+            ),
+        }
+
+        if let Some(encryption) = encryption {
+            let key_management_alg = encryption.key_management_alg();
+            match provider_metadata.request_object_encryption_alg_values_supported() {
+                Some(supported_algs) => {
+                    if !supported_algs.contains(key_management_alg) {
+                        return Err(ConfigurationError::UnsupportedRequestObjectEncryptionAlg(
+                            variant_name(key_management_alg).to_string(),
+                        ));
+                    }
+                }
+                None => warn!(
+                    "provider metadata does not advertise \
+                     `request_object_encryption_alg_values_supported`; the provider may not \
+                     support request object key management algorithm `{}`",
+                    variant_name(key_management_alg)
+                ),
+            }
+
+            let content_encryption_alg = encryption.content_encryption_alg();
+            match provider_metadata.request_object_encryption_enc_values_supported() {
+                Some(supported_encs) => {
+                    if !supported_encs.contains(content_encryption_alg) {
+                        return Err(ConfigurationError::UnsupportedRequestObjectEncryptionAlg(
+                            variant_name(content_encryption_alg).to_string(),
+                        ));
+                    }
+                }
+                None => warn!(
+                    "provider metadata does not advertise \
+                     `request_object_encryption_enc_values_supported`; the provider may not \
+                     support request object content encryption algorithm `{}`",
+                    variant_name(content_encryption_alg)
+                ),
+            }
+        }
+        Ok(())
+    }
+
+    fn check_pkce_method(
+        &self,
+        pkce_method: &PkceCodeChallengeMethod,
+    ) -> Result<(), ConfigurationError> {
+        let provider_metadata = match self.provider_metadata {
+            Some(ref provider_metadata) => provider_metadata,
+            None => return Ok(()),
+        };
+        match provider_metadata.code_challenge_methods_supported() {
+            Some(supported_methods) => {
+                if supported_methods.contains(pkce_method) {
+                    Ok(())
+                } else {
+                    Err(ConfigurationError::UnsupportedPkceMethod(
+                        pkce_method.to_string(),
+                    ))
+                }
+            }
+            None => {
+                warn!(
+                    "provider metadata does not advertise `code_challenge_methods_supported`; \
+                     the provider may not support PKCE"
+                );
+                Ok(())
+            }
+        }
+    }
+
+    fn authorize_url_impl<NF, SF>(
+        &self,
+        authentication_flow: &AuthenticationFlow<RT>,
+        state_fn: SF,
+        nonce_fn: NF,
+        id_token_hint: Option<&IdToken<AC, GC, JE, JS, JT>>,
+        login_hint: Option<&LoginHint>,
+        pkce_challenge: Option<&PkceCodeChallenge>,
+    ) -> (Url, CsrfToken, Nonce)
     where
         NF: Fn() -> Nonce,
         SF: Fn() -> CsrfToken,
@@ -448,6 +1038,11 @@ where
         // Create string versions of any options that need to be converted. This must be done
         // before creating extra_params so that the lifetimes extend beyond extra_params's lifetime.
         let acr_values_opt = join_optional_vec(self.auth_context_values());
+        let claims_opt = self.claims_request().map(|claims_request| {
+            // Claims requests consist of string keys and JSON values, which always serialize
+            // successfully.
+            serde_json::to_string(claims_request).expect("failed to serialize claims request")
+        });
         let claims_locales_opt = join_optional_vec(self.claims_locales());
         let max_age_opt = self.max_age().map(|max_age| max_age.as_secs().to_string());
         let prompts_opt = join_optional_vec(self.prompts());
@@ -464,20 +1059,33 @@ where
                 extra_params.push(("acr_values", acr_values));
             }
 
+            if let Some(ref claims) = claims_opt {
+                extra_params.push(("claims", claims));
+            }
+
             if let Some(ref claims_locales) = claims_locales_opt {
                 extra_params.push(("claims_locales", claims_locales));
             }
 
+            if let Some(pkce_challenge) = pkce_challenge {
+                extra_params.push(("code_challenge", pkce_challenge.as_str()));
+                extra_params.push(("code_challenge_method", pkce_challenge.method()));
+            }
+
             if let Some(display) = self.display() {
                 extra_params.push(("display", display.to_str()));
             }
 
-            // FIXME: uncomment
-/*
             if let Some(id_token_hint) = id_token_hint {
-                extra_params.push(("id_token_hint", id_token_hint));
+                if id_token_hint.is_encrypted() {
+                    warn!(
+                        "omitting encrypted `id_token_hint`, which must be decrypted before \
+                         being sent to the provider"
+                    );
+                } else {
+                    extra_params.push(("id_token_hint", id_token_hint.raw_token()));
+                }
             }
-*/
 
             if let Some(login_hint) = login_hint {
                 extra_params.push(("login_hint", login_hint.secret()));
@@ -491,47 +1099,270 @@ where
                 extra_params.push(("prompt", prompts));
             }
 
+            if let Some(ref response_mode) = self.response_mode {
+                extra_params.push(("response_mode", variant_name(response_mode)));
+            }
+
             if let Some(ref ui_locales) = ui_locales_opt {
                 extra_params.push(("ui_locales", ui_locales));
             }
 
-            let response_type = match *authentication_flow {
-                AuthenticationFlow::AuthorizationCode => core::CoreResponseType::Code.to_oauth2(),
-                AuthenticationFlow::Implicit(include_token) => {
-                    if include_token {
-                        OAuth2ResponseType::new(
-                            vec![
-                                core::CoreResponseType::IdToken,
-                                core::CoreResponseType::Token,
-                            ].iter()
-                            .map(variant_name)
-                            .collect::<Vec<_>>()
-                            .join(" "),
-                        )
-                    } else {
-                        core::CoreResponseType::IdToken.to_oauth2()
-                    }
-                }
-                AuthenticationFlow::Hybrid(ref response_types) => OAuth2ResponseType::new(
-                    response_types
-                        .iter()
-                        .map(variant_name)
-                        .collect::<Vec<_>>()
-                        .join(" "),
-                ),
-            };
-
             self.oauth2_client
-                .authorize_url_extension(&response_type, &extra_params)
+                .authorize_url_extension(&oauth2_response_type(authentication_flow), &extra_params)
         };
         (url, state, nonce)
     }
 
-    pub fn exchange_code(
+    pub fn exchange_code<HC>(
         &self,
         code: AuthorizationCode,
-    ) -> Result<TokenResponse<IdTokenFields<AC, GC, JE, JS, JT>, TT>, RequestTokenError<TE>> {
-        self.oauth2_client.exchange_code(code)
+        http_client: &HC,
+    ) -> Result<
+        TokenResponse<IdTokenFields<AC, GC, JE, JS, JT>, TT>,
+        RequestTokenError<TE, HC::Error>,
+    >
+    where
+        HC: HttpClient,
+        TE: Send + Sync + 'static,
+    {
+        let token_response = http_client
+            .request(self.code_token_request(&code, None)?)
+            .map_err(RequestTokenError::Request)?;
+        token::token_response(token_response)
+    }
+
+    ///
+    /// Exchanges an authorization code returned in response to
+    /// [`authorize_url_with_pkce`](#method.authorize_url_with_pkce) for an access token and ID
+    /// token, proving possession of the PKCE code verifier.
+    ///
+    pub fn exchange_code_with_pkce<HC>(
+        &self,
+        code: AuthorizationCode,
+        pkce_verifier: &PkceCodeVerifier,
+        http_client: &HC,
+    ) -> Result<
+        TokenResponse<IdTokenFields<AC, GC, JE, JS, JT>, TT>,
+        RequestTokenError<TE, HC::Error>,
+    >
+    where
+        HC: HttpClient,
+        TE: Send + Sync + 'static,
+    {
+        let token_response = http_client
+            .request(self.code_token_request(&code, Some(pkce_verifier))?)
+            .map_err(RequestTokenError::Request)?;
+        token::token_response(token_response)
+    }
+
+    ///
+    /// Asynchronously exchanges an authorization code for an access token and ID token.
+    ///
+    /// This is the asynchronous counterpart of `exchange_code()`.
+    ///
+    pub fn exchange_code_async<'a, HC>(
+        &self,
+        code: AuthorizationCode,
+        http_client: &HC,
+    ) -> Box<
+        Future<
+                Item = TokenResponse<IdTokenFields<AC, GC, JE, JS, JT>, TT>,
+                Error = RequestTokenError<TE, HC::Error>,
+            > + 'a,
+    >
+    where
+        HC: AsyncHttpClient,
+        HC::Future: 'a,
+        TE: Send + Sync + 'static,
+        Self: 'a,
+    {
+        let token_request = match self.code_token_request(&code, None) {
+            Ok(token_request) => token_request,
+            Err(err) => return Box::new(future::err(err)),
+        };
+        Box::new(
+            http_client
+                .request(token_request)
+                .map_err(RequestTokenError::Request)
+                .and_then(token::token_response),
+        )
+    }
+
+    ///
+    /// Asynchronously exchanges an authorization code for an access token and ID token, proving
+    /// possession of the PKCE code verifier.
+    ///
+    /// This is the asynchronous counterpart of `exchange_code_with_pkce()`.
+    ///
+    pub fn exchange_code_with_pkce_async<'a, HC>(
+        &self,
+        code: AuthorizationCode,
+        pkce_verifier: &PkceCodeVerifier,
+        http_client: &HC,
+    ) -> Box<
+        Future<
+                Item = TokenResponse<IdTokenFields<AC, GC, JE, JS, JT>, TT>,
+                Error = RequestTokenError<TE, HC::Error>,
+            > + 'a,
+    >
+    where
+        HC: AsyncHttpClient,
+        HC::Future: 'a,
+        TE: Send + Sync + 'static,
+        Self: 'a,
+    {
+        let token_request = match self.code_token_request(&code, Some(pkce_verifier)) {
+            Ok(token_request) => token_request,
+            Err(err) => return Box::new(future::err(err)),
+        };
+        Box::new(
+            http_client
+                .request(token_request)
+                .map_err(RequestTokenError::Request)
+                .and_then(token::token_response),
+        )
+    }
+
+    ///
+    /// Exchanges a refresh token for a new access token and, optionally, a new ID token (see
+    /// [Section 12 of OpenID Connect Core 1.0](
+    ///     https://openid.net/specs/openid-connect-core-1_0.html#RefreshTokens)).
+    ///
+    /// Any ID token returned should be verified against the original ID token using
+    /// [`RefreshedIdTokenFields::verified_id_token_claims`].
+    ///
+    pub fn exchange_refresh_token<HC>(
+        &self,
+        refresh_token: &RefreshToken,
+        http_client: &HC,
+    ) -> Result<
+        TokenResponse<RefreshedIdTokenFields<AC, GC, JE, JS, JT>, TT>,
+        RequestTokenError<TE, HC::Error>,
+    >
+    where
+        HC: HttpClient,
+        TE: Send + Sync + 'static,
+    {
+        let token_response = http_client
+            .request(self.refresh_token_request(refresh_token)?)
+            .map_err(RequestTokenError::Request)?;
+        token::token_response(token_response)
+    }
+
+    ///
+    /// Asynchronously exchanges a refresh token for a new access token and, optionally, a new
+    /// ID token.
+    ///
+    /// This is the asynchronous counterpart of `exchange_refresh_token()`.
+    ///
+    pub fn exchange_refresh_token_async<'a, HC>(
+        &self,
+        refresh_token: &RefreshToken,
+        http_client: &HC,
+    ) -> Box<
+        Future<
+                Item = TokenResponse<RefreshedIdTokenFields<AC, GC, JE, JS, JT>, TT>,
+                Error = RequestTokenError<TE, HC::Error>,
+            > + 'a,
+    >
+    where
+        HC: AsyncHttpClient,
+        HC::Future: 'a,
+        TE: Send + Sync + 'static,
+        Self: 'a,
+    {
+        let token_request = match self.refresh_token_request(refresh_token) {
+            Ok(token_request) => token_request,
+            Err(err) => return Box::new(future::err(err)),
+        };
+        Box::new(
+            http_client
+                .request(token_request)
+                .map_err(RequestTokenError::Request)
+                .and_then(token::token_response),
+        )
+    }
+
+    fn code_token_request<RE>(
+        &self,
+        code: &AuthorizationCode,
+        pkce_verifier: Option<&PkceCodeVerifier>,
+    ) -> Result<HttpRequest, RequestTokenError<TE, RE>>
+    where
+        RE: Fail,
+        TE: Send + Sync + 'static,
+    {
+        let mut params = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code.secret().as_str()),
+        ];
+        if let Some(pkce_verifier) = pkce_verifier {
+            params.push(("code_verifier", pkce_verifier.secret().as_str()));
+        }
+        token::token_request(self.token_url.as_ref(), &self.token_request_auth(), params)
+    }
+
+    fn refresh_token_request<RE>(
+        &self,
+        refresh_token: &RefreshToken,
+    ) -> Result<HttpRequest, RequestTokenError<TE, RE>>
+    where
+        RE: Fail,
+        TE: Send + Sync + 'static,
+    {
+        token::token_request(
+            self.token_url.as_ref(),
+            // The redirect URI is only sent with authorization codes.
+            &TokenRequestAuth {
+                redirect_uri: None,
+                ..self.token_request_auth()
+            },
+            vec![
+                ("grant_type", "refresh_token"),
+                ("refresh_token", refresh_token.secret().as_str()),
+            ],
+        )
+    }
+
+    fn token_request_auth(&self) -> TokenRequestAuth {
+        TokenRequestAuth {
+            auth_type: &self.auth_type,
+            client_id: &self.client_id,
+            client_secret: self.client_secret.as_ref(),
+            redirect_uri: self.redirect_uri.as_ref(),
+        }
+    }
+}
+
+fn oauth2_response_type<RT>(authentication_flow: &AuthenticationFlow<RT>) -> OAuth2ResponseType
+where
+    RT: ResponseType,
+{
+    match *authentication_flow {
+        AuthenticationFlow::AuthorizationCode => core::CoreResponseType::Code.to_oauth2(),
+        AuthenticationFlow::Implicit(include_token) => {
+            if include_token {
+                OAuth2ResponseType::new(
+                    vec![
+                        core::CoreResponseType::IdToken,
+                        core::CoreResponseType::Token,
+                    ]
+                    .iter()
+                    .map(variant_name)
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                )
+            } else {
+                core::CoreResponseType::IdToken.to_oauth2()
+            }
+        }
+        AuthenticationFlow::Hybrid(ref response_types) => OAuth2ResponseType::new(
+            response_types
+                .iter()
+                .map(variant_name)
+                .collect::<Vec<_>>()
+                .join(" "),
+        ),
     }
 }
 
@@ -553,14 +1384,71 @@ where
 
 #[cfg(test)]
 mod tests {
+    use std::cell::RefCell;
+    use std::io;
     use std::time::Duration;
 
+    use base64;
+    use futures::future::{self, FutureResult};
+    use futures::Future;
     use oauth2::prelude::*;
-    use oauth2::{AuthUrl, ClientId, ClientSecret, CsrfToken, RedirectUrl, Scope, TokenUrl};
+    use oauth2::{
+        AuthType, AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken, RedirectUrl,
+        RefreshToken, Scope, TokenUrl,
+    };
+    use serde_json::{self, Value};
     use url::Url;
 
-    use super::core::{CoreAuthDisplay, CoreAuthPrompt, CoreClient, CoreResponseType};
-    use super::{AuthenticationContextClass, AuthenticationFlow, LanguageTag, Nonce};
+    use super::core::{
+        CoreAuthDisplay, CoreAuthPrompt, CoreClient, CoreIdToken, CoreJsonWebKey,
+        CoreJweContentEncryptionAlgorithm, CoreJweKeyManagementAlgorithm, CoreJwsSigningAlgorithm,
+        CoreProviderMetadata, CoreRequestObjectEncryption, CoreRequestObjectSigning,
+        CoreResponseMode, CoreResponseType,
+    };
+    use super::id_token::tests::{
+        new_id_token_verifier, new_signed_id_token, TEST_ENCRYPTED_ID_TOKEN,
+    };
+    use super::jwt::tests::TEST_RSA_PRIV_KEY;
+    use super::{
+        AsyncHttpClient, AuthenticationContextClass, AuthenticationFlow, ClaimsRequest,
+        ClaimsVerificationError, ConfigurationError, EncryptionError, HttpClient, HttpRequest,
+        HttpRequestMethod, HttpResponse, IndividualClaimRequest, JsonWebEncryption, JsonWebKey,
+        JsonWebTokenError, LanguageTag, LoginHint, Nonce, PkceCodeChallenge, PkceCodeVerifier,
+        PrivateDecryptionKey, PrivateSigningKey, RequestObjectError, RequestTokenError,
+    };
+
+    struct MockHttpClient {
+        response: HttpResponse,
+        requests: RefCell<Vec<HttpRequest>>,
+    }
+    impl MockHttpClient {
+        fn new(status_code: u32, body: &str) -> Self {
+            MockHttpClient {
+                response: HttpResponse {
+                    status_code,
+                    content_type: Some("application/json".to_string()),
+                    body: body.as_bytes().to_vec(),
+                },
+                requests: RefCell::new(Vec::new()),
+            }
+        }
+    }
+    impl HttpClient for MockHttpClient {
+        type Error = io::Error;
+
+        fn request(&self, request: HttpRequest) -> Result<HttpResponse, io::Error> {
+            self.requests.borrow_mut().push(request);
+            Ok(self.response.clone())
+        }
+    }
+    impl AsyncHttpClient for MockHttpClient {
+        type Error = io::Error;
+        type Future = FutureResult<HttpResponse, io::Error>;
+
+        fn request(&self, request: HttpRequest) -> Self::Future {
+            future::result(HttpClient::request(self, request))
+        }
+    }
 
     fn new_client() -> CoreClient {
         CoreClient::new(
@@ -571,6 +1459,18 @@ mod tests {
         )
     }
 
+    fn new_provider_metadata(extra_fields: &str) -> CoreProviderMetadata {
+        serde_json::from_str(&format!(
+            "{{\"issuer\": \"https://example\", \
+             \"authorization_endpoint\": \"https://example/authorize\", \
+             \"token_endpoint\": \"https://example/token\", \
+             \"response_types_supported\": [\"code\"], \
+             \"subject_types_supported\": [\"public\"], \
+             \"id_token_signing_alg_values_supported\": [\"RS256\"]{}}}",
+            extra_fields
+        )).expect("failed to deserialize provider metadata")
+    }
+
     #[test]
     fn test_authorize_url_minimal() {
         let client = new_client();
@@ -619,4 +1519,832 @@ mod tests {
             authorize_url.to_string()
         );
     }
+
+    #[test]
+    fn test_authorize_url_with_hint() {
+        let client = new_client();
+        let id_token = new_signed_id_token("{}");
+        assert_eq!(
+            serde_json::to_value(&id_token).unwrap().as_str(),
+            Some(id_token.raw_token())
+        );
+
+        let (authorize_url, _, _) = client.authorize_url_with_hint(
+            &AuthenticationFlow::AuthorizationCode::<CoreResponseType>,
+            || CsrfToken::new("CSRF123".to_string()),
+            || Nonce::new("NONCE456".to_string()),
+            Some(&id_token),
+            Some(&LoginHint::new("foo@bar.com".to_string())),
+        );
+        assert_eq!(
+            format!(
+                "https://example/authorize?response_type=code&client_id=aaa&scope=openid&\
+                 state=CSRF123&nonce=NONCE456&id_token_hint={}&login_hint=foo%40bar.com",
+                id_token.raw_token()
+            ),
+            authorize_url.to_string()
+        );
+
+        // Encrypted ID tokens are omitted, since they must be decrypted first.
+        let encrypted_id_token = serde_json::from_value::<CoreIdToken>(Value::String(
+            TEST_ENCRYPTED_ID_TOKEN.to_string(),
+        )).unwrap();
+        let (authorize_url, _, _) = client.authorize_url_with_hint(
+            &AuthenticationFlow::AuthorizationCode::<CoreResponseType>,
+            || CsrfToken::new("CSRF123".to_string()),
+            || Nonce::new("NONCE456".to_string()),
+            Some(&encrypted_id_token),
+            None,
+        );
+        assert_eq!(
+            "https://example/authorize?response_type=code&client_id=aaa&scope=openid&\
+             state=CSRF123&nonce=NONCE456",
+            authorize_url.to_string()
+        );
+    }
+
+    #[test]
+    fn test_authorize_url_pkce() {
+        let pkce_verifier =
+            PkceCodeVerifier::new("dBjftJeZ4CVP-mJ92K27uhbUJU1p1r_wW1gFWFOEjXk".to_string());
+        let pkce_challenge = PkceCodeChallenge::from_code_verifier_sha256(&pkce_verifier);
+        let login_hint = LoginHint::new("foo@bar.com".to_string());
+        let authorize_url_with_pkce = |client: &CoreClient, pkce_challenge, login_hint| {
+            client
+                .authorize_url_with_pkce(
+                    &AuthenticationFlow::AuthorizationCode::<CoreResponseType>,
+                    || CsrfToken::new("CSRF123".to_string()),
+                    || Nonce::new("NONCE456".to_string()),
+                    pkce_challenge,
+                    None,
+                    login_hint,
+                ).map(|(authorize_url, _, _)| authorize_url.to_string())
+        };
+
+        assert_eq!(
+            "https://example/authorize?response_type=code&client_id=aaa&scope=openid&\
+             state=CSRF123&nonce=NONCE456&\
+             code_challenge=ngF5GsXcbwljx6u133FFr3Xht9xooA_DuaX_3QwODtc&\
+             code_challenge_method=S256",
+            authorize_url_with_pkce(&new_client(), &pkce_challenge, None).unwrap()
+        );
+        // The code challenge is included alongside any hints.
+        assert_eq!(
+            "https://example/authorize?response_type=code&client_id=aaa&scope=openid&\
+             state=CSRF123&nonce=NONCE456&\
+             code_challenge=ngF5GsXcbwljx6u133FFr3Xht9xooA_DuaX_3QwODtc&\
+             code_challenge_method=S256&login_hint=foo%40bar.com",
+            authorize_url_with_pkce(&new_client(), &pkce_challenge, Some(&login_hint)).unwrap()
+        );
+
+        // Request objects include the code challenge too.
+        let (authorize_url, _, _) = new_client()
+            .authorize_url_with_request_object(
+                &AuthenticationFlow::AuthorizationCode::<CoreResponseType>,
+                || CsrfToken::new("CSRF123".to_string()),
+                || Nonce::new("NONCE456".to_string()),
+                &CoreRequestObjectSigning::Unsigned,
+                None,
+                Some(&pkce_challenge),
+            ).unwrap();
+        let request = authorize_url
+            .query_pairs()
+            .find(|&(ref key, _)| key == "request")
+            .map(|(_, value)| value.into_owned())
+            .expect("missing request parameter");
+        let (_, claims, _, _) = split_request_object(&request);
+        assert_eq!(
+            Some("ngF5GsXcbwljx6u133FFr3Xht9xooA_DuaX_3QwODtc"),
+            claims["code_challenge"].as_str()
+        );
+        assert_eq!(Some("S256"), claims["code_challenge_method"].as_str());
+
+        // Providers that don't advertise any methods may still support PKCE.
+        let client = CoreClient::from_provider_metadata(
+            ClientId::new("aaa".to_string()),
+            None,
+            new_provider_metadata(""),
+        );
+        authorize_url_with_pkce(&client, &pkce_challenge, None).unwrap();
+
+        let client = CoreClient::from_provider_metadata(
+            ClientId::new("aaa".to_string()),
+            None,
+            new_provider_metadata(", \"code_challenge_methods_supported\": [\"S256\"]"),
+        );
+        authorize_url_with_pkce(&client, &pkce_challenge, None).unwrap();
+        let plain_challenge = PkceCodeChallenge::from_code_verifier_plain(&pkce_verifier);
+        match authorize_url_with_pkce(&client, &plain_challenge, None) {
+            Err(ConfigurationError::UnsupportedPkceMethod(ref method)) if method == "plain" => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match client.authorize_url_with_request_object(
+            &AuthenticationFlow::AuthorizationCode::<CoreResponseType>,
+            || CsrfToken::new("CSRF123".to_string()),
+            || Nonce::new("NONCE456".to_string()),
+            &CoreRequestObjectSigning::Unsigned,
+            None,
+            Some(&plain_challenge),
+        ) {
+            Err(RequestObjectError::Configuration(ConfigurationError::UnsupportedPkceMethod(
+                ref method,
+            ))) if method == "plain" => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_authorize_url_response_mode() {
+        let client = new_client()
+            .set_response_mode(Some(CoreResponseMode::FormPost))
+            .unwrap();
+        let (authorize_url, _, _) = client.authorize_url(
+            &AuthenticationFlow::AuthorizationCode::<CoreResponseType>,
+            || CsrfToken::new("CSRF123".to_string()),
+            || Nonce::new("NONCE456".to_string()),
+        );
+        assert_eq!(
+            "https://example/authorize?response_type=code&client_id=aaa&scope=openid&\
+             state=CSRF123&nonce=NONCE456&response_mode=form_post",
+            authorize_url.to_string()
+        );
+
+        // Providers that don't advertise any response modes may still support them.
+        CoreClient::from_provider_metadata(
+            ClientId::new("aaa".to_string()),
+            None,
+            new_provider_metadata(""),
+        ).set_response_mode(Some(CoreResponseMode::FormPost))
+        .unwrap();
+
+        let client = CoreClient::from_provider_metadata(
+            ClientId::new("aaa".to_string()),
+            None,
+            new_provider_metadata(", \"response_modes_supported\": [\"query\", \"fragment\"]"),
+        );
+        let client = client
+            .set_response_mode(Some(CoreResponseMode::Fragment))
+            .unwrap()
+            .set_response_mode(None)
+            .unwrap();
+        assert_eq!(None, client.response_mode());
+        match client.set_response_mode(Some(CoreResponseMode::FormPost)) {
+            Err(ConfigurationError::UnsupportedResponseMode(ref mode)) if mode == "form_post" => {}
+            Err(other) => panic!("unexpected error: {:?}", other),
+            Ok(_) => panic!("expected an error"),
+        }
+    }
+
+    #[test]
+    fn test_exchange_code_with_pkce() {
+        let client = new_client();
+        let pkce_verifier =
+            PkceCodeVerifier::new("dBjftJeZ4CVP-mJ92K27uhbUJU1p1r_wW1gFWFOEjXk".to_string());
+        let http_client = MockHttpClient::new(400, "{\"error\": \"invalid_grant\"}");
+
+        client
+            .exchange_code_with_pkce(
+                AuthorizationCode::new("CODE123".to_string()),
+                &pkce_verifier,
+                &http_client,
+            ).unwrap_err();
+        client
+            .exchange_code_with_pkce_async(
+                AuthorizationCode::new("CODE123".to_string()),
+                &pkce_verifier,
+                &http_client,
+            ).wait()
+            .unwrap_err();
+
+        let requests = http_client.requests.borrow();
+        assert_eq!(2, requests.len());
+        for request in requests.iter() {
+            assert_eq!(
+                "grant_type=authorization_code&code=CODE123&\
+                 code_verifier=dBjftJeZ4CVP-mJ92K27uhbUJU1p1r_wW1gFWFOEjXk",
+                String::from_utf8(request.body.clone()).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_exchange_code_with_pkce_public_client() {
+        // Public clients have no client secret, so they identify themselves using the client_id
+        // parameter instead of HTTP Basic auth.
+        let client = CoreClient::new(
+            ClientId::new("aaa".to_string()),
+            None,
+            AuthUrl::new(Url::parse("https://example/authorize").unwrap()),
+            Some(TokenUrl::new(Url::parse("https://example/token").unwrap())),
+        );
+        let pkce_verifier =
+            PkceCodeVerifier::new("dBjftJeZ4CVP-mJ92K27uhbUJU1p1r_wW1gFWFOEjXk".to_string());
+        let http_client = MockHttpClient::new(400, "{\"error\": \"invalid_grant\"}");
+
+        client
+            .exchange_code_with_pkce(
+                AuthorizationCode::new("CODE123".to_string()),
+                &pkce_verifier,
+                &http_client,
+            ).unwrap_err();
+
+        let requests = http_client.requests.borrow();
+        assert_eq!(1, requests.len());
+        assert_eq!(
+            vec![
+                ("Accept".to_string(), "application/json".to_string()),
+                (
+                    "Content-Type".to_string(),
+                    "application/x-www-form-urlencoded".to_string(),
+                ),
+            ],
+            requests[0].headers
+        );
+        assert_eq!(
+            "grant_type=authorization_code&code=CODE123&\
+             code_verifier=dBjftJeZ4CVP-mJ92K27uhbUJU1p1r_wW1gFWFOEjXk&client_id=aaa",
+            String::from_utf8(requests[0].body.clone()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_exchange_code_request() {
+        let client = new_client().set_redirect_uri(RedirectUrl::new(
+            Url::parse("http://localhost:8888/").unwrap(),
+        ));
+        let http_client = MockHttpClient::new(400, "{\"error\": \"invalid_grant\"}");
+
+        match client.exchange_code(AuthorizationCode::new("CODE123".to_string()), &http_client) {
+            Err(RequestTokenError::ServerResponse(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match client
+            .exchange_code_async(AuthorizationCode::new("CODE123".to_string()), &http_client)
+            .wait()
+        {
+            Err(RequestTokenError::ServerResponse(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let requests = http_client.requests.borrow();
+        assert_eq!(2, requests.len());
+        assert_eq!(requests[0], requests[1]);
+        assert_eq!("https://example/token", requests[0].url.as_str());
+        assert_eq!(HttpRequestMethod::Post, requests[0].method);
+        assert_eq!(
+            vec![
+                ("Accept".to_string(), "application/json".to_string()),
+                (
+                    "Content-Type".to_string(),
+                    "application/x-www-form-urlencoded".to_string(),
+                ),
+                ("Authorization".to_string(), "Basic YWFhOmJiYg==".to_string()),
+            ],
+            requests[0].headers
+        );
+        assert_eq!(
+            "grant_type=authorization_code&code=CODE123&\
+             redirect_uri=http%3A%2F%2Flocalhost%3A8888%2F",
+            String::from_utf8(requests[0].body.clone()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_exchange_code_request_body_auth() {
+        let client = new_client().set_auth_type(AuthType::RequestBody);
+        let http_client = MockHttpClient::new(400, "{\"error\": \"invalid_grant\"}");
+
+        client
+            .exchange_code(AuthorizationCode::new("CODE123".to_string()), &http_client)
+            .unwrap_err();
+
+        let requests = http_client.requests.borrow();
+        assert_eq!(1, requests.len());
+        assert_eq!(
+            vec![
+                ("Accept".to_string(), "application/json".to_string()),
+                (
+                    "Content-Type".to_string(),
+                    "application/x-www-form-urlencoded".to_string(),
+                ),
+            ],
+            requests[0].headers
+        );
+        assert_eq!(
+            "grant_type=authorization_code&code=CODE123&client_id=aaa&client_secret=bbb",
+            String::from_utf8(requests[0].body.clone()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_id_token_verifier_auth_time() {
+        let nonce = Nonce::new("the_nonce".to_string());
+        let no_auth_time = new_signed_id_token("{}");
+        let old_auth_time = new_signed_id_token("{\"auth_time\": 1311279000}");
+        let is_invalid_auth_time = |result: Result<_, ClaimsVerificationError>| match result {
+            Err(ClaimsVerificationError::InvalidAuthTime(_)) => true,
+            _ => false,
+        };
+
+        let verifier = new_client().configure_id_token_verifier(new_id_token_verifier());
+        no_auth_time
+            .claims(&verifier, &nonce)
+            .expect("verification failed");
+
+        let client = new_client().set_require_auth_time(true);
+        let verifier = client.configure_id_token_verifier(new_id_token_verifier());
+        assert!(is_invalid_auth_time(no_auth_time.claims(&verifier, &nonce)));
+        old_auth_time
+            .claims(&verifier, &nonce)
+            .expect("verification failed");
+
+        // The old authentication time exceeds the registered default_max_age.
+        let client = new_client().set_default_max_age(Some(Duration::from_secs(1800)));
+        let verifier = client.configure_id_token_verifier(new_id_token_verifier());
+        assert!(is_invalid_auth_time(no_auth_time.claims(&verifier, &nonce)));
+        assert!(is_invalid_auth_time(
+            old_auth_time.claims(&verifier, &nonce)
+        ));
+
+        // The max_age parameter overrides the default_max_age.
+        let client = new_client()
+            .set_default_max_age(Some(Duration::from_secs(1800)))
+            .set_max_age(Some(Duration::from_secs(3600)));
+        let verifier = client.configure_id_token_verifier(new_id_token_verifier());
+        assert!(is_invalid_auth_time(no_auth_time.claims(&verifier, &nonce)));
+        old_auth_time
+            .claims(&verifier, &nonce)
+            .expect("verification failed");
+    }
+
+    #[test]
+    fn test_id_token_verifier_auth_context() {
+        let nonce = Nonce::new("the_nonce".to_string());
+        let no_acr = new_signed_id_token("{}");
+        let bronze = new_signed_id_token("{\"acr\": \"bronze\"}");
+
+        let verifier = new_client().configure_id_token_verifier(new_id_token_verifier());
+        bronze
+            .claims(&verifier, &nonce)
+            .expect("verification failed");
+
+        let client = new_client().set_auth_context_values(Some(vec![
+            AuthenticationContextClass::new("silver".to_string()),
+            AuthenticationContextClass::new("gold".to_string()),
+        ]));
+        let verifier = client.configure_id_token_verifier(new_id_token_verifier());
+        no_acr
+            .claims(&verifier, &nonce)
+            .expect("verification failed");
+        match bronze.claims(&verifier, &nonce) {
+            Err(ClaimsVerificationError::InvalidAuthContext(ref err)) => {
+                assert_eq!(
+                    err.requested(),
+                    client.auth_context_values().unwrap().as_slice()
+                )
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_claims_request() {
+        let claims_request = ClaimsRequest::new()
+            .add_userinfo_claim(
+                "email",
+                None,
+                Some(IndividualClaimRequest::new().set_essential(Some(true))),
+            ).add_id_token_claim(
+                "acr",
+                None,
+                Some(
+                    IndividualClaimRequest::new()
+                        .set_essential(Some(true))
+                        .set_values(Some(vec![Value::String("gold".to_string())])),
+                ),
+            );
+        let client = new_client()
+            .set_auth_context_values(Some(vec![AuthenticationContextClass::new(
+                "silver".to_string(),
+            )]))
+            .set_claims_request(Some(claims_request.clone()))
+            .unwrap();
+
+        let (authorize_url, _, _) = client.authorize_url(
+            &AuthenticationFlow::AuthorizationCode::<CoreResponseType>,
+            || CsrfToken::new("CSRF123".to_string()),
+            || Nonce::new("NONCE456".to_string()),
+        );
+        assert_eq!(
+            Some(serde_json::to_string(&claims_request).unwrap()),
+            authorize_url
+                .query_pairs()
+                .find(|&(ref key, _)| key == "claims")
+                .map(|(_, value)| value.into_owned())
+        );
+
+        // The essential acr values take precedence over the voluntary acr_values.
+        let nonce = Nonce::new("the_nonce".to_string());
+        let verifier = client.configure_id_token_verifier(new_id_token_verifier());
+        new_signed_id_token("{\"acr\": \"gold\"}")
+            .claims(&verifier, &nonce)
+            .expect("verification failed");
+        for id_token in &[
+            new_signed_id_token("{}"),
+            new_signed_id_token("{\"acr\": \"silver\"}"),
+        ] {
+            match id_token.claims(&verifier, &nonce) {
+                Err(ClaimsVerificationError::InvalidAuthContext(_)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+
+        let client = CoreClient::from_provider_metadata(
+            ClientId::new("aaa".to_string()),
+            None,
+            new_provider_metadata(", \"claims_parameter_supported\": false"),
+        );
+        match client.set_claims_request(Some(claims_request)) {
+            Err(ConfigurationError::UnsupportedClaimsParameter) => {}
+            Err(other) => panic!("unexpected error: {:?}", other),
+            Ok(_) => panic!("expected an error"),
+        }
+    }
+
+    // Splits a request object into its decoded JOSE header, decoded claims, signing input, and
+    // signature.
+    fn split_request_object(request: &str) -> (Value, Value, String, Vec<u8>) {
+        let parts = request.split('.').collect::<Vec<_>>();
+        assert_eq!(3, parts.len());
+        let decode_json = |part: &str| -> Value {
+            serde_json::from_slice(&base64::decode_config(part, base64::URL_SAFE_NO_PAD).unwrap())
+                .unwrap()
+        };
+        (
+            decode_json(parts[0]),
+            decode_json(parts[1]),
+            format!("{}.{}", parts[0], parts[1]),
+            base64::decode_config(parts[2], base64::URL_SAFE_NO_PAD).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_authorize_url_with_request_object() {
+        let client = CoreClient::from_provider_metadata(
+            ClientId::new("aaa".to_string()),
+            Some(ClientSecret::new("bbb".to_string())),
+            new_provider_metadata(
+                ", \"request_parameter_supported\": true, \
+                 \"request_object_signing_alg_values_supported\": \
+                 [\"none\", \"HS256\", \"RS256\"], \
+                 \"request_object_encryption_alg_values_supported\": [\"RSA-OAEP\", \"A128KW\"], \
+                 \"request_object_encryption_enc_values_supported\": [\"A128GCM\"]",
+            ),
+        ).set_redirect_uri(RedirectUrl::new(
+            Url::parse("http://localhost:8888/").unwrap(),
+        )).set_max_age(Some(Duration::from_secs(3600)))
+        .set_claims_request(Some(ClaimsRequest::new().add_userinfo_claim("email", None, None)))
+        .unwrap();
+
+        let (authorize_url, csrf_state, nonce) = client
+            .authorize_url_with_request_object(
+                &AuthenticationFlow::AuthorizationCode::<CoreResponseType>,
+                || CsrfToken::new("CSRF123".to_string()),
+                || Nonce::new("NONCE456".to_string()),
+                &CoreRequestObjectSigning::Unsigned,
+                None,
+                None,
+            ).unwrap();
+        assert_eq!("CSRF123", csrf_state.secret());
+        assert_eq!("NONCE456", nonce.secret());
+        let request = authorize_url
+            .query_pairs()
+            .find(|&(ref key, _)| key == "request")
+            .map(|(_, value)| value.into_owned())
+            .expect("missing request parameter");
+        assert_eq!(
+            format!(
+                "https://example/authorize?response_type=code&client_id=aaa&\
+                 redirect_uri=http%3A%2F%2Flocalhost%3A8888%2F&scope=openid&request={}",
+                request
+            ),
+            authorize_url.to_string()
+        );
+
+        let (header, claims, _, signature) = split_request_object(&request);
+        assert_eq!(
+            serde_json::from_str::<Value>("{\"alg\": \"none\"}").unwrap(),
+            header
+        );
+        assert!(signature.is_empty());
+        assert_eq!(
+            serde_json::from_str::<Value>(
+                "{\"response_type\": \"code\", \"client_id\": \"aaa\", \
+                 \"redirect_uri\": \"http://localhost:8888/\", \"scope\": \"openid\", \
+                 \"state\": \"CSRF123\", \"nonce\": \"NONCE456\", \
+                 \"claims\": {\"userinfo\": {\"email\": null}}, \"max_age\": 3600, \
+                 \"iss\": \"aaa\", \"aud\": \"https://example\"}"
+            ).unwrap(),
+            claims
+        );
+
+        // HMAC keyed by the client secret.
+        let (authorize_url, _, _) = client
+            .authorize_url_with_request_object(
+                &AuthenticationFlow::AuthorizationCode::<CoreResponseType>,
+                || CsrfToken::new("CSRF123".to_string()),
+                || Nonce::new("NONCE456".to_string()),
+                &CoreRequestObjectSigning::ClientSecret(CoreJwsSigningAlgorithm::HmacSha256),
+                None,
+                None,
+            ).unwrap();
+        let request = authorize_url
+            .query_pairs()
+            .find(|&(ref key, _)| key == "request")
+            .map(|(_, value)| value.into_owned())
+            .unwrap();
+        let (header, _, signing_input, signature) = split_request_object(&request);
+        assert_eq!(
+            serde_json::from_str::<Value>("{\"alg\": \"HS256\"}").unwrap(),
+            header
+        );
+        CoreJsonWebKey::new_symmetric(b"bbb".to_vec())
+            .verify_signature(
+                &CoreJwsSigningAlgorithm::HmacSha256,
+                &signing_input,
+                &signature,
+            ).expect("invalid request object signature");
+
+        let provider_decryption_key: CoreJsonWebKey =
+            serde_json::from_str(&TEST_RSA_PRIV_KEY.replace("\"sig\"", "\"enc\""))
+                .expect("deserialization failed");
+        let provider_encryption_key = provider_decryption_key.as_verification_key();
+
+        // Signed with a private key and encrypted to the provider's public key.
+        #[cfg(feature = "rsa-oaep")]
+        {
+            let signing_key: CoreJsonWebKey =
+                serde_json::from_str(TEST_RSA_PRIV_KEY).expect("deserialization failed");
+            let (authorize_url, _, _) = client
+                .authorize_url_with_request_object(
+                    &AuthenticationFlow::AuthorizationCode::<CoreResponseType>,
+                    || CsrfToken::new("CSRF123".to_string()),
+                    || Nonce::new("NONCE456".to_string()),
+                    &CoreRequestObjectSigning::PrivateKey(
+                        &signing_key,
+                        CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+                    ),
+                    Some(&CoreRequestObjectEncryption::new(
+                        &provider_encryption_key,
+                        CoreJweKeyManagementAlgorithm::RsaOaep,
+                        CoreJweContentEncryptionAlgorithm::Aes128Gcm,
+                    )),
+                    None,
+                ).unwrap();
+            let request = authorize_url
+                .query_pairs()
+                .find(|&(ref key, _)| key == "request")
+                .map(|(_, value)| value.into_owned())
+                .unwrap();
+            let jwe: JsonWebEncryption = serde_json::from_value(Value::String(request)).unwrap();
+            let nested_jwt = String::from_utf8(
+                provider_decryption_key
+                    .decrypt(
+                        &CoreJweKeyManagementAlgorithm::RsaOaep,
+                        &CoreJweContentEncryptionAlgorithm::Aes128Gcm,
+                        &jwe,
+                    ).expect("decryption failed"),
+            ).unwrap();
+            let (header, claims, signing_input, signature) = split_request_object(&nested_jwt);
+            assert_eq!(
+                serde_json::from_str::<Value>("{\"alg\": \"RS256\", \"kid\": \"rsa-signing-key\"}")
+                    .unwrap(),
+                header
+            );
+            assert_eq!(Some("aaa"), claims["iss"].as_str());
+            signing_key
+                .as_verification_key()
+                .verify_signature(
+                    &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+                    &signing_input,
+                    &signature,
+                ).expect("invalid request object signature");
+        }
+
+        // Encrypted using a key derived from the client secret (the left-most 128 bits of the
+        // SHA-256 hash of `bbb`).
+        let (authorize_url, _, _) = client
+            .authorize_url_with_request_object(
+                &AuthenticationFlow::AuthorizationCode::<CoreResponseType>,
+                || CsrfToken::new("CSRF123".to_string()),
+                || Nonce::new("NONCE456".to_string()),
+                &CoreRequestObjectSigning::Unsigned,
+                Some(&CoreRequestObjectEncryption::new_client_secret(
+                    CoreJweKeyManagementAlgorithm::AesKeyWrap128,
+                    CoreJweContentEncryptionAlgorithm::Aes128Gcm,
+                )),
+                None,
+            ).unwrap();
+        let request = authorize_url
+            .query_pairs()
+            .find(|&(ref key, _)| key == "request")
+            .map(|(_, value)| value.into_owned())
+            .unwrap();
+        let jwe: JsonWebEncryption = serde_json::from_value(Value::String(request)).unwrap();
+        let nested_jwt = String::from_utf8(
+            CoreJsonWebKey::new_symmetric(vec![
+                62, 116, 75, 157, 195, 147, 137, 186, 240, 197, 160, 102, 5, 137, 184, 64,
+            ])
+            .decrypt(
+                &CoreJweKeyManagementAlgorithm::AesKeyWrap128,
+                &CoreJweContentEncryptionAlgorithm::Aes128Gcm,
+                &jwe,
+            ).expect("decryption failed"),
+        ).unwrap();
+        let (header, claims, _, _) = split_request_object(&nested_jwt);
+        assert_eq!(
+            serde_json::from_str::<Value>("{\"alg\": \"none\"}").unwrap(),
+            header
+        );
+        assert_eq!(Some("aaa"), claims["iss"].as_str());
+
+        // Only symmetric key management algorithms can use the client secret.
+        match client.authorize_url_with_request_object(
+            &AuthenticationFlow::AuthorizationCode::<CoreResponseType>,
+            || CsrfToken::new("CSRF123".to_string()),
+            || Nonce::new("NONCE456".to_string()),
+            &CoreRequestObjectSigning::Unsigned,
+            Some(&CoreRequestObjectEncryption::new_client_secret(
+                CoreJweKeyManagementAlgorithm::RsaOaep,
+                CoreJweContentEncryptionAlgorithm::Aes128Gcm,
+            )),
+            None,
+        ) {
+            Err(RequestObjectError::JsonWebToken(JsonWebTokenError::Encryption(
+                EncryptionError::InvalidKey(_),
+            ))) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Algorithms not supported by the provider are rejected.
+        match client.authorize_url_with_request_object(
+            &AuthenticationFlow::AuthorizationCode::<CoreResponseType>,
+            || CsrfToken::new("CSRF123".to_string()),
+            || Nonce::new("NONCE456".to_string()),
+            &CoreRequestObjectSigning::ClientSecret(CoreJwsSigningAlgorithm::HmacSha512),
+            None,
+            None,
+        ) {
+            Err(RequestObjectError::Configuration(
+                ConfigurationError::UnsupportedRequestObjectSigningAlg(ref alg),
+            )) => assert_eq!("HS512", alg),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match client.authorize_url_with_request_object(
+            &AuthenticationFlow::AuthorizationCode::<CoreResponseType>,
+            || CsrfToken::new("CSRF123".to_string()),
+            || Nonce::new("NONCE456".to_string()),
+            &CoreRequestObjectSigning::Unsigned,
+            Some(&CoreRequestObjectEncryption::new(
+                &provider_encryption_key,
+                CoreJweKeyManagementAlgorithm::RsaOaep,
+                CoreJweContentEncryptionAlgorithm::Aes256Gcm,
+            )),
+            None,
+        ) {
+            Err(RequestObjectError::Configuration(
+                ConfigurationError::UnsupportedRequestObjectEncryptionAlg(ref alg),
+            )) => assert_eq!("A256GCM", alg),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Signing with the client secret requires one.
+        match CoreClient::new(
+            ClientId::new("aaa".to_string()),
+            None,
+            AuthUrl::new(Url::parse("https://example/authorize").unwrap()),
+            None,
+        ).authorize_url_with_request_object(
+            &AuthenticationFlow::AuthorizationCode::<CoreResponseType>,
+            || CsrfToken::new("CSRF123".to_string()),
+            || Nonce::new("NONCE456".to_string()),
+            &CoreRequestObjectSigning::ClientSecret(CoreJwsSigningAlgorithm::HmacSha256),
+            None,
+            None,
+        ) {
+            Err(RequestObjectError::MissingClientSecret) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        // So does encrypting with it.
+        match CoreClient::new(
+            ClientId::new("aaa".to_string()),
+            None,
+            AuthUrl::new(Url::parse("https://example/authorize").unwrap()),
+            None,
+        ).authorize_url_with_request_object(
+            &AuthenticationFlow::AuthorizationCode::<CoreResponseType>,
+            || CsrfToken::new("CSRF123".to_string()),
+            || Nonce::new("NONCE456".to_string()),
+            &CoreRequestObjectSigning::Unsigned,
+            Some(&CoreRequestObjectEncryption::new_client_secret(
+                CoreJweKeyManagementAlgorithm::Direct,
+                CoreJweContentEncryptionAlgorithm::Aes128Gcm,
+            )),
+            None,
+        ) {
+            Err(RequestObjectError::MissingClientSecret) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let client = CoreClient::from_provider_metadata(
+            ClientId::new("aaa".to_string()),
+            None,
+            new_provider_metadata(", \"request_parameter_supported\": false"),
+        );
+        match client.authorize_url_with_request_object(
+            &AuthenticationFlow::AuthorizationCode::<CoreResponseType>,
+            || CsrfToken::new("CSRF123".to_string()),
+            || Nonce::new("NONCE456".to_string()),
+            &CoreRequestObjectSigning::Unsigned,
+            None,
+            None,
+        ) {
+            Err(RequestObjectError::Configuration(
+                ConfigurationError::UnsupportedRequestParameter,
+            )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_exchange_refresh_token() {
+        let client = new_client().set_redirect_uri(RedirectUrl::new(
+            Url::parse("http://localhost:8888/").unwrap(),
+        ));
+        let refresh_token = RefreshToken::new("REFRESH123".to_string());
+        let nonce = Nonce::new("the_nonce".to_string());
+        let verifier = new_id_token_verifier();
+        let original_claims = new_signed_id_token("{}")
+            .claims(&verifier, &nonce)
+            .expect("verification failed")
+            .clone();
+
+        // Providers may omit the ID token from refresh responses.
+        let http_client = MockHttpClient::new(
+            200,
+            "{\"access_token\": \"ACCESS456\", \"token_type\": \"bearer\"}",
+        );
+        let token_response = client
+            .exchange_refresh_token(&refresh_token, &http_client)
+            .expect("token request failed");
+        assert_eq!(token_response.access_token().secret(), "ACCESS456");
+        assert!(token_response.extra_fields().id_token().is_none());
+        assert_eq!(
+            token_response
+                .extra_fields()
+                .verified_id_token_claims(&verifier, &original_claims),
+            Ok(None)
+        );
+        {
+            let requests = http_client.requests.borrow();
+            assert_eq!(1, requests.len());
+            assert_eq!(
+                "grant_type=refresh_token&refresh_token=REFRESH123",
+                String::from_utf8(requests[0].body.clone()).unwrap()
+            );
+        }
+
+        let refreshed_id_token = serde_json::to_string(&new_signed_id_token("{\"nonce\": null}"))
+            .expect("failed to serialize");
+        let http_client = MockHttpClient::new(
+            200,
+            &format!(
+                "{{\"access_token\": \"ACCESS456\", \"token_type\": \"bearer\", \
+                 \"id_token\": {}}}",
+                refreshed_id_token
+            ),
+        );
+        let token_response = client
+            .exchange_refresh_token_async(&refresh_token, &http_client)
+            .wait()
+            .expect("token request failed");
+        let refreshed_claims = token_response
+            .extra_fields()
+            .verified_id_token_claims(&verifier, &original_claims)
+            .expect("verification failed")
+            .expect("missing ID token");
+        assert_eq!(refreshed_claims.nonce(), None);
+
+        let other_claims = new_signed_id_token("{\"sub\": \"other_sub\"}")
+            .claims(&verifier, &nonce)
+            .expect("verification failed")
+            .clone();
+        match token_response
+            .extra_fields()
+            .verified_id_token_claims(&verifier, &other_claims)
+        {
+            Err(ClaimsVerificationError::InvalidSubject(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
 }
@@ -4,7 +4,8 @@ use std::marker::{PhantomData, Send, Sync};
 use std::time::Duration;
 
 use chrono::{DateTime, TimeZone, Utc};
-use curl;
+use failure::Fail;
+use futures::{future, Future};
 use oauth2::{AccessToken, ClientId, ClientSecret, ErrorResponse, ErrorResponseType, RedirectUrl};
 use serde;
 use serde::de::{Deserialize, DeserializeOwned, Deserializer, MapAccess, Visitor};
@@ -14,8 +15,9 @@ use serde_json;
 
 use super::discovery::JsonWebKeySetUrl;
 use super::http::{
-    auth_bearer, HttpRequest, HttpRequestMethod, ACCEPT_JSON, CONTENT_TYPE_JSON,
-    HTTP_STATUS_BAD_REQUEST, HTTP_STATUS_CREATED, MIME_TYPE_JSON,
+    accept_json, auth_bearer, content_type_json, AsyncHttpClient, HttpClient, HttpRequest,
+    HttpRequestMethod, HttpResponse, HTTP_STATUS_BAD_REQUEST, HTTP_STATUS_CREATED,
+    MIME_TYPE_JSON,
 };
 use super::macros::TraitStructExtract;
 use super::types::helpers::split_language_tag_key;
@@ -314,70 +316,55 @@ where
     fn initial_access_token(&self) -> Option<&AccessToken>;
     fn set_initial_access_token(&mut self, access_token: Option<AccessToken>);
 
-    fn register(
+    fn register<HC>(
         &self,
         registration_endpoint: &RegistrationUrl,
-    ) -> Result<CR, ClientRegistrationError<ET>> {
-        let request_json = serde_json::to_string(self.client_metadata())
-            .map_err(ClientRegistrationError::Json)?
-            .into_bytes();
-
-        let auth_header_opt = if let Some(initial_access_token) = self.initial_access_token() {
-            Some(auth_bearer(initial_access_token))
-        } else {
-            None
-        };
-
-        let mut headers = vec![ACCEPT_JSON, CONTENT_TYPE_JSON];
-        if let Some((header, ref value)) = auth_header_opt {
-            headers.push((header, value.as_ref()));
-        }
-
-        let register_response = HttpRequest {
-            url: registration_endpoint.url(),
-            method: HttpRequestMethod::Post,
-            headers: &headers,
-            post_body: &request_json,
-        }.request()
-        .map_err(ClientRegistrationError::Request)?;
-
-        // FIXME: check for WWW-Authenticate response header if bearer auth was used (see
-        //   https://tools.ietf.org/html/rfc6750#section-3)
-        // FIXME: improve error handling (i.e., is there a body response?)
-        // FIXME: other necessary response validation? check spec
-
-        // Spec says that a successful response SHOULD use 201 Created, and a registration error
-        // condition returns (no "SHOULD") 400 Bad Request. For now, only accept these two status
-        // codes. We may need to relax the success status to improve interoperability.
-        if register_response.status_code != HTTP_STATUS_CREATED
-            && register_response.status_code != HTTP_STATUS_BAD_REQUEST
-        {
-            return Err(ClientRegistrationError::Response(
-                register_response.status_code,
-                "unexpected HTTP status code".to_string(),
-            ));
-        }
-
-        register_response
-            .check_content_type(MIME_TYPE_JSON)
-            .map_err(|err_msg| {
-                ClientRegistrationError::Response(register_response.status_code, err_msg)
-            })?;
-
-        let response_body = String::from_utf8(register_response.body).map_err(|parse_error| {
-            ClientRegistrationError::Other(format!(
-                "couldn't parse response as UTF-8: {}",
-                parse_error
-            ))
-        })?;
+        http_client: &HC,
+    ) -> Result<CR, ClientRegistrationError<ET, HC::Error>>
+    where
+        HC: HttpClient,
+    {
+        let register_request = registration_request(
+            self.client_metadata(),
+            self.initial_access_token(),
+            registration_endpoint,
+        )?;
+        let register_response = http_client
+            .request(register_request)
+            .map_err(ClientRegistrationError::Request)?;
 
-        if register_response.status_code == HTTP_STATUS_BAD_REQUEST {
-            let response_error: ErrorResponse<ET> =
-                serde_json::from_str(&response_body).map_err(ClientRegistrationError::Json)?;
-            return Err(ClientRegistrationError::ServerResponse(response_error));
-        }
+        registration_response(register_response)
+    }
 
-        serde_json::from_str(&response_body).map_err(ClientRegistrationError::Json)
+    ///
+    /// Asynchronously registers the client with the OpenID Provider.
+    ///
+    /// This is the asynchronous counterpart of `register()`.
+    ///
+    fn register_async<'a, HC>(
+        &self,
+        registration_endpoint: &RegistrationUrl,
+        http_client: &HC,
+    ) -> Box<Future<Item = CR, Error = ClientRegistrationError<ET, HC::Error>> + 'a>
+    where
+        HC: AsyncHttpClient,
+        HC::Future: 'a,
+        CR: 'a,
+    {
+        let register_request = match registration_request(
+            self.client_metadata(),
+            self.initial_access_token(),
+            registration_endpoint,
+        ) {
+            Ok(register_request) => register_request,
+            Err(err) => return Box::new(future::err(err)),
+        };
+        Box::new(
+            http_client
+                .request(register_request)
+                .map_err(ClientRegistrationError::Request)
+                .and_then(registration_response),
+        )
     }
 
     field_setter_decls![
@@ -727,14 +714,92 @@ where
     ];
 }
 
+fn registration_request<CM, ET, RE>(
+    client_metadata: &CM,
+    initial_access_token: Option<&AccessToken>,
+    registration_endpoint: &RegistrationUrl,
+) -> Result<HttpRequest, ClientRegistrationError<ET, RE>>
+where
+    CM: Serialize,
+    ET: RegisterErrorResponseType,
+    RE: Fail,
+{
+    let request_json = serde_json::to_string(client_metadata)
+        .map_err(ClientRegistrationError::Json)?
+        .into_bytes();
+
+    let mut headers = vec![accept_json(), content_type_json()];
+    if let Some(initial_access_token) = initial_access_token {
+        headers.push(auth_bearer(initial_access_token));
+    }
+
+    Ok(HttpRequest {
+        url: registration_endpoint.url().clone(),
+        method: HttpRequestMethod::Post,
+        headers,
+        body: request_json,
+    })
+}
+
+fn registration_response<CR, ET, RE>(
+    register_response: HttpResponse,
+) -> Result<CR, ClientRegistrationError<ET, RE>>
+where
+    CR: DeserializeOwned,
+    ET: RegisterErrorResponseType,
+    RE: Fail,
+{
+    // FIXME: check for WWW-Authenticate response header if bearer auth was used (see
+    //   https://tools.ietf.org/html/rfc6750#section-3)
+    // FIXME: improve error handling (i.e., is there a body response?)
+    // FIXME: other necessary response validation? check spec
+
+    // Spec says that a successful response SHOULD use 201 Created, and a registration error
+    // condition returns (no "SHOULD") 400 Bad Request. For now, only accept these two status
+    // codes. We may need to relax the success status to improve interoperability.
+    if register_response.status_code != HTTP_STATUS_CREATED
+        && register_response.status_code != HTTP_STATUS_BAD_REQUEST
+    {
+        return Err(ClientRegistrationError::Response(
+            register_response.status_code,
+            "unexpected HTTP status code".to_string(),
+        ));
+    }
+
+    register_response
+        .check_content_type(MIME_TYPE_JSON)
+        .map_err(|err_msg| {
+            ClientRegistrationError::Response(register_response.status_code, err_msg)
+        })?;
+
+    let response_body = String::from_utf8(register_response.body).map_err(|parse_error| {
+        ClientRegistrationError::Other(format!(
+            "couldn't parse response as UTF-8: {}",
+            parse_error
+        ))
+    })?;
+
+    if register_response.status_code == HTTP_STATUS_BAD_REQUEST {
+        let response_error: ErrorResponse<ET> =
+            serde_json::from_str(&response_body).map_err(ClientRegistrationError::Json)?;
+        return Err(ClientRegistrationError::ServerResponse(response_error));
+    }
+
+    serde_json::from_str(&response_body).map_err(ClientRegistrationError::Json)
+}
+
 // FIXME: implement client configuration endpoint request (Section 4)
 
 pub trait RegisterErrorResponseType: 'static + Clone + ErrorResponseType + Send + Sync {}
 
 #[derive(Debug, Fail)]
-pub enum ClientRegistrationError<T: RegisterErrorResponseType> {
+pub enum ClientRegistrationError<T, RE>
+where
+    T: RegisterErrorResponseType,
+    RE: Fail,
+{
     #[fail(display = "Request error: {}", _0)]
-    Request(curl::Error),
+    Request(RE),
     #[fail(display = "Response error (status={}): {}", _0, _1)]
     Response(u32, String),
     #[fail(display = "JSON error: {}", _0)]
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+use chrono::{DateTime, Utc};
+
+///
+/// Cache of consumed nonces and JWT IDs (`jti` claims), used by
+/// [`IdTokenVerifier`](struct.IdTokenVerifier.html) to detect replayed ID tokens (see
+/// [Section 15.5.2 of OpenID Connect Core 1.0](
+///     https://openid.net/specs/openid-connect-core-1_0.html#NonceNotes)).
+///
+/// Keys are prefixed with the type of value consumed (`nonce:` or `jti:`), and JWT ID keys also
+/// include the issuer (`jti:{iss}:{jti}`) since JWT IDs are only unique per issuer. Each key only
+/// needs to be retained until its expiration time, after which the ID token it was consumed from
+/// is rejected by the verifier regardless.
+///
+/// Implementations shared by multiple processes (e.g., load-balanced frontends) should be backed
+/// by a shared store that supports atomically inserting a key only if it's absent.
+///
+pub trait ReplayCache {
+    ///
+    /// Marks `key` as consumed until `expiration`. Returns `Ok(true)` if the key was not
+    /// already consumed, `Ok(false)` if it was consumed previously and has not yet expired, or an
+    /// error message if the cache could not be queried or updated.
+    ///
+    fn consume(
+        &self,
+        key: &str,
+        expiration: DateTime<Utc>,
+        cur_time: DateTime<Utc>,
+    ) -> Result<bool, String>;
+}
+
+// Minimum number of keys retained before expired keys are purged.
+const MIN_PURGE_THRESHOLD: usize = 64;
+
+///
+/// In-memory [`ReplayCache`] that retains each key until its expiration time.
+///
+/// This cache is only suitable for clients running in a single process.
+///
+#[derive(Debug, Default)]
+pub struct InMemoryReplayCache {
+    entries: Mutex<Entries>,
+}
+impl InMemoryReplayCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Returns the number of unexpired keys as of the last call to
+    /// [`consume`](#method.consume).
+    ///
+    pub fn len(&self) -> usize {
+        let entries = self.lock_entries();
+        match entries.last_time {
+            Some(last_time) => entries
+                .keys
+                .values()
+                .filter(|expiration| **expiration >= last_time)
+                .count(),
+            None => 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock_entries(&self) -> MutexGuard<Entries> {
+        // A panic while holding the lock can't leave the map in an inconsistent state, so it's
+        // safe to keep using it.
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+impl ReplayCache for InMemoryReplayCache {
+    fn consume(
+        &self,
+        key: &str,
+        expiration: DateTime<Utc>,
+        cur_time: DateTime<Utc>,
+    ) -> Result<bool, String> {
+        let mut guard = self.lock_entries();
+        let entries = &mut *guard;
+        entries.last_time = Some(cur_time);
+
+        // Purging expired keys takes time linear in the size of the cache, so it's only done once
+        // the cache has doubled in size since the last purge. This keeps the amortized cost of
+        // each call constant.
+        if entries.keys.len() >= entries.purge_threshold {
+            entries
+                .keys
+                .retain(|_, entry_expiration| *entry_expiration >= cur_time);
+            entries.purge_threshold = MIN_PURGE_THRESHOLD.max(2 * entries.keys.len());
+        }
+
+        match entries.keys.get(key) {
+            Some(entry_expiration) if *entry_expiration >= cur_time => return Ok(false),
+            _ => {}
+        }
+        entries.keys.insert(key.to_string(), expiration);
+        Ok(true)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    keys: HashMap<String, DateTime<Utc>>,
+    // Number of keys at which expired keys are next purged.
+    purge_threshold: usize,
+    // Current time passed to the most recent call to `consume`.
+    last_time: Option<DateTime<Utc>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::{InMemoryReplayCache, ReplayCache, MIN_PURGE_THRESHOLD};
+
+    #[test]
+    fn test_in_memory_replay_cache() {
+        let cache = InMemoryReplayCache::new();
+        assert!(cache.is_empty());
+
+        let expiration = Utc.timestamp(1311281970, 0);
+        let cur_time = Utc.timestamp(1311281000, 0);
+        assert_eq!(cache.consume("nonce:abc", expiration, cur_time), Ok(true));
+        assert_eq!(cache.consume("nonce:abc", expiration, cur_time), Ok(false));
+        assert_eq!(cache.consume("jti:abc", expiration, cur_time), Ok(true));
+        assert_eq!(cache.len(), 2);
+
+        // Still consumed at the expiration time.
+        assert_eq!(
+            cache.consume("nonce:abc", expiration, expiration),
+            Ok(false)
+        );
+
+        // Expired keys are no longer counted and may be consumed again.
+        let later_time = Utc.timestamp(1311281971, 0);
+        assert_eq!(
+            cache.consume("nonce:def", Utc.timestamp(1311282970, 0), later_time),
+            Ok(true)
+        );
+        assert_eq!(cache.len(), 1);
+        assert_eq!(
+            cache.consume("nonce:abc", Utc.timestamp(1311282970, 0), later_time),
+            Ok(true)
+        );
+    }
+
+    #[test]
+    fn test_in_memory_replay_cache_purge() {
+        let cache = InMemoryReplayCache::new();
+        let expiration = Utc.timestamp(1311281970, 0);
+        let cur_time = Utc.timestamp(1311281000, 0);
+        for i in 0..MIN_PURGE_THRESHOLD {
+            assert_eq!(
+                cache.consume(&format!("nonce:{}", i), expiration, cur_time),
+                Ok(true)
+            );
+        }
+        assert_eq!(cache.lock_entries().keys.len(), MIN_PURGE_THRESHOLD);
+
+        // Expired keys are purged once the cache reaches the purge threshold.
+        let later_time = Utc.timestamp(1311281971, 0);
+        assert_eq!(
+            cache.consume("nonce:abc", Utc.timestamp(1311282970, 0), later_time),
+            Ok(true)
+        );
+        assert_eq!(cache.lock_entries().keys.len(), 1);
+        assert_eq!(cache.len(), 1);
+    }
+}
@@ -0,0 +1,111 @@
+use super::{ConfigurationError, JsonWebTokenError};
+
+///
+/// How a request object is signed (see
+/// [Section 6.1 of OpenID Connect Core 1.0](
+///     https://openid.net/specs/openid-connect-core-1_0.html#RequestObject)).
+///
+#[derive(Clone, Debug)]
+pub enum RequestObjectSigning<'a, JS, K: 'a> {
+    ///
+    /// The request object is not signed, using the `none` algorithm.
+    ///
+    /// # Security Warning
+    ///
+    /// The provider can't verify that an unsigned request object originated from this client.
+    /// Unsigned request objects should generally be encrypted to the provider.
+    ///
+    Unsigned,
+    ///
+    /// The request object is signed with a MAC (e.g., `HS256`) keyed by the client secret.
+    ///
+    ClientSecret(JS),
+    ///
+    /// The request object is signed with the given private key using the given algorithm. The
+    /// provider verifies the signature using the client's registered public keys.
+    ///
+    PrivateKey(&'a K, JS),
+}
+
+///
+/// How a request object is encrypted to the OpenID Provider.
+///
+/// Signed request objects are encrypted as nested JSON Web Tokens, as described in
+/// [Section 6.1 of OpenID Connect Core 1.0](
+///     https://openid.net/specs/openid-connect-core-1_0.html#RequestObject).
+///
+#[derive(Clone, Debug)]
+pub struct RequestObjectEncryption<'a, JE, JK, K: 'a> {
+    key: Option<&'a K>,
+    key_management_alg: JK,
+    content_encryption_alg: JE,
+}
+impl<'a, JE, JK, K> RequestObjectEncryption<'a, JE, JK, K> {
+    ///
+    /// Encrypts request objects to the given key (typically one of the provider's public keys)
+    /// using the given key management (`alg`) and content encryption (`enc`) algorithms.
+    /// RSA-OAEP key management requires the `rsa-oaep` feature.
+    ///
+    pub fn new(key: &'a K, key_management_alg: JK, content_encryption_alg: JE) -> Self {
+        RequestObjectEncryption {
+            key: Some(key),
+            key_management_alg,
+            content_encryption_alg,
+        }
+    }
+
+    ///
+    /// Encrypts request objects using a symmetric key derived from the client secret, as
+    /// described in
+    /// [Section 10.2 of OpenID Connect Core 1.0](
+    ///     https://openid.net/specs/openid-connect-core-1_0.html#Encryption), and the given
+    /// symmetric key management (`dir` or AES key wrap) and content encryption (`enc`)
+    /// algorithms.
+    ///
+    pub fn new_client_secret(key_management_alg: JK, content_encryption_alg: JE) -> Self {
+        RequestObjectEncryption {
+            key: None,
+            key_management_alg,
+            content_encryption_alg,
+        }
+    }
+
+    ///
+    /// Returns the key to encrypt request objects to, or `None` if the key is derived from the
+    /// client secret.
+    ///
+    pub fn key(&self) -> Option<&K> {
+        self.key
+    }
+
+    pub fn key_management_alg(&self) -> &JK {
+        &self.key_management_alg
+    }
+
+    pub fn content_encryption_alg(&self) -> &JE {
+        &self.content_encryption_alg
+    }
+}
+
+///
+/// Error building a request object.
+///
+#[derive(Debug, Fail)]
+pub enum RequestObjectError {
+    ///
+    /// The provider doesn't support the request object, according to its metadata.
+    ///
+    #[fail(display = "Configuration error: {}", _0)]
+    Configuration(#[cause] ConfigurationError),
+    ///
+    /// The request object is to be signed or encrypted using the client secret, but the client
+    /// has none.
+    ///
+    #[fail(display = "Client secret is required to sign or encrypt the request object")]
+    MissingClientSecret,
+    ///
+    /// Failed to serialize, sign, or encrypt the request object.
+    ///
+    #[fail(display = "Failed to build request object: {}", _0)]
+    JsonWebToken(#[cause] JsonWebTokenError),
+}
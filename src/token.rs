@@ -0,0 +1,171 @@
+use base64;
+use failure::Fail;
+use oauth2::prelude::*;
+use oauth2::{
+    AuthType, ClientId, ClientSecret, ErrorResponse, ErrorResponseType, ExtraTokenFields,
+    RedirectUrl, TokenResponse, TokenType, TokenUrl,
+};
+use serde_json;
+use url::form_urlencoded;
+
+use super::http::{
+    accept_json, HttpRequest, HttpRequestMethod, HttpResponse, AUTHORIZATION, BASIC, CONTENT_TYPE,
+    HTTP_STATUS_OK, MIME_TYPE_FORM_URLENCODED, MIME_TYPE_JSON,
+};
+
+///
+/// Error encountered while requesting an access token from the token endpoint.
+///
+#[derive(Debug, Fail)]
+pub enum RequestTokenError<TE, RE>
+where
+    TE: ErrorResponseType + Send + Sync + 'static,
+    RE: Fail,
+{
+    ///
+    /// Error response returned by the authorization server. Contains the parsed `ErrorResponse`
+    /// returned by the server.
+    ///
+    #[fail(display = "Server returned error response `{}`", _0)]
+    ServerResponse(ErrorResponse<TE>),
+    ///
+    /// An error occurred while sending the request or receiving the response (e.g., network
+    /// connectivity failed).
+    ///
+    #[fail(display = "Request failed")]
+    Request(#[cause] RE),
+    ///
+    /// Failed to parse the server response. Parse errors may occur while parsing either
+    /// successful or error responses. Contains the raw response body.
+    ///
+    #[fail(display = "Failed to parse server response")]
+    Parse(#[cause] serde_json::Error, Vec<u8>),
+    ///
+    /// Some other type of error occurred (e.g., an unexpected server response).
+    ///
+    #[fail(display = "Other error: {}", _0)]
+    Other(String),
+}
+
+///
+/// Client settings used for authenticating requests to the token endpoint.
+///
+pub(crate) struct TokenRequestAuth<'a> {
+    pub auth_type: &'a AuthType,
+    pub client_id: &'a ClientId,
+    pub client_secret: Option<&'a ClientSecret>,
+    pub redirect_uri: Option<&'a RedirectUrl>,
+}
+
+///
+/// Builds a form-encoded request to the token endpoint containing the specified parameters.
+///
+pub(crate) fn token_request<'a, TE, RE>(
+    token_url: Option<&TokenUrl>,
+    auth: &TokenRequestAuth<'a>,
+    mut params: Vec<(&'a str, &'a str)>,
+) -> Result<HttpRequest, RequestTokenError<TE, RE>>
+where
+    TE: ErrorResponseType + Send + Sync + 'static,
+    RE: Fail,
+{
+    let token_url = token_url.ok_or_else(|| {
+        // The token endpoint may be absent from the provider metadata (e.g., for providers that
+        // only support the Implicit Flow), so we return an error instead of panicking.
+        RequestTokenError::Other("no token endpoint configured".to_string())
+    })?;
+
+    let mut headers = vec![
+        accept_json(),
+        (
+            CONTENT_TYPE.to_string(),
+            MIME_TYPE_FORM_URLENCODED.to_string(),
+        ),
+    ];
+
+    // FIXME: add support for auth extensions? e.g., client_secret_jwt and private_key_jwt
+    match (auth.auth_type, auth.client_secret) {
+        // Public clients without a client secret identify themselves using the client_id
+        // parameter instead of authenticating (see Section 4.1.3 of RFC 6749).
+        (&AuthType::RequestBody, client_secret) | (&AuthType::BasicAuth, client_secret @ None) => {
+            params.push(("client_id", auth.client_id.as_str()));
+            if let Some(client_secret) = client_secret {
+                params.push(("client_secret", client_secret.secret()));
+            }
+        }
+        (&AuthType::BasicAuth, Some(client_secret)) => {
+            // Section 2.3.1 of RFC 6749 requires separately url-encoding the id and secret
+            // before using them as HTTP Basic auth username and password. Note that this is
+            // not standard for ordinary Basic auth.
+            let encoded_id = form_urlencoded::byte_serialize(auth.client_id.as_bytes())
+                .collect::<String>();
+            let encoded_secret = form_urlencoded::byte_serialize(client_secret.secret().as_bytes())
+                .collect::<String>();
+            headers.push((
+                AUTHORIZATION.to_string(),
+                format!(
+                    "{} {}",
+                    BASIC,
+                    base64::encode(&format!("{}:{}", encoded_id, encoded_secret))
+                ),
+            ));
+        }
+    }
+
+    if let Some(redirect_uri) = auth.redirect_uri {
+        params.push(("redirect_uri", redirect_uri.as_str()));
+    }
+
+    let body = form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(params)
+        .finish()
+        .into_bytes();
+
+    Ok(HttpRequest {
+        url: (**token_url).clone(),
+        method: HttpRequestMethod::Post,
+        headers,
+        body,
+    })
+}
+
+///
+/// Parses a response returned by the token endpoint.
+///
+pub(crate) fn token_response<EF, TE, TT, RE>(
+    http_response: HttpResponse,
+) -> Result<TokenResponse<EF, TT>, RequestTokenError<TE, RE>>
+where
+    EF: ExtraTokenFields,
+    TE: ErrorResponseType + Send + Sync + 'static,
+    TT: TokenType,
+    RE: Fail,
+{
+    if http_response.status_code != HTTP_STATUS_OK {
+        let reason = http_response.body.as_slice();
+        if reason.is_empty() {
+            return Err(RequestTokenError::Other(
+                "Server returned empty error response".to_string(),
+            ));
+        } else {
+            let error = match serde_json::from_slice::<ErrorResponse<TE>>(reason) {
+                Ok(error) => RequestTokenError::ServerResponse(error),
+                Err(error) => RequestTokenError::Parse(error, reason.to_vec()),
+            };
+            return Err(error);
+        }
+    }
+
+    http_response
+        .check_content_type(MIME_TYPE_JSON)
+        .map_err(RequestTokenError::Other)?;
+
+    if http_response.body.is_empty() {
+        Err(RequestTokenError::Other(
+            "Server returned empty response body".to_string(),
+        ))
+    } else {
+        serde_json::from_slice(&http_response.body)
+            .map_err(|e| RequestTokenError::Parse(e, http_response.body.clone()))
+    }
+}
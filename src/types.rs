@@ -7,14 +7,19 @@ use base64;
 use oauth2;
 use oauth2::helpers::deserialize_space_delimited_vec;
 use oauth2::prelude::*;
+use oauth2::{AccessToken, AuthorizationCode};
 use rand::{thread_rng, Rng};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use serde_json;
+use sha2::{Digest, Sha256};
 use url;
 use url::Url;
 
-use super::SignatureVerificationError;
+use super::{
+    DecryptionError, EncryptedContent, EncryptionError, JsonWebEncryption,
+    SignatureVerificationError, SigningError,
+};
 
 pub trait ApplicationType: Clone + Debug + DeserializeOwned + PartialEq + Serialize {}
 
@@ -57,6 +62,85 @@ where
     ) -> Result<(), SignatureVerificationError>;
 }
 
+///
+/// Private or symmetric key for signing [JSON Web Tokens](https://tools.ietf.org/html/rfc7519).
+///
+pub trait PrivateSigningKey<JS, JT, JU, K>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    ///
+    /// Signs the given `message` using the given signature algorithm.
+    ///
+    fn sign(&self, signature_alg: &JS, message: &[u8]) -> Result<Vec<u8>, SigningError>;
+
+    ///
+    /// Returns the [JSON Web Key](https://tools.ietf.org/html/rfc7517) that verifies signatures
+    /// produced by this key. For asymmetric keys, this is the public key, and for symmetric keys,
+    /// it is the key itself.
+    ///
+    fn as_verification_key(&self) -> K;
+}
+
+///
+/// Private or symmetric key for decrypting
+/// [JSON Web Encryption](https://tools.ietf.org/html/rfc7516) (JWE) content.
+///
+pub trait PrivateDecryptionKey<JE, JK, JS, JT, JU>: JsonWebKey<JS, JT, JU>
+where
+    JE: JweContentEncryptionAlgorithm,
+    JK: JweKeyManagementAlgorithm,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+{
+    ///
+    /// Returns whether this key is of the type required by the given key management algorithm.
+    ///
+    fn allows_key_management_alg(&self, key_management_alg: &JK) -> bool;
+
+    ///
+    /// Decrypts the content of `jwe` using the given key management and content encryption
+    /// algorithms (taken from the JWE's JOSE header) and returns the plaintext.
+    ///
+    fn decrypt(
+        &self,
+        key_management_alg: &JK,
+        content_encryption_alg: &JE,
+        jwe: &JsonWebEncryption,
+    ) -> Result<Vec<u8>, DecryptionError>;
+}
+
+///
+/// Public or symmetric key for encrypting
+/// [JSON Web Encryption](https://tools.ietf.org/html/rfc7516) (JWE) content, such as a request
+/// object encrypted to the OpenID Provider.
+///
+pub trait PublicEncryptionKey<JE, JK, JS, JT, JU>: JsonWebKey<JS, JT, JU>
+where
+    JE: JweContentEncryptionAlgorithm,
+    JK: JweKeyManagementAlgorithm,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+{
+    ///
+    /// Encrypts `plaintext` using the given key management and content encryption algorithms.
+    /// The `additional_authenticated_data` is the ASCII encoding of the base64url-encoded JWE
+    /// Protected Header, which the content encryption algorithm must integrity protect.
+    ///
+    fn encrypt(
+        &self,
+        key_management_alg: &JK,
+        content_encryption_alg: &JE,
+        additional_authenticated_data: &[u8],
+        plaintext: &[u8],
+    ) -> Result<EncryptedContent, EncryptionError>;
+}
+
 pub trait JsonWebKeyType: Clone + Debug + DeserializeOwned + PartialEq + Serialize {}
 pub trait JsonWebKeyUse: Clone + Debug + DeserializeOwned + PartialEq + Serialize {
     fn allows_signature(&self) -> bool;
@@ -66,11 +150,24 @@ pub trait JsonWebKeyUse: Clone + Debug + DeserializeOwned + PartialEq + Serializ
 pub trait JweContentEncryptionAlgorithm:
     Clone + Debug + DeserializeOwned + Eq + Hash + PartialEq + Serialize
 {
+    ///
+    /// Returns the length in bytes of the content encryption key used by this algorithm.
+    ///
+    fn key_len(&self) -> usize;
 }
 // FIXME: add a key_type() method?
 pub trait JweKeyManagementAlgorithm:
     Clone + Debug + DeserializeOwned + Eq + Hash + PartialEq + Serialize
 {
+    ///
+    /// Returns the length in bytes of the symmetric key this algorithm uses with the given
+    /// content encryption algorithm, or `None` if the algorithm doesn't use a symmetric key of a
+    /// fixed length. For direct encryption (`dir`), this is the length of the content encryption
+    /// key.
+    ///
+    fn symmetric_key_len<JE>(&self, content_encryption_alg: &JE) -> Option<usize>
+    where
+        JE: JweContentEncryptionAlgorithm;
 }
 
 pub trait JwsSigningAlgorithm<JT>:
@@ -82,6 +179,12 @@ where
     fn key_type(&self) -> Result<JT, String>;
     fn is_symmetric(&self) -> bool;
     fn rsa_sha_256() -> Self;
+
+    ///
+    /// Hashes the given bytes using the hash function associated with this algorithm (e.g.,
+    /// SHA-256 for `RS256`), as required to compute the `at_hash` and `c_hash` ID token claims.
+    ///
+    fn hash_bytes(&self, bytes: &[u8]) -> Result<Vec<u8>, String>;
 }
 
 pub trait ResponseMode: Clone + Debug + DeserializeOwned + PartialEq + Serialize {}
@@ -113,6 +216,36 @@ new_type![#[derive(
     Deserialize, Eq, Hash, Ord, PartialOrd, Serialize,
 )]
 AccessTokenHash(String)];
+impl AccessTokenHash {
+    ///
+    /// Computes the `at_hash` of an access token for an ID token signed using `alg` (see
+    /// [Section 3.2.2.9 of OpenID Connect Core 1.0](
+    ///     https://openid.net/specs/openid-connect-core-1_0.html#ImplicitTokenValidation)).
+    ///
+    pub fn from_token<JS, JT>(access_token: &AccessToken, alg: &JS) -> Result<Self, SigningError>
+    where
+        JS: JwsSigningAlgorithm<JT>,
+        JT: JsonWebKeyType,
+    {
+        left_half_hash(alg, access_token.secret()).map(AccessTokenHash::new)
+    }
+}
+
+// Both `at_hash` and `c_hash` are the base64url encoding of the left-most half of the hash of the
+// ASCII representation of the token or code.
+fn left_half_hash<JS, JT>(alg: &JS, value: &str) -> Result<String, SigningError>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    let hash = alg
+        .hash_bytes(value.as_bytes())
+        .map_err(SigningError::UnsupportedAlg)?;
+    Ok(base64::encode_config(
+        &hash[0..hash.len() / 2],
+        base64::URL_SAFE_NO_PAD,
+    ))
+}
 
 new_type![#[derive(
     Deserialize, Eq, Hash, Ord, PartialOrd, Serialize,
@@ -143,6 +276,20 @@ new_type![#[derive(
     Deserialize, Eq, Hash, Ord, PartialOrd, Serialize,
 )]
 AuthorizationCodeHash(String)];
+impl AuthorizationCodeHash {
+    ///
+    /// Computes the `c_hash` of an authorization code for an ID token signed using `alg` (see
+    /// [Section 3.3.2.11 of OpenID Connect Core 1.0](
+    ///     https://openid.net/specs/openid-connect-core-1_0.html#HybridIDToken)).
+    ///
+    pub fn from_code<JS, JT>(code: &AuthorizationCode, alg: &JS) -> Result<Self, SigningError>
+    where
+        JS: JwsSigningAlgorithm<JT>,
+        JT: JsonWebKeyType,
+    {
+        left_half_hash(alg, code.secret()).map(AuthorizationCodeHash::new)
+    }
+}
 
 new_type![
     #[derive(Deserialize, Eq, Hash, Serialize)]
@@ -247,6 +394,11 @@ new_type![#[derive(
 )]
 JsonWebKeyId(String)];
 
+new_type![#[derive(
+    Deserialize, Eq, Hash, Ord, PartialOrd, Serialize,
+)]
+JsonWebTokenId(String)];
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct JsonWebKeySet<JS, JT, JU, K>
 where
@@ -337,6 +489,125 @@ new_url_type![OpTosUrl];
 
 new_url_type![PolicyUrl];
 
+///
+/// Code challenge used for [PKCE](https://tools.ietf.org/html/rfc7636) protection of the
+/// authorization code flow, sent via the `code_challenge` and `code_challenge_method`
+/// authorization request parameters.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct PkceCodeChallenge {
+    code_challenge: String,
+    code_challenge_method: PkceCodeChallengeMethod,
+}
+impl PkceCodeChallenge {
+    ///
+    /// Generates a new random code verifier and its corresponding `S256` code challenge.
+    ///
+    /// This is the method recommended by
+    /// [Section 4.2 of RFC 7636](https://tools.ietf.org/html/rfc7636#section-4.2).
+    ///
+    pub fn new_random_sha256() -> (Self, PkceCodeVerifier) {
+        let code_verifier = PkceCodeVerifier::new_random();
+        (
+            Self::from_code_verifier_sha256(&code_verifier),
+            code_verifier,
+        )
+    }
+
+    ///
+    /// Generates a new random code verifier and its corresponding `plain` code challenge.
+    ///
+    /// The `plain` method should only be used with providers that don't support `S256`.
+    ///
+    pub fn new_random_plain() -> (Self, PkceCodeVerifier) {
+        let code_verifier = PkceCodeVerifier::new_random();
+        (
+            Self::from_code_verifier_plain(&code_verifier),
+            code_verifier,
+        )
+    }
+
+    ///
+    /// Returns the `S256` code challenge for the given code verifier.
+    ///
+    pub fn from_code_verifier_sha256(code_verifier: &PkceCodeVerifier) -> Self {
+        let digest = Sha256::digest(code_verifier.secret().as_bytes());
+        PkceCodeChallenge {
+            code_challenge: base64::encode_config(&digest, base64::URL_SAFE_NO_PAD),
+            code_challenge_method: PkceCodeChallengeMethod::new("S256".to_string()),
+        }
+    }
+
+    ///
+    /// Returns the `plain` code challenge for the given code verifier, which is the code verifier
+    /// itself.
+    ///
+    pub fn from_code_verifier_plain(code_verifier: &PkceCodeVerifier) -> Self {
+        PkceCodeChallenge {
+            code_challenge: code_verifier.secret().to_string(),
+            code_challenge_method: PkceCodeChallengeMethod::new("plain".to_string()),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.code_challenge
+    }
+
+    pub fn method(&self) -> &PkceCodeChallengeMethod {
+        &self.code_challenge_method
+    }
+}
+
+new_type![
+    ///
+    /// Method used to derive a [`PkceCodeChallenge`](struct.PkceCodeChallenge.html) from a
+    /// [`PkceCodeVerifier`](struct.PkceCodeVerifier.html) (e.g., `S256` or `plain`).
+    ///
+    #[derive(Deserialize, Eq, Hash, Ord, PartialOrd, Serialize)]
+    PkceCodeChallengeMethod(String)
+];
+impl AsRef<str> for PkceCodeChallengeMethod {
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+new_secret_type![
+    ///
+    /// Code verifier used for [PKCE](https://tools.ietf.org/html/rfc7636) protection of the
+    /// authorization code flow, sent via the `code_verifier` token request parameter.
+    ///
+    /// The value must be between 43 and 128 characters long, and each character must be ASCII
+    /// alphanumeric or one of `-`, `.`, `_` or `~`.
+    ///
+    #[derive(Deserialize, Serialize)]
+    PkceCodeVerifier(String)
+    impl {
+        ///
+        /// Generate a new random, base64-encoded 256-bit code verifier.
+        ///
+        pub fn new_random() -> Self {
+            PkceCodeVerifier::new_random_len(32)
+        }
+        ///
+        /// Generate a new random, base64-encoded code verifier of the specified length.
+        ///
+        /// # Arguments
+        ///
+        /// * `num_bytes` - Number of random bytes to generate, prior to base64-encoding. Must be
+        ///   between 32 and 96 (inclusive) to produce a code verifier of a valid length.
+        ///
+        pub fn new_random_len(num_bytes: u32) -> Self {
+            assert!(num_bytes >= 32 && num_bytes <= 96);
+            let random_bytes: Vec<u8> = (0..num_bytes).map(|_| thread_rng().gen::<u8>()).collect();
+            PkceCodeVerifier::new(base64::encode_config(
+                &random_bytes,
+                base64::URL_SAFE_NO_PAD,
+            ))
+        }
+    }
+];
+
 new_secret_type![#[derive(
     Deserialize, Eq, Hash, Ord, PartialOrd, Serialize,
 )]
@@ -522,9 +793,63 @@ mod serde_base64url_byte_array {
 
 #[cfg(test)]
 mod tests {
+    use oauth2::prelude::*;
+    use oauth2::{AccessToken, AuthorizationCode};
     use serde_json;
 
-    use super::super::IssuerUrl;
+    use super::super::core::CoreJwsSigningAlgorithm;
+    use super::super::{IssuerUrl, SigningError};
+    use super::{AccessTokenHash, AuthorizationCodeHash, PkceCodeChallenge, PkceCodeVerifier};
+
+    #[test]
+    fn test_access_token_hash() {
+        // Source: the examples in Appendix A of OpenID Connect Core 1.0. The ES384 and EdDSA
+        // hashes of the same access token aren't part of the specification and were computed
+        // separately (the left-most halves of its SHA-384 and SHA-512 digests, respectively).
+        let access_token =
+            AccessToken::new("jHkWEdUXMU1BwAsC4vtUsZwnNvTIxEl0z9K3vx5KF0Y".to_string());
+        assert_eq!(
+            AccessTokenHash::from_token(
+                &access_token,
+                &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256
+            ),
+            Ok(AccessTokenHash::new("77QmUPtjPfzWtF2AnpK9RQ".to_string()))
+        );
+        assert_eq!(
+            AccessTokenHash::from_token(&access_token, &CoreJwsSigningAlgorithm::EcdsaP384Sha384),
+            Ok(AccessTokenHash::new(
+                "jtAeDp945y1dDqU3nkIVGNZP1HjH_MFs".to_string()
+            ))
+        );
+        assert_eq!(
+            AccessTokenHash::from_token(&access_token, &CoreJwsSigningAlgorithm::EdDsa),
+            Ok(AccessTokenHash::new(
+                "q7nS86GgvvFaZkzALLWqJYaJIKw2wCDAVfCAsm5CrBM".to_string()
+            ))
+        );
+        match AccessTokenHash::from_token(&access_token, &CoreJwsSigningAlgorithm::None) {
+            Err(SigningError::UnsupportedAlg(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_authorization_code_hash() {
+        // Source: the examples in Appendix A of OpenID Connect Core 1.0.
+        let code = AuthorizationCode::new(
+            "Qcb0Orv1zh30vL1MPRsbm-diHiMwcLyZvn1arpZv-Jxf_11jnpEX3Tgfvk".to_string(),
+        );
+        assert_eq!(
+            AuthorizationCodeHash::from_code(&code, &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256),
+            Ok(AuthorizationCodeHash::new(
+                "LDktKdoQak3Pk0cnXxCltA".to_string()
+            ))
+        );
+        match AuthorizationCodeHash::from_code(&code, &CoreJwsSigningAlgorithm::None) {
+            Err(SigningError::UnsupportedAlg(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
 
     #[test]
     fn test_issuer_url_append() {
@@ -562,6 +887,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_pkce_code_challenge() {
+        let code_verifier =
+            PkceCodeVerifier::new("dBjftJeZ4CVP-mJ92K27uhbUJU1p1r_wW1gFWFOEjXk".to_string());
+        let code_challenge = PkceCodeChallenge::from_code_verifier_sha256(&code_verifier);
+        assert_eq!(
+            "ngF5GsXcbwljx6u133FFr3Xht9xooA_DuaX_3QwODtc",
+            code_challenge.as_str()
+        );
+        assert_eq!("S256", code_challenge.method().as_str());
+
+        let code_challenge = PkceCodeChallenge::from_code_verifier_plain(&code_verifier);
+        assert_eq!(code_verifier.secret(), code_challenge.as_str());
+        assert_eq!("plain", code_challenge.method().as_str());
+
+        let (code_challenge, code_verifier) = PkceCodeChallenge::new_random_sha256();
+        assert_eq!(43, code_verifier.secret().len());
+        assert_eq!(
+            PkceCodeChallenge::from_code_verifier_sha256(&code_verifier),
+            code_challenge
+        );
+        assert_eq!(128, PkceCodeVerifier::new_random_len(96).secret().len());
+    }
+
     #[test]
     fn test_url_serialize() {
         let issuer_url =
@@ -3,26 +3,29 @@ use std::ops::Deref;
 use std::str;
 
 use chrono::{DateTime, Utc};
-use curl;
+use failure::Fail;
+use futures::Future;
 use oauth2::AccessToken;
 use serde_json;
 use url::Url;
 
 use super::claims::StandardClaimsImpl;
 use super::http::{
-    auth_bearer, HttpRequest, HttpRequestMethod, ACCEPT_JSON, HTTP_STATUS_OK, MIME_TYPE_JSON,
-    MIME_TYPE_JWT,
+    accept_json, auth_bearer, AsyncHttpClient, HttpClient, HttpRequest, HttpRequestMethod,
+    HttpResponse, HTTP_STATUS_OK, MIME_TYPE_JSON, MIME_TYPE_JWT,
 };
-use super::jwt::JsonWebTokenJsonPayloadDeserializer;
+use super::jwt::{JsonWebEncryption, JsonWebTokenJsonPayloadDeserializer};
 use super::types::helpers::seconds_to_utc;
+use super::types::Seconds;
 use super::verification::UserInfoVerifier;
 use super::{
     AdditionalClaims, AddressClaim, Audience, AudiencesClaim, ClaimsVerificationError,
     EndUserBirthday, EndUserEmail, EndUserFamilyName, EndUserGivenName, EndUserMiddleName,
     EndUserName, EndUserNickname, EndUserPhoneNumber, EndUserPictureUrl, EndUserProfileUrl,
     EndUserTimezone, EndUserUsername, EndUserWebsiteUrl, GenderClaim, IssuerClaim, IssuerUrl,
-    JsonWebKey, JsonWebKeyType, JsonWebKeyUse, JsonWebToken, JweContentEncryptionAlgorithm,
-    JwsSigningAlgorithm, LanguageTag, StandardClaims, SubjectIdentifier,
+    JsonWebKeyType, JsonWebKeyUse, JsonWebToken, JweContentEncryptionAlgorithm,
+    JweKeyManagementAlgorithm, JwsSigningAlgorithm, LanguageTag, PrivateDecryptionKey,
+    StandardClaims, SubjectIdentifier,
 };
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
@@ -36,6 +39,12 @@ where
     // FIXME: this needs to be a vector, but it may also come as a single string
     #[serde(skip_serializing_if = "Option::is_none")]
     aud: Option<Vec<Audience>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exp: Option<Seconds>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    iat: Option<Seconds>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nbf: Option<Seconds>,
 
     #[serde(bound = "GC: GenderClaim")]
     #[serde(flatten)]
@@ -57,6 +66,15 @@ where
     pub fn audiences(&self) -> Option<&Vec<Audience>> {
         self.aud.as_ref()
     }
+    pub fn expiration(&self) -> Option<Result<DateTime<Utc>, ()>> {
+        self.exp.as_ref().map(seconds_to_utc)
+    }
+    pub fn issue_time(&self) -> Option<Result<DateTime<Utc>, ()>> {
+        self.iat.as_ref().map(seconds_to_utc)
+    }
+    pub fn not_before(&self) -> Option<Result<DateTime<Utc>, ()>> {
+        self.nbf.as_ref().map(seconds_to_utc)
+    }
     pub fn additional_claims(&self) -> &AC {
         &self.additional_claims
     }
@@ -135,91 +153,142 @@ where
 new_url_type![
     UserInfoUrl
     impl {
-        pub fn get_user_info<AC, GC, JE, JS, JT, JU, K>(
+        pub fn get_user_info<AC, GC, HC, JE, JK, JS, JT, JU, K>(
             &self,
             access_token: &AccessToken,
             verifier: &UserInfoVerifier<JE, JS, JT, JU, K>,
-        ) -> Result<UserInfoClaims<AC, GC>, UserInfoError>
+            http_client: &HC,
+        ) -> Result<UserInfoClaims<AC, GC>, UserInfoError<HC::Error>>
         where AC: AdditionalClaims,
                 GC: GenderClaim,
+                HC: HttpClient,
                 JE: JweContentEncryptionAlgorithm,
+                JK: JweKeyManagementAlgorithm,
                 JS: JwsSigningAlgorithm<JT>,
                 JT: JsonWebKeyType,
                 JU: JsonWebKeyUse,
-                K: JsonWebKey<JS, JT, JU>{
-            let (auth_header, auth_value) = auth_bearer(access_token);
+                K: PrivateDecryptionKey<JE, JK, JS, JT, JU>{
             let user_info_response =
-                HttpRequest {
-                    url: &self.0,
-                    method: HttpRequestMethod::Get,
-                    headers: &vec![ACCEPT_JSON, (auth_header, auth_value.as_ref())],
-                    post_body: &vec![],
-                }
-                .request()
-                .map_err(UserInfoError::Request)?;
-
-            // FIXME: improve error handling (i.e., is there a body response?)
-            // possibly consolidate this error handling with discovery::get_provider_metadata().
-            if user_info_response.status_code != HTTP_STATUS_OK {
-                return Err(
-                    UserInfoError::Response(
-                        user_info_response.status_code,
-                        "unexpected HTTP status code".to_string()
-                    )
-                );
-            }
+                http_client
+                    .request(user_info_request(self.url(), access_token))
+                    .map_err(UserInfoError::Request)?;
 
-            match user_info_response.content_type.as_ref().map(String::as_str) {
-                None | Some(MIME_TYPE_JSON) => {
-                    verifier
-                        .verified_claims(
-                            UnverifiedUserInfoClaims::JsonClaims(
-                                serde_json::from_slice(&user_info_response.body)
-                                    .map_err(UserInfoError::Json)?
-                            )
-                        )
-                        .map_err(UserInfoError::ClaimsVerification)
-                }
-                Some(MIME_TYPE_JWT) => {
-                    let jwt_str =
-                        str::from_utf8(&user_info_response.body)
-                            .map_err(|_|
-                                UserInfoError::Other(
-                                    "response body has invalid UTF-8 encoding".to_string()
-                                )
-                            )?;
-                    // TODO: Implement a simple deserializer so that we can go straight from a str
-                    // to a JsonWebToken without first converting to/from JSON.
-                    let jwt_json =
-                        serde_json::to_string(&jwt_str)
-                            .map_err(UserInfoError::Json)?;
-                    verifier
-                        .verified_claims(
-                            UnverifiedUserInfoClaims::JwtClaims(
-                                serde_json::from_str(&jwt_json)
-                                    .map_err(UserInfoError::Json)?
-                            )
-                        )
-                        .map_err(UserInfoError::ClaimsVerification)
-                }
-                Some(content_type) =>
-                    Err(
-                        UserInfoError::Response(
-                            user_info_response.status_code,
-                            format!("unexpected response Content-Type: `{}`", content_type)
-                        )
-                    ),
-            }
+            user_info_response_claims(verifier, user_info_response)
+        }
+
+        ///
+        /// Asynchronously fetches and verifies the UserInfo claims.
+        ///
+        /// This is the asynchronous counterpart of `get_user_info()`.
+        ///
+        pub fn get_user_info_async<'a, AC, GC, HC, JE, JK, JS, JT, JU, K>(
+            &self,
+            access_token: &AccessToken,
+            verifier: &'a UserInfoVerifier<JE, JS, JT, JU, K>,
+            http_client: &HC,
+        ) -> Box<Future<Item = UserInfoClaims<AC, GC>, Error = UserInfoError<HC::Error>> + 'a>
+        where AC: AdditionalClaims + 'a,
+                GC: GenderClaim + 'a,
+                HC: AsyncHttpClient,
+                HC::Future: 'a,
+                JE: JweContentEncryptionAlgorithm,
+                JK: JweKeyManagementAlgorithm,
+                JS: JwsSigningAlgorithm<JT>,
+                JT: JsonWebKeyType,
+                JU: JsonWebKeyUse,
+                K: PrivateDecryptionKey<JE, JK, JS, JT, JU>{
+            Box::new(
+                http_client
+                    .request(user_info_request(self.url(), access_token))
+                    .map_err(UserInfoError::Request)
+                    .and_then(move |user_info_response| {
+                        user_info_response_claims(verifier, user_info_response)
+                    })
+            )
         }
     }
 ];
 
+fn user_info_request(user_info_url: &Url, access_token: &AccessToken) -> HttpRequest {
+    HttpRequest {
+        url: user_info_url.clone(),
+        method: HttpRequestMethod::Get,
+        headers: vec![accept_json(), auth_bearer(access_token)],
+        body: vec![],
+    }
+}
+
+fn user_info_response_claims<AC, GC, JE, JK, JS, JT, JU, K, RE>(
+    verifier: &UserInfoVerifier<JE, JS, JT, JU, K>,
+    user_info_response: HttpResponse,
+) -> Result<UserInfoClaims<AC, GC>, UserInfoError<RE>>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm,
+    JK: JweKeyManagementAlgorithm,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: PrivateDecryptionKey<JE, JK, JS, JT, JU>,
+    RE: Fail,
+{
+    // FIXME: improve error handling (i.e., is there a body response?)
+    // possibly consolidate this error handling with discovery::get_provider_metadata().
+    if user_info_response.status_code != HTTP_STATUS_OK {
+        return Err(UserInfoError::Response(
+            user_info_response.status_code,
+            "unexpected HTTP status code".to_string(),
+        ));
+    }
+
+    // Section 3.1.1.1 of RFC 7231 indicates that media types are case insensitive and may be
+    // followed by parameters (e.g., charset), which don't affect how the response is parsed.
+    match user_info_response.media_type().as_ref().map(String::as_str) {
+        None | Some(MIME_TYPE_JSON) => verifier
+            .verified_claims(UnverifiedUserInfoClaims::JsonClaims(
+                serde_json::from_slice(&user_info_response.body).map_err(UserInfoError::Json)?,
+            )).map_err(UserInfoError::ClaimsVerification),
+        Some(MIME_TYPE_JWT) => {
+            let jwt_str = str::from_utf8(&user_info_response.body).map_err(|_| {
+                UserInfoError::Other("response body has invalid UTF-8 encoding".to_string())
+            })?;
+            // TODO: Implement a simple deserializer so that we can go straight from a str
+            // to a JsonWebToken without first converting to/from JSON.
+            let jwt_json = serde_json::to_string(&jwt_str).map_err(UserInfoError::Json)?;
+            // The JWE compact serialization has five parts, while the JWS one has three.
+            let unverified_user_info = if jwt_str.split('.').count() == 5 {
+                UnverifiedUserInfoClaims::JweClaims(
+                    serde_json::from_str(&jwt_json).map_err(UserInfoError::Json)?,
+                )
+            } else {
+                UnverifiedUserInfoClaims::JwtClaims(
+                    serde_json::from_str(&jwt_json).map_err(UserInfoError::Json)?,
+                )
+            };
+            verifier
+                .verified_claims(unverified_user_info)
+                .map_err(UserInfoError::ClaimsVerification)
+        }
+        Some(_) => Err(UserInfoError::Response(
+            user_info_response.status_code,
+            format!(
+                "unexpected response Content-Type: `{}`",
+                user_info_response.content_type.as_ref().unwrap()
+            ),
+        )),
+    }
+}
+
 #[derive(Debug, Fail)]
-pub enum UserInfoError {
+pub enum UserInfoError<RE>
+where
+    RE: Fail,
+{
     #[fail(display = "Failed to verify claims")]
     ClaimsVerification(#[cause] ClaimsVerificationError),
     #[fail(display = "Request failed")]
-    Request(#[cause] curl::Error),
+    Request(#[cause] RE),
     #[fail(display = "Response error (status={}): {}", _0, _1)]
     Response(u32, String),
     #[fail(display = "Failed to parse response")]
@@ -242,4 +311,217 @@ where
         #[serde(bound = "AC: AdditionalClaims")]
         JsonWebToken<UserInfoClaims<AC, GC>, JE, JS, JT, JsonWebTokenJsonPayloadDeserializer>,
     ),
+    // Either encrypted JSON claims or a nested signed JWT; we can't tell which until the JWE is
+    // decrypted.
+    JweClaims(JsonWebEncryption),
+}
+
+#[cfg(test)]
+mod tests {
+    // Most of these are only needed by the RSA-OAEP encrypted UserInfo tests.
+    #![cfg_attr(not(feature = "rsa-oaep"), allow(unused_imports))]
+
+    use std::io;
+    use std::time::Duration;
+
+    use chrono::{TimeZone, Utc};
+    use oauth2::prelude::*;
+    use oauth2::ClientId;
+    use serde_json;
+
+    use super::super::core::{
+        CoreGenderClaim, CoreJsonWebKey, CoreJsonWebKeySet, CoreUserInfoClaims,
+        CoreUserInfoVerifier,
+    };
+    use super::super::http::{HttpResponse, HTTP_STATUS_OK, MIME_TYPE_JSON, MIME_TYPE_JWT};
+    use super::super::jwt::tests::TEST_RSA_PRIV_KEY;
+    use super::super::{
+        ClaimsVerificationError, EmptyAdditionalClaims, IssuerUrl, PrivateSigningKey,
+        StandardClaims, SubjectIdentifier,
+    };
+    use super::{user_info_response_claims, UserInfoError};
+
+    // UserInfo JWT signed with TEST_RSA_PRIV_KEY (RS256) and then encrypted to the same key using
+    // RSA-OAEP and A128GCM.
+    #[cfg(feature = "rsa-oaep")]
+    const TEST_NESTED_USER_INFO_JWT: &str =
+        "eyJhbGciOiJSU0EtT0FFUCIsImVuYyI6IkExMjhHQ00iLCJraWQiOiJyc2EtZW5jcnlwdGlvbi1rZXkiLCJjdHkiOi\
+         JKV1QifQ.oONwVY8j-Shfo8M4-pBw9NwSpsjGvwTliz8KQu6dW92y23sp2jRRJBS5ygLbvC6xX13aSOr0pVjMBf2fV\
+         fDL71AEVXD6czdnHbiHRo6w3QY3ZO-K1-HBllu4kkzt6_tWDO49zGYXXfdCzUlyHPY7X7VyLOjrdP8aZilXSFur9lO\
+         ESWXwc17f2mYZPRxQIw-zlBLacPXO4_icto9uCKf9qnEqGtLTP4GGXdWBrQ_1SBSkMz80hYowJKtxQyqsyCVhqCOY_\
+         ry4tcms0QGaZC99KB8gonEfggcKkVpjWYWPfVmduYVd-Sum_ocSokIS6PCxKv2ttGl1AvvlizBQCbOlAg.35nVJVi5\
+         11jNIIr4.d4CZF-fhOSyXS4hr2IIUk8yYWV4-4lAHYpnNbheSrKrcRrTiGfE9rnKook4i4tRqz_LqxDC6EIP3MP0qg\
+         ItQB7hpKFmgmAeJBKz33UWKt_pRBl7HmosQK3pIX9k_aV9AaBjG5gdoJUtfj-RQCgX04hEXAdaDR5KHrihJ9x48mkr\
+         RqMZkpGaD82H4ubTZok7wMBRWQysuIwUKcG8NWawpznFB_ihad82u2ZDroe2MQFcIL5i9E3a_cKHd2tDWS0VLykPO-\
+         9DvZhj-i4U3xvgd8wIEXNa_AdHG01epsVBZczf_-BJpqIbZIa_7-iV78oJOCXjxOX--QbojZl6W2fXpfSU1Rg8WLAK\
+         lxBFURliNQjwcMWKxdQlqK45U7qEeSTW08KF_0QaHP-3Rt4b-Ku9DpzE_ev_gfTxD3BCZ85H47fqODB7GSweREEZkI\
+         NdMUGC-j0S30P4kV7QU7VYJnRbo8L2LdgoSADRzJ7GRGwX0AueYKwJJcRcSlFtKgB-B3xGSlarURRsuiSvk7gggw0S\
+         qOaSJlqQr2BNsra2otTXoI1uH5yqW1JaZLhAuZe98oAxGXblyNSXb2PGC5cALPSlXIo5l75tUeE3I75zgYhrYtxBvI\
+         vKzOht53hm4ZMefXPTvKUtK3uRhHxioVhjqVWiyczkyOcHSyrB0OkbAsnECaiMHRqNePK_Mfv-Jj4Nk49cVUCaeBP1\
+         p1g.xYVgh8zN1XDU1jSj39-ZKQ";
+
+    // UserInfo JSON claims encrypted to TEST_RSA_PRIV_KEY using RSA-OAEP and A128GCM.
+    #[cfg(feature = "rsa-oaep")]
+    const TEST_ENCRYPTED_USER_INFO_JSON: &str =
+        "eyJhbGciOiJSU0EtT0FFUCIsImVuYyI6IkExMjhHQ00iLCJraWQiOiJyc2EtZW5jcnlwdGlvbi1rZXkifQ.Y7s9Hxy\
+         qXF_w1YfeNGN7lZ1i7-fNohb_IQppPZpl_BSeaPzfQrP1HG0PH-N13AbyuoAKB9EMObB8eA3xRtoOhg0UwzqobpS-y\
+         PIM-3_XRR4Xd_GX_trlkmjqOBAkO4Qu9VNeKGM_05jvs4URJdB56PXgHX59j67bEDxDSf0lslI5WlQkTqRtPxw4Fho\
+         MKuRIvUK_L7MVPVr8JnfmrzcJBy0X1ftgvwPxLqn3dx3pAVYD7o58J8iuB_gVZAvwkCV2qq_m20AlZme0fgm3RAUS6\
+         ekhqmeBLNJ0mCTzpUcrWTsHMuMSmW6gjd_nKRB1ksLkM_krhGdzjn8kxC718VdzVw.zim1xzFyF3B4FrIu.e-WDAiH\
+         gmSfg5UOfk5ng-Xl7nQcLl2dQ75cgcDFSamsQPVJ1.Ij1VJd-37Rg1P6K4C8zsQg";
+
+    fn user_info_claims(
+        verifier: &CoreUserInfoVerifier,
+        content_type: &str,
+        body: &str,
+    ) -> Result<CoreUserInfoClaims, UserInfoError<io::Error>> {
+        user_info_response_claims::<EmptyAdditionalClaims, CoreGenderClaim, _, _, _, _, _, _, _>(
+            verifier,
+            HttpResponse {
+                status_code: HTTP_STATUS_OK,
+                content_type: Some(content_type.to_string()),
+                body: body.as_bytes().to_vec(),
+            },
+        )
+    }
+
+    #[test]
+    #[cfg(feature = "rsa-oaep")]
+    fn test_encrypted_user_info() {
+        let signing_key: CoreJsonWebKey =
+            serde_json::from_str(TEST_RSA_PRIV_KEY).expect("deserialization failed");
+        let decryption_key: CoreJsonWebKey = serde_json::from_str(
+            &TEST_RSA_PRIV_KEY
+                .replace("\"rsa-signing-key\"", "\"rsa-encryption-key\"")
+                .replace("\"sig\"", "\"enc\""),
+        ).expect("deserialization failed");
+        let new_verifier = || {
+            CoreUserInfoVerifier::new(
+                ClientId::new("s6BhdRkqt3".to_string()),
+                IssuerUrl::new("https://server.example.com".to_string()).unwrap(),
+                CoreJsonWebKeySet::new(vec![signing_key.as_verification_key()]),
+                SubjectIdentifier::new("24400320".to_string()),
+            )
+        };
+        let verifier = new_verifier().set_decryption_keys(vec![decryption_key.clone()]);
+
+        // Signed, then encrypted.
+        let claims = user_info_claims(&verifier, MIME_TYPE_JWT, TEST_NESTED_USER_INFO_JWT)
+            .expect("verification failed");
+        assert_eq!(
+            *claims.sub(),
+            SubjectIdentifier::new("24400320".to_string())
+        );
+        assert_eq!(
+            claims.issuer().map(|issuer| issuer.url().as_str()),
+            Some("https://server.example.com/")
+        );
+        let signed_verifier = new_verifier()
+            .set_decryption_keys(vec![decryption_key])
+            .require_signed_response(true);
+        user_info_claims(&signed_verifier, MIME_TYPE_JWT, TEST_NESTED_USER_INFO_JWT)
+            .expect("verification failed");
+
+        // Encrypted only.
+        let claims = user_info_claims(&verifier, MIME_TYPE_JWT, TEST_ENCRYPTED_USER_INFO_JSON)
+            .expect("verification failed");
+        assert_eq!(
+            *claims.sub(),
+            SubjectIdentifier::new("24400320".to_string())
+        );
+        assert_eq!(claims.issuer(), None);
+        // Encrypted claims aren't signed.
+        match user_info_claims(
+            &signed_verifier,
+            MIME_TYPE_JWT,
+            TEST_ENCRYPTED_USER_INFO_JSON,
+        ) {
+            Err(UserInfoError::ClaimsVerification(ClaimsVerificationError::NoSignature)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Unencrypted responses are rejected once decryption keys are set.
+        match user_info_claims(
+            &verifier,
+            MIME_TYPE_JSON,
+            "{\"sub\": \"24400320\", \"name\": \"Jane Doe\"}",
+        ) {
+            Err(UserInfoError::ClaimsVerification(ClaimsVerificationError::NoEncryption)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match user_info_claims(&new_verifier(), MIME_TYPE_JWT, TEST_NESTED_USER_INFO_JWT) {
+            Err(UserInfoError::ClaimsVerification(ClaimsVerificationError::Decryption(_))) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_user_info_content_type() {
+        let verifier = CoreUserInfoVerifier::new(
+            ClientId::new("s6BhdRkqt3".to_string()),
+            IssuerUrl::new("https://server.example.com".to_string()).unwrap(),
+            CoreJsonWebKeySet::new(vec![]),
+            SubjectIdentifier::new("24400320".to_string()),
+        );
+
+        // Media types are case insensitive and may include parameters.
+        for content_type in &[
+            "application/json",
+            "Application/JSON",
+            "application/json; charset=utf-8",
+            "application/json;charset=UTF-8",
+        ] {
+            let claims = user_info_claims(&verifier, content_type, "{\"sub\": \"24400320\"}")
+                .expect("failed to parse claims");
+            assert_eq!(
+                *claims.sub(),
+                SubjectIdentifier::new("24400320".to_string())
+            );
+        }
+
+        match user_info_claims(
+            &verifier,
+            "text/html; charset=utf-8",
+            "{\"sub\": \"24400320\"}",
+        ) {
+            Err(UserInfoError::Response(HTTP_STATUS_OK, ref message)) => assert_eq!(
+                "unexpected response Content-Type: `text/html; charset=utf-8`",
+                message
+            ),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_user_info_validity_period() {
+        let new_verifier = || {
+            CoreUserInfoVerifier::new(
+                ClientId::new("s6BhdRkqt3".to_string()),
+                IssuerUrl::new("https://server.example.com".to_string()).unwrap(),
+                CoreJsonWebKeySet::new(vec![]),
+                SubjectIdentifier::new("24400320".to_string()),
+            ).set_time_fn(Box::new(|| Utc.timestamp(1311281000, 0)))
+        };
+        let expired = "{\"sub\": \"24400320\", \"exp\": 1311280990}";
+        let not_yet_valid = "{\"sub\": \"24400320\", \"nbf\": 1311281010}";
+
+        let claims = user_info_claims(&new_verifier(), MIME_TYPE_JSON, "{\"sub\": \"24400320\"}")
+            .expect("verification failed");
+        assert_eq!(claims.expiration(), None);
+        assert_eq!(claims.not_before(), None);
+        for body in &[expired, not_yet_valid] {
+            match user_info_claims(&new_verifier(), MIME_TYPE_JSON, body) {
+                Err(UserInfoError::ClaimsVerification(ClaimsVerificationError::Expired(_))) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+
+        let verifier = new_verifier().set_leeway(Duration::from_secs(10));
+        let claims =
+            user_info_claims(&verifier, MIME_TYPE_JSON, expired).expect("verification failed");
+        assert_eq!(claims.expiration(), Some(Ok(Utc.timestamp(1311280990, 0))));
+        let claims = user_info_claims(&verifier, MIME_TYPE_JSON, not_yet_valid)
+            .expect("verification failed");
+        assert_eq!(claims.not_before(), Some(Ok(Utc.timestamp(1311281010, 0))));
+    }
 }
@@ -2,21 +2,28 @@ use std::collections::HashSet;
 use std::fmt::Debug;
 use std::marker::PhantomData;
 use std::ops::Deref;
+use std::sync::Arc;
+use std::time::Duration;
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
 use oauth2::helpers::variant_name;
 use oauth2::prelude::*;
 use oauth2::{ClientId, ClientSecret};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use serde_json;
 
-use super::jwt::{JsonWebToken, JsonWebTokenJsonPayloadDeserializer};
+use super::jwt::{
+    client_secret_encryption_key, JsonWebEncryption, JsonWebEncryptionHeader, JsonWebToken,
+    JsonWebTokenJsonPayloadDeserializer,
+};
 use super::user_info::UnverifiedUserInfoClaims;
 use super::{
-    AdditionalClaims, Audience, AuthenticationContextClass, GenderClaim, IdTokenClaims, IssuerUrl,
-    JsonWebKey, JsonWebKeySet, JsonWebKeyType, JsonWebKeyUse, JsonWebTokenAccess,
-    JsonWebTokenAlgorithm, JsonWebTokenHeader, JweContentEncryptionAlgorithm, JwsSigningAlgorithm,
-    Nonce, StandardClaims, SubjectIdentifier, UserInfoClaims,
+    AdditionalClaims, Audience, AuthenticationContextClass, ConfigurationError, GenderClaim,
+    IdTokenClaims, IssuerUrl, JsonWebKey, JsonWebKeySet, JsonWebKeyType, JsonWebKeyUse,
+    JsonWebTokenAccess, JsonWebTokenAlgorithm, JsonWebTokenHeader, JweContentEncryptionAlgorithm,
+    JweKeyManagementAlgorithm, JwsSigningAlgorithm, Nonce, PrivateDecryptionKey, ReplayCache,
+    StandardClaims, SubjectIdentifier, UserInfoClaims,
 };
 
 /*
@@ -56,14 +63,56 @@ pub trait IssuerClaim {
     fn issuer(&self) -> Option<&IssuerUrl>;
 }
 
+///
+/// Verifier for the `nonce` claim of ID tokens.
+///
+/// This is implemented by `&Nonce`, which requires the `nonce` claim to exactly match the nonce
+/// sent in the authentication request, and by functions and closures accepting the `nonce` claim
+/// (if present) and returning an error message if verification fails. The latter allow clients
+/// to validate nonces without storing them (e.g., by deriving each nonce from the End-User's
+/// session using a MAC).
+///
+pub trait NonceVerifier {
+    ///
+    /// Verifies the `nonce` claim, returning an error message if verification fails.
+    ///
+    fn verify(self, nonce: Option<&Nonce>) -> Result<(), String>;
+}
+impl<'a> NonceVerifier for &'a Nonce {
+    fn verify(self, nonce: Option<&Nonce>) -> Result<(), String> {
+        if let Some(claims_nonce) = nonce {
+            if claims_nonce != self {
+                return Err("nonce mismatch".to_string());
+            }
+        } else {
+            return Err("missing nonce claim".to_string());
+        }
+        Ok(())
+    }
+}
+impl<F> NonceVerifier for F
+where
+    F: FnOnce(Option<&Nonce>) -> Result<(), String>,
+{
+    fn verify(self, nonce: Option<&Nonce>) -> Result<(), String> {
+        self(nonce)
+    }
+}
+
 ///
 /// Error verifying claims.
 ///
 #[derive(Clone, Debug, Fail, PartialEq)]
 pub enum ClaimsVerificationError {
+    /// Failed to decrypt the claims.
+    #[fail(display = "Decryption failed")]
+    Decryption(#[cause] DecryptionError),
     /// Claims have expired.
     #[fail(display = "Expired: {}", _0)]
     Expired(String),
+    /// Access token hash (`at_hash`) claim is missing or does not match the access token.
+    #[fail(display = "Invalid access token hash: {}", _0)]
+    InvalidAccessTokenHash(String),
     /// Audience claim is invalid.
     #[fail(display = "Invalid audiences: {}", _0)]
     InvalidAudience(String),
@@ -72,10 +121,14 @@ pub enum ClaimsVerificationError {
         display = "Invalid authorization context class reference: {}",
         _0
     )]
-    InvalidAuthContext(String),
+    InvalidAuthContext(AuthContextError),
     /// User authenticated too long ago.
     #[fail(display = "Invalid authentication time: {}", _0)]
     InvalidAuthTime(String),
+    /// Authorization code hash (`c_hash`) claim is missing or does not match the authorization
+    /// code.
+    #[fail(display = "Invalid authorization code hash: {}", _0)]
+    InvalidAuthorizationCodeHash(String),
     /// Issuer claim is invalid.
     #[fail(display = "Invalid issuer: {}", _0)]
     InvalidIssuer(String),
@@ -85,12 +138,18 @@ pub enum ClaimsVerificationError {
     /// Subject claim is invalid.
     #[fail(display = "Invalid subject: {}", _0)]
     InvalidSubject(String),
+    /// Claims must be encrypted but were received unencrypted.
+    #[fail(display = "Claims must be encrypted")]
+    NoEncryption,
     /// No signature present but claims must be signed.
     #[fail(display = "Claims must be signed")]
     NoSignature,
     /// An unexpected error occurred.
     #[fail(display = "{}", _0)]
     Other(String),
+    /// Nonce or JWT ID (`jti`) claim was already consumed by a previously verified token.
+    #[fail(display = "Replayed: {}", _0)]
+    Replayed(String),
     /// Failed to verify the claims signature.
     #[fail(display = "Signature verification failed")]
     SignatureVerification(#[cause] SignatureVerificationError),
@@ -99,6 +158,60 @@ pub enum ClaimsVerificationError {
     Unsupported(String),
 }
 
+///
+/// Error verifying the authentication context class reference (`acr`) claim.
+///
+#[derive(Clone, Debug, Fail, PartialEq)]
+#[fail(display = "{}", message)]
+pub struct AuthContextError {
+    message: String,
+    requested: Vec<AuthenticationContextClass>,
+    received: Option<AuthenticationContextClass>,
+}
+impl AuthContextError {
+    fn new(
+        requested: &[AuthenticationContextClass],
+        received: Option<&AuthenticationContextClass>,
+        reason: &str,
+    ) -> Self {
+        AuthContextError {
+            message: format!(
+                "{} (requested: {}; received: {})",
+                reason,
+                requested
+                    .iter()
+                    .map(|acr| format!("`{}`", **acr))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                received.map_or("none".to_string(), |acr| format!("`{}`", **acr))
+            ),
+            requested: requested.to_vec(),
+            received: received.cloned(),
+        }
+    }
+
+    ///
+    /// Returns a description of the error, including the requested and received values.
+    ///
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+    ///
+    /// Returns the requested authentication context class references, if known. This is empty
+    /// for errors returned by custom verification functions (see
+    /// [`IdTokenVerifier::set_auth_context_verifier_fn`]).
+    ///
+    pub fn requested(&self) -> &[AuthenticationContextClass] {
+        &self.requested
+    }
+    ///
+    /// Returns the authentication context class reference asserted by the ID token, if any.
+    ///
+    pub fn received(&self) -> Option<&AuthenticationContextClass> {
+        self.received.as_ref()
+    }
+}
+
 ///
 /// Error verifying claims signature.
 ///
@@ -143,6 +256,34 @@ pub enum SignatureVerificationError {
     Other(String),
 }
 
+///
+/// Error decrypting [JSON Web Encryption](https://tools.ietf.org/html/rfc7516) (JWE) content.
+///
+#[derive(Clone, Debug, Fail, PartialEq)]
+pub enum DecryptionError {
+    /// More than one key matches the supplied key constraints (e.g., key ID).
+    #[fail(display = "Ambiguous key identification: {}", _0)]
+    AmbiguousKeyId(String),
+    /// Failed to decrypt the content or verify its authentication tag using the supplied key.
+    #[fail(display = "Crypto error: {}", _0)]
+    CryptoError(String),
+    /// The supplied key cannot be used in this context. This may occur if the key type does not
+    /// match the key management algorithm (e.g., a symmetric key used with RSA-OAEP), the key is
+    /// missing private key parameters, or the JWK usage disallows encryption.
+    #[fail(display = "Invalid cryptographic key: {}", _0)]
+    InvalidKey(String),
+    /// None of the client's decryption keys match the JWE header's key ID (`kid`) and key
+    /// management algorithm (`alg`).
+    #[fail(display = "No matching key found")]
+    NoMatchingKey,
+    /// Unsupported key management or content encryption algorithm.
+    #[fail(display = "Unsupported encryption algorithm: {}", _0)]
+    UnsupportedAlg(String),
+    /// An unexpected error occurred.
+    #[fail(display = "Other error: {}", _0)]
+    Other(String),
+}
+
 // This struct is intentionally private.
 #[derive(Clone, Debug)]
 struct JwtClaimsVerifier<JS, JT, JU, K>
@@ -156,10 +297,12 @@ where
     aud_required: bool,
     client_id: ClientId,
     client_secret: Option<ClientSecret>,
+    decryption_keys: Vec<K>,
     iss_required: bool,
     issuer: IssuerUrl,
     is_signature_check_enabled: bool,
     signature_keys: JsonWebKeySet<JS, JT, JU, K>,
+    trusted_audiences: Option<HashSet<Audience>>,
 }
 impl<JS, JT, JU, K> JwtClaimsVerifier<JS, JT, JU, K>
 where
@@ -178,10 +321,12 @@ where
             aud_required: true,
             client_id,
             client_secret: None,
+            decryption_keys: Vec::new(),
             iss_required: true,
             issuer,
             is_signature_check_enabled: true,
             signature_keys,
+            trusted_audiences: None,
         }
     }
 
@@ -217,6 +362,110 @@ where
         self
     }
 
+    pub fn set_decryption_keys(mut self, decryption_keys: Vec<K>) -> Self {
+        self.decryption_keys = decryption_keys;
+        self
+    }
+
+    pub fn set_trusted_audiences<I>(mut self, audiences: I) -> Self
+    where
+        I: IntoIterator<Item = Audience>,
+    {
+        self.trusted_audiences = Some(audiences.into_iter().collect());
+        self
+    }
+
+    pub fn is_decryption_enabled(&self) -> bool {
+        !self.decryption_keys.is_empty()
+    }
+
+    // Decrypts a JWE using the client's decryption keys and returns its JOSE header along with
+    // the plaintext. Whether the plaintext is itself a JWT is up to the caller to determine.
+    pub fn decrypt<JE, JK>(
+        &self,
+        jwe: &JsonWebEncryption,
+    ) -> Result<(JsonWebEncryptionHeader<JE, JK>, Vec<u8>), ClaimsVerificationError>
+    where
+        JE: JweContentEncryptionAlgorithm,
+        JK: JweKeyManagementAlgorithm,
+        K: PrivateDecryptionKey<JE, JK, JS, JT, JU>,
+    {
+        let jose_header = jwe.unverified_header::<JE, JK>().map_err(|err| {
+            ClaimsVerificationError::Other(format!("Failed to parse JWE header JSON: {:?}", err))
+        })?;
+
+        // See the comment in validate_jose_header below.
+        if jose_header.crit.is_some() {
+            return Err(ClaimsVerificationError::Unsupported(
+                "critical JWE header fields are unsupported".to_string(),
+            ));
+        }
+        // OpenID Connect providers have no reason to compress the (short) JWT plaintext, and
+        // decompressing untrusted input carries its own risks.
+        if let Some(ref zip) = jose_header.zip {
+            return Err(ClaimsVerificationError::Unsupported(format!(
+                "unsupported JWE compression algorithm `{}`",
+                zip
+            )));
+        }
+
+        // As with signature verification, the 'kid' and 'alg' header fields are untrusted. Each
+        // key's own algorithm checks must hold regardless of how these fields are manipulated.
+        let mut decryption_keys = self
+            .decryption_keys
+            .iter()
+            .filter(|key| {
+                key.allows_key_management_alg(&jose_header.alg) &&
+                    // Either the key hasn't specified it's allowed usage (in which case any usage
+                    // is acceptable), or the key supports encryption.
+                    (key.key_use().is_none() ||
+                        key.key_use().iter().any(|key_use| key_use.allows_encryption())) &&
+                    (jose_header.kid.is_none() || jose_header.kid.as_ref() == key.key_id())
+            }).collect::<Vec<&K>>();
+
+        // Section 10.2 of OpenID Connect Core 1.0 specifies that symmetric encryption keys are
+        // derived from the client secret. This key is only used if none of the client's own keys
+        // are eligible, and it's subject to the same algorithm checks.
+        let client_secret_key;
+        if decryption_keys.is_empty() {
+            let key_len = jose_header.alg.symmetric_key_len(&jose_header.enc);
+            if let (Some(client_secret), Some(key_len)) = (self.client_secret.as_ref(), key_len) {
+                client_secret_key = client_secret_encryption_key(client_secret, key_len)
+                    .map(K::new_symmetric)
+                    .ok_or_else(|| {
+                        ClaimsVerificationError::Decryption(DecryptionError::UnsupportedAlg(
+                            format!(
+                                "no {}-bit key can be derived from the client secret",
+                                key_len * 8
+                            ),
+                        ))
+                    })?;
+                if client_secret_key.allows_key_management_alg(&jose_header.alg) {
+                    decryption_keys.push(&client_secret_key);
+                }
+            }
+        }
+
+        if decryption_keys.is_empty() {
+            return Err(ClaimsVerificationError::Decryption(
+                DecryptionError::NoMatchingKey,
+            ));
+        } else if decryption_keys.len() != 1 {
+            return Err(ClaimsVerificationError::Decryption(
+                DecryptionError::AmbiguousKeyId(format!(
+                    "found {} eligible decryption keys; the JWE header must include a key ID \
+                     matching exactly one of them",
+                    decryption_keys.len()
+                )),
+            ));
+        }
+
+        let plaintext = decryption_keys[0]
+            .decrypt(&jose_header.alg, &jose_header.enc, jwe)
+            .map_err(ClaimsVerificationError::Decryption)?;
+        Ok((jose_header, plaintext))
+    }
+
     fn validate_jose_header<JE>(
         jose_header: &JsonWebTokenHeader<JE, JS, JT>,
     ) -> Result<(), ClaimsVerificationError>
@@ -233,8 +482,9 @@ where
             }
         }
         // The 'cty' header field must be omitted, since it's only used for JWTs that contain
-        // content types other than JSON-encoded claims. This may include nested JWTs, such as if
-        // JWE encryption is used. This is currently unsupported.
+        // content types other than JSON-encoded claims. This may include nested JWTs. Encrypted
+        // JWTs nesting a signed JWT are decrypted separately (see decrypt above), and the nested
+        // JWT arrives here on its own; signed JWTs nesting other JWTs are unsupported.
         if let Some(ref content_type) = jose_header.cty {
             if content_type.to_uppercase() == "JWT" {
                 return Err(ClaimsVerificationError::Unsupported(
@@ -279,16 +529,17 @@ where
             //    specified during Registration that the OP was to use to encrypt the ID Token. If
             //    encryption was negotiated with the OP at Registration time and the ID Token is not
             //    encrypted, the RP SHOULD reject it.
-
+            //
+            // Encrypted JWTs are decrypted by the caller (see decrypt above) before their nested
+            // JWTs are passed here, so this JWT must not be encrypted.
             if let JsonWebTokenAlgorithm::Encryption(ref encryption_alg) = jose_header.alg {
                 return Err(ClaimsVerificationError::Unsupported(format!(
-                    "JWE encryption is not currently supported (found algorithm `{}`)",
+                    "unexpected JWE encryption algorithm `{}` in JWS header",
                     variant_name(encryption_alg),
                 )));
             }
         }
 
-        // TODO: Add encryption (JWE) support
         {
             // 2. The Issuer Identifier for the OpenID Provider (which is typically obtained during
             //    Discovery) MUST exactly match the value of the iss (issuer) Claim.
@@ -330,6 +581,24 @@ where
                                 .join(", ")
                         )));
                     }
+
+                    // Unless the client has specified which additional audiences it trusts, we
+                    // accept any additional audiences.
+                    if let Some(ref trusted_audiences) = self.trusted_audiences {
+                        let untrusted_audiences = audiences
+                            .iter()
+                            .filter(|aud| {
+                                (**aud).deref() != self.client_id.deref()
+                                    && !trusted_audiences.contains(aud)
+                            }).map(|aud| format!("`{}`", Deref::deref(aud)))
+                            .collect::<Vec<_>>();
+                        if !untrusted_audiences.is_empty() {
+                            return Err(ClaimsVerificationError::InvalidAudience(format!(
+                                "untrusted audiences: {}",
+                                untrusted_audiences.join(", ")
+                            )));
+                        }
+                    }
                 } else {
                     return Err(ClaimsVerificationError::InvalidAudience(
                         "missing audiences claim".to_string(),
@@ -469,6 +738,151 @@ where
     }
 }
 
+///
+/// Policy for verifying the authorized party (`azp`) claim of ID tokens (see steps 4 and 5 of
+/// [Section 3.1.3.7 of OpenID Connect Core 1.0](
+///     https://openid.net/specs/openid-connect-core-1_0.html#IDTokenValidation)).
+///
+pub enum AuthorizedPartyPolicy {
+    ///
+    /// Ignore the `azp` claim (the default).
+    ///
+    Ignore,
+    ///
+    /// Require an `azp` claim if the ID token has more than one audience, and require any `azp`
+    /// claim to match the client ID.
+    ///
+    RequireForMultipleAudiences,
+    ///
+    /// Always require an `azp` claim matching the client ID.
+    ///
+    RequireClientId,
+    ///
+    /// Verify the `azp` claim using a custom function, which is passed the `azp` claim (if
+    /// present) and the ID token's audiences, and returns an error message if verification
+    /// fails.
+    ///
+    Custom(Box<Fn(Option<&ClientId>, &[Audience]) -> Result<(), String>>),
+}
+
+///
+/// Policy for verifying the authentication context class reference (`acr`) claim of ID tokens
+/// (see step 12 of [Section 3.1.3.7 of OpenID Connect Core 1.0](
+///     https://openid.net/specs/openid-connect-core-1_0.html#IDTokenValidation)).
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum AuthContextPolicy {
+    ///
+    /// Accept any `acr` claim, or none (the default).
+    ///
+    Ignore,
+    ///
+    /// Require any `acr` claim to be one of the given values, as requested voluntarily via the
+    /// `acr_values` parameter. ID tokens without an `acr` claim are accepted, since the provider
+    /// is not required to satisfy voluntary requests.
+    ///
+    AnyOf(Vec<AuthenticationContextClass>),
+    ///
+    /// Require an `acr` claim with one of the given values, as requested by marking the `acr`
+    /// claim as essential.
+    ///
+    Essential(Vec<AuthenticationContextClass>),
+    ///
+    /// Require an `acr` claim at or above a minimum level of assurance. Use
+    /// [`at_least`](#method.at_least) to construct this policy.
+    ///
+    AtLeast(AuthContextLevels),
+}
+impl AuthContextPolicy {
+    ///
+    /// Returns a policy requiring an `acr` claim at or above the `minimum` level of assurance,
+    /// where `levels` lists the recognized values from the lowest to the highest level of
+    /// assurance.
+    ///
+    /// Returns an error if `minimum` isn't one of the given `levels`, since no ID token could
+    /// satisfy the policy.
+    ///
+    pub fn at_least(
+        levels: Vec<AuthenticationContextClass>,
+        minimum: AuthenticationContextClass,
+    ) -> Result<Self, ConfigurationError> {
+        let minimum_index = levels
+            .iter()
+            .position(|level| *level == minimum)
+            .ok_or_else(|| ConfigurationError::UnrecognizedAuthContextClass((*minimum).clone()))?;
+        Ok(AuthContextPolicy::AtLeast(AuthContextLevels {
+            levels,
+            minimum_index,
+        }))
+    }
+
+    fn verify(&self, acr: Option<&AuthenticationContextClass>) -> Result<(), AuthContextError> {
+        match *self {
+            AuthContextPolicy::Ignore => Ok(()),
+            AuthContextPolicy::AnyOf(ref requested) => match acr {
+                Some(acr) if !requested.contains(acr) => Err(AuthContextError::new(
+                    requested,
+                    Some(acr),
+                    "unexpected authentication context class reference",
+                )),
+                _ => Ok(()),
+            },
+            AuthContextPolicy::Essential(ref requested) => match acr {
+                Some(acr) if requested.contains(acr) => Ok(()),
+                Some(acr) => Err(AuthContextError::new(
+                    requested,
+                    Some(acr),
+                    "unexpected authentication context class reference",
+                )),
+                None => Err(AuthContextError::new(
+                    requested,
+                    None,
+                    "missing essential authentication context class reference",
+                )),
+            },
+            AuthContextPolicy::AtLeast(ref levels) => {
+                let requested = levels.accepted();
+                match acr {
+                    Some(acr) if requested.contains(acr) => Ok(()),
+                    Some(acr) => Err(AuthContextError::new(
+                        requested,
+                        Some(acr),
+                        "insufficient authentication context class reference",
+                    )),
+                    None => Err(AuthContextError::new(
+                        requested,
+                        None,
+                        "missing authentication context class reference",
+                    )),
+                }
+            }
+        }
+    }
+}
+
+///
+/// Recognized authentication context class references ordered by level of assurance, along with
+/// the minimum level required by [`AuthContextPolicy::AtLeast`](enum.AuthContextPolicy.html).
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuthContextLevels {
+    levels: Vec<AuthenticationContextClass>,
+    minimum_index: usize,
+}
+impl AuthContextLevels {
+    pub fn levels(&self) -> &[AuthenticationContextClass] {
+        &self.levels
+    }
+
+    pub fn minimum(&self) -> &AuthenticationContextClass {
+        &self.levels[self.minimum_index]
+    }
+
+    fn accepted(&self) -> &[AuthenticationContextClass] {
+        &self.levels[self.minimum_index..]
+    }
+}
+
 ///
 /// ID token verifier.
 ///
@@ -479,10 +893,17 @@ where
     JU: JsonWebKeyUse,
     K: JsonWebKey<JS, JT, JU>,
 {
+    acr_policy: AuthContextPolicy,
     acr_verifier_fn: Box<Fn(Option<&AuthenticationContextClass>) -> Result<(), String>>,
+    auth_time_required: bool,
     auth_time_verifier_fn: Box<Fn(Option<&DateTime<Utc>>) -> Result<(), String>>,
+    azp_policy: AuthorizedPartyPolicy,
     iat_verifier_fn: Box<Fn(&DateTime<Utc>) -> Result<(), String>>,
     jwt_verifier: JwtClaimsVerifier<JS, JT, JU, K>,
+    leeway: ChronoDuration,
+    max_auth_age: Option<ChronoDuration>,
+    max_issue_age: Option<ChronoDuration>,
+    replay_cache: Option<Arc<ReplayCache>>,
     time_fn: Box<Fn() -> DateTime<Utc>>,
 }
 impl<JS, JT, JU, K> IdTokenVerifier<JS, JT, JU, K>
@@ -495,11 +916,20 @@ where
     fn new(jwt_verifier: JwtClaimsVerifier<JS, JT, JU, K>) -> Self {
         IdTokenVerifier {
             // By default, accept authorization context reference (acr claim).
+            acr_policy: AuthContextPolicy::Ignore,
             acr_verifier_fn: Box::new(|_| Ok(())),
+            auth_time_required: false,
             auth_time_verifier_fn: Box::new(|_| Ok(())),
+            // By default, ignore the authorized party (azp claim); see verified_claims below.
+            azp_policy: AuthorizedPartyPolicy::Ignore,
             // By default, accept any issued time (iat claim).
             iat_verifier_fn: Box::new(|_| Ok(())),
             jwt_verifier,
+            // By default, allow no clock skew.
+            leeway: ChronoDuration::zero(),
+            max_auth_age: None,
+            max_issue_age: None,
+            replay_cache: None,
             // By default, use the current system time.
             time_fn: Box::new(Utc::now),
         }
@@ -537,6 +967,69 @@ where
         self
     }
 
+    ///
+    /// Sets the client's private keys for decrypting encrypted ID tokens (see
+    /// [`IdToken::decrypt`]). Once any keys are set, unencrypted ID tokens are rejected, as
+    /// recommended by
+    /// [Section 3.1.3.7 of OpenID Connect Core 1.0](
+    ///     https://openid.net/specs/openid-connect-core-1_0.html#IDTokenValidation).
+    ///
+    /// Symmetric key management algorithms (`dir` and AES key wrap) require a symmetric key of
+    /// the length expected by the algorithm. If no such key is set and the verifier has a client
+    /// secret, the key is derived from the client secret as described in
+    /// [Section 10.2 of OpenID Connect Core 1.0](
+    ///     https://openid.net/specs/openid-connect-core-1_0.html#Encryption).
+    ///
+    /// RSA-OAEP key management (`RSA-OAEP` and `RSA-OAEP-256`) requires the `rsa-oaep` feature.
+    ///
+    /// # Security Warning
+    ///
+    /// The `rsa-oaep` feature decrypts using the `rsa` crate, whose private key operations are
+    /// not constant-time and are vulnerable to the Marvin timing attack
+    /// ([RUSTSEC-2023-0071](https://rustsec.org/advisories/RUSTSEC-2023-0071)). An attacker able
+    /// to submit many encrypted tokens and measure how long the client takes to reject them may
+    /// be able to recover the private key. Blinding mitigates, but doesn't eliminate, this risk.
+    /// Prefer symmetric key management where possible, and don't use RSA decryption keys for
+    /// any other purpose.
+    ///
+    pub fn set_decryption_keys(mut self, decryption_keys: Vec<K>) -> Self {
+        self.jwt_verifier = self.jwt_verifier.set_decryption_keys(decryption_keys);
+        self
+    }
+
+    ///
+    /// Sets the additional audiences, besides the client ID, that this client trusts. Once set,
+    /// ID tokens listing any other audiences are rejected. By default, any additional audiences
+    /// are accepted.
+    ///
+    pub fn set_trusted_audiences<I>(mut self, audiences: I) -> Self
+    where
+        I: IntoIterator<Item = Audience>,
+    {
+        self.jwt_verifier = self.jwt_verifier.set_trusted_audiences(audiences);
+        self
+    }
+
+    ///
+    /// Sets the policy for verifying the authorized party (`azp`) claim. By default, the claim
+    /// is ignored.
+    ///
+    pub fn set_authorized_party_policy(mut self, azp_policy: AuthorizedPartyPolicy) -> Self {
+        self.azp_policy = azp_policy;
+        self
+    }
+
+    ///
+    /// Sets the policy for verifying the authentication context class reference (`acr`) claim.
+    /// By default, any `acr` claim is accepted. Any function set using
+    /// [`set_auth_context_verifier_fn`](#method.set_auth_context_verifier_fn) is called only if
+    /// the claim satisfies this policy.
+    ///
+    pub fn set_auth_context_policy(mut self, acr_policy: AuthContextPolicy) -> Self {
+        self.acr_policy = acr_policy;
+        self
+    }
+
     pub fn set_auth_context_verifier_fn(
         mut self,
         acr_verifier_fn: Box<Fn(Option<&AuthenticationContextClass>) -> Result<(), String>>,
@@ -545,6 +1038,29 @@ where
         self
     }
 
+    ///
+    /// Requires ID tokens to contain an authentication time (`auth_time`) claim. This should be
+    /// enabled if the client registered with `require_auth_time` or requested the claim
+    /// explicitly.
+    ///
+    pub fn require_auth_time(mut self, auth_time_required: bool) -> Self {
+        self.auth_time_required = auth_time_required;
+        self
+    }
+
+    ///
+    /// Rejects ID tokens whose authentication time (`auth_time`) claim is missing or more than
+    /// `max_auth_age` before the current time, allowing for the configured
+    /// [leeway](#method.set_leeway). This should be set whenever the authentication request
+    /// included the `max_age` parameter, or the client registered a `default_max_age` (see
+    /// [Section 3.1.2.1 of OpenID Connect Core 1.0](
+    ///     https://openid.net/specs/openid-connect-core-1_0.html#AuthRequest)).
+    ///
+    pub fn set_max_auth_age(mut self, max_auth_age: Duration) -> Self {
+        self.max_auth_age = Some(to_chrono_duration(max_auth_age));
+        self
+    }
+
     pub fn set_auth_time_verifier_fn(
         mut self,
         auth_time_verifier_fn: Box<Fn(Option<&DateTime<Utc>>) -> Result<(), String>>,
@@ -567,6 +1083,40 @@ where
         self
     }
 
+    ///
+    /// Sets the allowed clock skew between the client and the provider when verifying the
+    /// expiration (`exp`), not-before (`nbf`), issue time (`iat`), and authentication time
+    /// (`auth_time`) claims. By default, no clock skew is allowed.
+    ///
+    pub fn set_leeway(mut self, leeway: Duration) -> Self {
+        self.leeway = to_chrono_duration(leeway);
+        self
+    }
+
+    ///
+    /// Rejects ID tokens issued more than `max_issue_age` before the current time, or issued
+    /// after the current time, in each case allowing for the configured
+    /// [leeway](#method.set_leeway). By default, ID tokens issued at any time are accepted.
+    ///
+    pub fn set_max_issue_age(mut self, max_issue_age: Duration) -> Self {
+        self.max_issue_age = Some(to_chrono_duration(max_issue_age));
+        self
+    }
+
+    ///
+    /// Sets the cache used to reject ID tokens whose nonce or JWT ID (`jti` claim) was consumed
+    /// by a previously verified ID token. Each value is retained until the ID token it was
+    /// consumed from expires, or exceeds the [maximum issue age](#method.set_max_issue_age) (if
+    /// set), allowing for the configured [leeway](#method.set_leeway).
+    ///
+    /// Nonces and JWT IDs are only consumed once the ID token has passed every other check. By
+    /// default, replayed ID tokens are not detected.
+    ///
+    pub fn set_replay_cache(mut self, replay_cache: Arc<ReplayCache>) -> Self {
+        self.replay_cache = Some(replay_cache);
+        self
+    }
+
     pub fn set_issue_time_verifier_fn(
         mut self,
         iat_verifier_fn: Box<Fn(&DateTime<Utc>) -> Result<(), String>>,
@@ -575,9 +1125,76 @@ where
         self
     }
 
-    // TODO: Add a version that accepts a nonce validation function. Some client applications may
-    // use crypto or some other mechanism to validate nonces instead of storing every nonce.
-    pub(super) fn verified_claims<'b, AC, GC, JE>(
+    pub(super) fn decrypt<JE, JK>(
+        &self,
+        jwe: &JsonWebEncryption,
+    ) -> Result<(JsonWebEncryptionHeader<JE, JK>, Vec<u8>), ClaimsVerificationError>
+    where
+        JE: JweContentEncryptionAlgorithm,
+        JK: JweKeyManagementAlgorithm,
+        K: PrivateDecryptionKey<JE, JK, JS, JT, JU>,
+    {
+        self.jwt_verifier.decrypt(jwe)
+    }
+
+    pub(super) fn is_decryption_enabled(&self) -> bool {
+        self.jwt_verifier.is_decryption_enabled()
+    }
+
+    // `extra_verifier` performs any additional verification (e.g., of the token hashes) before
+    // the ID token's nonce and JWT ID are consumed.
+    pub(super) fn verified_claims<'b, AC, GC, JE, N, V>(
+        &self,
+        jwt: &'b JsonWebToken<
+            IdTokenClaims<AC, GC>,
+            JE,
+            JS,
+            JT,
+            JsonWebTokenJsonPayloadDeserializer,
+        >,
+        nonce_verifier: N,
+        extra_verifier: V,
+    ) -> Result<&'b IdTokenClaims<AC, GC>, ClaimsVerificationError>
+    where
+        AC: AdditionalClaims,
+        GC: GenderClaim,
+        JE: JweContentEncryptionAlgorithm,
+        N: NonceVerifier,
+        V: FnOnce(&IdTokenClaims<AC, GC>) -> Result<(), ClaimsVerificationError>,
+    {
+        self.verify_claims(jwt, nonce_verifier, None, extra_verifier)
+    }
+
+    pub(super) fn verified_refreshed_claims<'b, AC, GC, JE>(
+        &self,
+        jwt: &'b JsonWebToken<
+            IdTokenClaims<AC, GC>,
+            JE,
+            JS,
+            JT,
+            JsonWebTokenJsonPayloadDeserializer,
+        >,
+        original_claims: &IdTokenClaims<AC, GC>,
+    ) -> Result<&'b IdTokenClaims<AC, GC>, ClaimsVerificationError>
+    where
+        AC: AdditionalClaims,
+        GC: GenderClaim,
+        JE: JweContentEncryptionAlgorithm,
+    {
+        // Refreshed ID tokens need not contain a nonce, but any nonce should be the original one.
+        let nonce_verifier = |nonce: Option<&Nonce>| match nonce {
+            Some(nonce) if Some(nonce) != original_claims.nonce() => {
+                Err("nonce does not match the original ID token".to_string())
+            }
+            _ => Ok(()),
+        };
+        self.verify_claims(jwt, nonce_verifier, Some(original_claims), |_| Ok(()))
+    }
+
+    // Verifies the claims of an ID token. If `original_claims` is provided, the ID token was
+    // returned in response to a refresh request and is additionally compared against the
+    // original ID token.
+    fn verify_claims<'b, AC, GC, JE, N, V>(
         &self,
         jwt: &'b JsonWebToken<
             IdTokenClaims<AC, GC>,
@@ -586,12 +1203,16 @@ where
             JT,
             JsonWebTokenJsonPayloadDeserializer,
         >,
-        nonce: Option<&Nonce>,
+        nonce_verifier: N,
+        original_claims: Option<&IdTokenClaims<AC, GC>>,
+        extra_verifier: V,
     ) -> Result<&'b IdTokenClaims<AC, GC>, ClaimsVerificationError>
     where
         AC: AdditionalClaims,
         GC: GenderClaim,
         JE: JweContentEncryptionAlgorithm,
+        N: NonceVerifier,
+        V: FnOnce(&IdTokenClaims<AC, GC>) -> Result<(), ClaimsVerificationError>,
     {
         // The code below roughly follows the validation steps described in
         // https://openid.net/specs/openid-connect-core-1_0.html#IDTokenValidation
@@ -601,58 +1222,88 @@ where
 
         // 4. If the ID Token contains multiple audiences, the Client SHOULD verify that an azp
         //    Claim is present.
+        // 5. If an azp (authorized party) Claim is present, the Client SHOULD verify that its
+        //    client_id is the Claim Value.
 
         // FIXME(docs): add a reference in the module documentation describing this intentional
         // deviation from the spec.
 
         // There is significant confusion and contradiction in the OpenID Connect Core spec around
         // the azp claim. See https://bitbucket.org/openid/connect/issues/973/ for a detailed
-        // discussion. Given the lack of clarity around how this claim should be used, we defer
-        // any verification of it here until a use case becomes apparent. If such a use case does
-        // arise, we most likely want to allow clients to pass in a function for validating the
-        // azp claim rather than introducing logic that affects all clients of this library.
-
-        // This naive implementation of the spec would almost certainly not be useful in practice:
-        /*
-        let azp_required = partially_verified_claims.audiences().len() > 1;
-
-        // 5. If an azp (authorized party) Claim is present, the Client SHOULD verify that its
-        //    client_id is the Claim Value.
-        if let Some(authorized_party) = partially_verified_claims.authorized_party() {
-            if *authorized_party != self.client_id {
-                return Err(ClaimsVerificationError::InvalidAudience(format!(
-                    "authorized party must match client ID `{}` (found `{}`",
-                    *self.client_id, **authorized_party
-                )));
+        // discussion. Given the lack of clarity around how this claim should be used, we ignore it
+        // by default and let clients opt into the policy appropriate for their deployment.
+        let authorized_party = partially_verified_claims.authorized_party();
+        let audiences = partially_verified_claims.audiences();
+        match self.azp_policy {
+            AuthorizedPartyPolicy::Ignore => {}
+            AuthorizedPartyPolicy::RequireForMultipleAudiences => {
+                if let Some(authorized_party) = authorized_party {
+                    self.verify_authorized_party(authorized_party)?;
+                } else if audiences.len() > 1 {
+                    return Err(ClaimsVerificationError::InvalidAudience(
+                        "missing authorized party claim but multiple audiences found".to_string(),
+                    ));
+                }
+            }
+            AuthorizedPartyPolicy::RequireClientId => {
+                if let Some(authorized_party) = authorized_party {
+                    self.verify_authorized_party(authorized_party)?;
+                } else {
+                    return Err(ClaimsVerificationError::InvalidAudience(
+                        "missing authorized party claim".to_string(),
+                    ));
+                }
+            }
+            AuthorizedPartyPolicy::Custom(ref azp_verifier_fn) => {
+                azp_verifier_fn(authorized_party, audiences)
+                    .map_err(ClaimsVerificationError::InvalidAudience)?;
             }
-        } else if azp_required {
-            return Err(ClaimsVerificationError::InvalidAudience(format!(
-                "missing authorized party claim but multiple audiences found"
-            )));
         }
-        */
 
         // Steps 6--8 are handled by the generic JwtClaimsVerifier.
 
         // 9. The current time MUST be before the time represented by the exp Claim.
+        //
+        // Additionally, the current time must not be before the time represented by the nbf
+        // Claim, if present (see Section 4.1.5 of RFC 7519).
         let cur_time = (*self.time_fn)();
-        if let Ok(expiration) = partially_verified_claims.expiration() {
-            if cur_time > expiration {
-                return Err(ClaimsVerificationError::Expired(format!(
-                    "ID token expired at {} (current time is {})",
-                    expiration, cur_time
-                )));
-            }
-        } else {
-            return Err(ClaimsVerificationError::Other(
-                "expiration out of bounds".to_string(),
-            ));
-        }
+        let expiration = partially_verified_claims
+            .expiration()
+            .map_err(|_| ClaimsVerificationError::Other("expiration out of bounds".to_string()))?;
+        let not_before = partially_verified_claims
+            .not_before()
+            .map_or(Ok(None), |nbf| nbf.map(Some))
+            .map_err(|_| {
+                ClaimsVerificationError::Other("not before time out of bounds".to_string())
+            })?;
+        verify_validity_period(
+            "ID token",
+            cur_time,
+            self.leeway,
+            Some(expiration),
+            not_before,
+        )?;
 
         // 10. The iat Claim can be used to reject tokens that were issued too far away from the
         //     current time, limiting the amount of time that nonces need to be stored to prevent
         //     attacks. The acceptable range is Client specific.
         if let Ok(ref issue_time) = partially_verified_claims.issue_time() {
+            if let Some(max_issue_age) = self.max_issue_age {
+                let max_issue_age_with_leeway = max_issue_age
+                    .checked_add(&self.leeway)
+                    .unwrap_or_else(ChronoDuration::max_value);
+                if is_after(&cur_time, issue_time, max_issue_age_with_leeway) {
+                    return Err(ClaimsVerificationError::Expired(format!(
+                        "ID token issued too long ago at {} (current time is {})",
+                        issue_time, cur_time
+                    )));
+                } else if is_after(issue_time, &cur_time, self.leeway) {
+                    return Err(ClaimsVerificationError::Expired(format!(
+                        "ID token issued in the future at {} (current time is {})",
+                        issue_time, cur_time
+                    )));
+                }
+            }
             (*self.iat_verifier_fn)(issue_time).map_err(ClaimsVerificationError::Expired)?;
         } else {
             return Err(ClaimsVerificationError::Other(
@@ -664,41 +1315,233 @@ where
         //     present and its value checked to verify that it is the same value as the one that was
         //     sent in the Authentication Request. The Client SHOULD check the nonce value for
         //     replay attacks. The precise method for detecting replay attacks is Client specific.
-        if let Some(expected_nonce) = nonce {
-            if let Some(claims_nonce) = partially_verified_claims.nonce() {
-                if claims_nonce != expected_nonce {
-                    return Err(ClaimsVerificationError::InvalidNonce(
-                        "nonce mismatch".to_string(),
-                    ));
-                }
-            } else {
-                return Err(ClaimsVerificationError::InvalidNonce(
-                    "missing nonce claim".to_string(),
-                ));
-            }
-        }
+        nonce_verifier
+            .verify(partially_verified_claims.nonce())
+            .map_err(ClaimsVerificationError::InvalidNonce)?;
 
         // 12. If the acr Claim was requested, the Client SHOULD check that the asserted Claim Value
         //     is appropriate. The meaning and processing of acr Claim Values is out of scope for
         //     this specification.
-        (*self.acr_verifier_fn)(partially_verified_claims.auth_context_ref())
+        let auth_context_ref = partially_verified_claims.auth_context_ref();
+        self.acr_policy
+            .verify(auth_context_ref)
             .map_err(ClaimsVerificationError::InvalidAuthContext)?;
+        (*self.acr_verifier_fn)(auth_context_ref).map_err(|err| {
+            ClaimsVerificationError::InvalidAuthContext(AuthContextError {
+                message: err,
+                requested: Vec::new(),
+                received: auth_context_ref.cloned(),
+            })
+        })?;
 
         // 13. If the auth_time Claim was requested, either through a specific request for this
         //     Claim or by using the max_age parameter, the Client SHOULD check the auth_time Claim
         //     value and request re-authentication if it determines too much time has elapsed since
         //     the last End-User authentication.
+        //
+        // Regardless of whether the auth_time Claim was requested, the End-User cannot have
+        // authenticated after the current time (allowing for clock skew).
         match partially_verified_claims.auth_time() {
-            Some(ref auth_time_result) => auth_time_result
-                .map(|auth_time| (*self.auth_time_verifier_fn)(Some(&auth_time)))
-                .map_err(|_| ClaimsVerificationError::Other("auth time out of bounds".to_string()))?
-                .map_err(ClaimsVerificationError::InvalidAuthTime)?,
-            None => (*self.auth_time_verifier_fn)(None)
-                .map_err(ClaimsVerificationError::InvalidAuthTime)?,
+            Some(ref auth_time_result) => {
+                let auth_time = auth_time_result.map_err(|_| {
+                    ClaimsVerificationError::Other("auth time out of bounds".to_string())
+                })?;
+                if is_after(&auth_time, &cur_time, self.leeway) {
+                    return Err(ClaimsVerificationError::InvalidAuthTime(format!(
+                        "authentication time {} is in the future (current time is {})",
+                        auth_time, cur_time
+                    )));
+                }
+                // Refreshed ID tokens carry the time of the original authentication, which was
+                // already checked against the maximum authentication age.
+                if let (Some(max_auth_age), None) = (self.max_auth_age, original_claims) {
+                    let max_auth_age_with_leeway = max_auth_age
+                        .checked_add(&self.leeway)
+                        .unwrap_or_else(ChronoDuration::max_value);
+                    if is_after(&cur_time, &auth_time, max_auth_age_with_leeway) {
+                        return Err(ClaimsVerificationError::InvalidAuthTime(format!(
+                            "authentication time {} exceeds the maximum authentication age of {} \
+                             seconds (current time is {})",
+                            auth_time,
+                            max_auth_age.num_seconds(),
+                            cur_time
+                        )));
+                    }
+                }
+                (*self.auth_time_verifier_fn)(Some(&auth_time))
+                    .map_err(ClaimsVerificationError::InvalidAuthTime)?
+            }
+            None => {
+                // The maximum authentication age only applies to the original authentication, so
+                // refreshed ID tokens may omit the claim unless it's explicitly required.
+                if self.auth_time_required
+                    || (self.max_auth_age.is_some() && original_claims.is_none())
+                {
+                    return Err(ClaimsVerificationError::InvalidAuthTime(
+                        "missing authentication time claim".to_string(),
+                    ));
+                }
+                (*self.auth_time_verifier_fn)(None)
+                    .map_err(ClaimsVerificationError::InvalidAuthTime)?
+            }
         };
 
+        if let Some(original_claims) = original_claims {
+            Self::verify_refreshed_claims(partially_verified_claims, original_claims)?;
+        }
+
+        extra_verifier(partially_verified_claims)?;
+
+        if let Some(ref replay_cache) = self.replay_cache {
+            self.consume_replay_keys(
+                &**replay_cache,
+                partially_verified_claims,
+                expiration,
+                cur_time,
+                // The nonce of a refreshed ID token was consumed by the original ID token.
+                original_claims.is_none(),
+            )?;
+        }
+
         Ok(partially_verified_claims)
     }
+
+    // Compares a refreshed ID token against the original ID token, as described in
+    // https://openid.net/specs/openid-connect-core-1_0.html#RefreshTokenResponse
+    fn verify_refreshed_claims<AC, GC>(
+        claims: &IdTokenClaims<AC, GC>,
+        original_claims: &IdTokenClaims<AC, GC>,
+    ) -> Result<(), ClaimsVerificationError>
+    where
+        AC: AdditionalClaims,
+        GC: GenderClaim,
+    {
+        // Its iss Claim Value MUST be the same as in the ID Token issued when the original
+        // authentication occurred.
+        if claims.issuer() != original_claims.issuer() {
+            return Err(ClaimsVerificationError::InvalidIssuer(format!(
+                "expected original issuer `{}` (found `{}`)",
+                **original_claims.issuer(),
+                **claims.issuer()
+            )));
+        }
+
+        // Its sub Claim Value MUST be the same as in the ID Token issued when the original
+        // authentication occurred.
+        if claims.sub() != original_claims.sub() {
+            return Err(ClaimsVerificationError::InvalidSubject(format!(
+                "expected original subject `{}` (found `{}`)",
+                **original_claims.sub(),
+                **claims.sub()
+            )));
+        }
+
+        // Its aud Claim Value MUST be the same as in the ID Token issued when the original
+        // authentication occurred.
+        let audiences = claims.audiences().iter().collect::<HashSet<_>>();
+        let original_audiences = original_claims.audiences().iter().collect::<HashSet<_>>();
+        if audiences != original_audiences {
+            return Err(ClaimsVerificationError::InvalidAudience(format!(
+                "expected original audiences {} (found audiences: {})",
+                original_claims
+                    .audiences()
+                    .iter()
+                    .map(|aud| format!("`{}`", Deref::deref(aud)))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                claims
+                    .audiences()
+                    .iter()
+                    .map(|aud| format!("`{}`", Deref::deref(aud)))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )));
+        }
+
+        // If the ID Token contains an auth_time Claim, its value MUST represent the time of the
+        // original authentication - not the time that the new ID token is issued.
+        if claims.auth_time().is_some() && claims.auth_time() != original_claims.auth_time() {
+            return Err(ClaimsVerificationError::InvalidAuthTime(
+                "authentication time differs from the original ID token".to_string(),
+            ));
+        }
+
+        // Its azp Claim Value MUST be the same as in the ID Token issued when the original
+        // authentication occurred; if no azp Claim was present in the original ID Token, one
+        // MUST NOT be present in the new ID Token.
+        if claims.authorized_party() != original_claims.authorized_party() {
+            return Err(ClaimsVerificationError::InvalidAudience(
+                "authorized party differs from the original ID token".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn consume_replay_keys<AC, GC>(
+        &self,
+        replay_cache: &ReplayCache,
+        claims: &IdTokenClaims<AC, GC>,
+        expiration: DateTime<Utc>,
+        cur_time: DateTime<Utc>,
+        consume_nonce: bool,
+    ) -> Result<(), ClaimsVerificationError>
+    where
+        AC: AdditionalClaims,
+        GC: GenderClaim,
+    {
+        // The keys only need to be retained for as long as the ID token would otherwise pass
+        // verification.
+        let latest_time = |time: DateTime<Utc>, max_age: ChronoDuration| {
+            time.checked_add_signed(max_age)
+                .and_then(|time| time.checked_add_signed(self.leeway))
+                .unwrap_or(time)
+        };
+        let mut cache_expiration = latest_time(expiration, ChronoDuration::zero());
+        if let (Some(max_issue_age), Ok(issue_time)) = (self.max_issue_age, claims.issue_time()) {
+            cache_expiration = cache_expiration.min(latest_time(issue_time, max_issue_age));
+        }
+
+        let replay_keys = claims
+            .nonce()
+            .filter(|_| consume_nonce)
+            .map(|nonce| ("nonce", format!("nonce:{}", nonce.secret())))
+            .into_iter()
+            .chain(
+                claims
+                    .jwt_id()
+                    // JWT IDs are only unique per issuer, and the cache may be shared by
+                    // verifiers for different issuers.
+                    .map(|jwt_id| ("JWT ID", format!("jti:{}:{}", **claims.issuer(), **jwt_id))),
+            );
+        for (description, key) in replay_keys {
+            let is_new_key = replay_cache
+                .consume(&key, cache_expiration, cur_time)
+                .map_err(|err| {
+                    ClaimsVerificationError::Other(format!("failed to query replay cache: {}", err))
+                })?;
+            if !is_new_key {
+                return Err(ClaimsVerificationError::Replayed(format!(
+                    "{} has already been used",
+                    description
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn verify_authorized_party(
+        &self,
+        authorized_party: &ClientId,
+    ) -> Result<(), ClaimsVerificationError> {
+        if *authorized_party != self.jwt_verifier.client_id {
+            return Err(ClaimsVerificationError::InvalidAudience(format!(
+                "authorized party must match client ID `{}` (found `{}`)",
+                *self.jwt_verifier.client_id, **authorized_party
+            )));
+        }
+        Ok(())
+    }
 }
 
 ///
@@ -714,7 +1557,9 @@ where
 {
     jwt_required: bool,
     jwt_verifier: JwtClaimsVerifier<JS, JT, JU, K>,
+    leeway: ChronoDuration,
     sub: SubjectIdentifier,
+    time_fn: Box<Fn() -> DateTime<Utc>>,
     _phantom: PhantomData<JE>,
 }
 impl<JE, JS, JT, JU, K> UserInfoVerifier<JE, JS, JT, JU, K>
@@ -734,7 +1579,11 @@ where
         UserInfoVerifier {
             jwt_required: false,
             jwt_verifier: JwtClaimsVerifier::new(client_id, issuer, signature_keys),
+            // By default, allow no clock skew.
+            leeway: ChronoDuration::zero(),
             sub,
+            // By default, use the current system time.
+            time_fn: Box::new(Utc::now),
             _phantom: PhantomData,
         }
     }
@@ -754,15 +1603,59 @@ where
         self
     }
 
-    pub(super) fn verified_claims<AC, GC>(
+    pub fn set_time_fn(mut self, time_fn: Box<Fn() -> DateTime<Utc>>) -> Self {
+        self.time_fn = time_fn;
+        self
+    }
+
+    ///
+    /// Sets the allowed clock skew between the client and the provider when verifying the
+    /// expiration (`exp`) and not-before (`nbf`) claims of UserInfo responses, which are only
+    /// verified if present. By default, no clock skew is allowed.
+    ///
+    pub fn set_leeway(mut self, leeway: Duration) -> Self {
+        self.leeway = to_chrono_duration(leeway);
+        self
+    }
+
+    ///
+    /// Sets the client's private keys for decrypting encrypted UserInfo responses. Once any keys
+    /// are set, unencrypted responses are rejected, since the client has registered a
+    /// `userinfo_encrypted_response_alg` (see
+    /// [Section 5.3.2 of OpenID Connect Core 1.0](
+    ///     https://openid.net/specs/openid-connect-core-1_0.html#UserInfoResponse)).
+    ///
+    /// Encrypted responses containing a nested signed JWT are verified as usual; those
+    /// containing plain JSON claims are rejected if
+    /// [`require_signed_response`](#method.require_signed_response) is enabled.
+    ///
+    /// # Security Warning
+    ///
+    /// RSA-OAEP decryption (the `rsa-oaep` feature) is vulnerable to a timing attack; see
+    /// [`IdTokenVerifier::set_decryption_keys`](
+    ///     struct.IdTokenVerifier.html#method.set_decryption_keys).
+    ///
+    pub fn set_decryption_keys(mut self, decryption_keys: Vec<K>) -> Self {
+        self.jwt_verifier = self.jwt_verifier.set_decryption_keys(decryption_keys);
+        self
+    }
+
+    pub(super) fn verified_claims<AC, GC, JK>(
         &self,
         unverified_user_info: UnverifiedUserInfoClaims<AC, GC, JE, JS, JT>,
     ) -> Result<UserInfoClaims<AC, GC>, ClaimsVerificationError>
     where
         AC: AdditionalClaims,
         GC: GenderClaim,
+        JK: JweKeyManagementAlgorithm,
+        K: PrivateDecryptionKey<JE, JK, JS, JT, JU>,
     {
         let user_info = match unverified_user_info {
+            UnverifiedUserInfoClaims::JsonClaims(_) | UnverifiedUserInfoClaims::JwtClaims(_)
+                if self.jwt_verifier.is_decryption_enabled() =>
+            {
+                return Err(ClaimsVerificationError::NoEncryption);
+            }
             UnverifiedUserInfoClaims::JsonClaims(user_info) => {
                 if self.jwt_required {
                     return Err(ClaimsVerificationError::NoSignature);
@@ -772,6 +1665,49 @@ where
             UnverifiedUserInfoClaims::JwtClaims(user_info_jwt) => {
                 self.jwt_verifier.verified_claims(user_info_jwt)?
             }
+            UnverifiedUserInfoClaims::JweClaims(user_info_jwe) => {
+                let (jose_header, plaintext) =
+                    self.jwt_verifier.decrypt::<JE, JK>(&user_info_jwe)?;
+                let is_nested_jwt = match jose_header.cty {
+                    Some(ref content_type) => content_type.to_uppercase() == "JWT",
+                    // Providers should set the content type of nested JWTs, but in case they
+                    // don't, a signed JWT is easily distinguished from a JSON object.
+                    None => plaintext.first() != Some(&b'{'),
+                };
+
+                if is_nested_jwt {
+                    let jwt_str = String::from_utf8(plaintext).map_err(|_| {
+                        ClaimsVerificationError::Other(
+                            "decrypted UserInfo JWT is not valid UTF-8".to_string(),
+                        )
+                    })?;
+                    let user_info_jwt: JsonWebToken<
+                        _,
+                        JE,
+                        JS,
+                        JT,
+                        JsonWebTokenJsonPayloadDeserializer,
+                    > = serde_json::from_value(serde_json::Value::String(jwt_str)).map_err(
+                        |err| {
+                            ClaimsVerificationError::Other(format!(
+                                "failed to parse decrypted UserInfo JWT: {}",
+                                err
+                            ))
+                        },
+                    )?;
+                    self.jwt_verifier.verified_claims(user_info_jwt)?
+                } else {
+                    if self.jwt_required {
+                        return Err(ClaimsVerificationError::NoSignature);
+                    }
+                    serde_json::from_slice(&plaintext).map_err(|err| {
+                        ClaimsVerificationError::Other(format!(
+                            "failed to parse decrypted UserInfo claims: {}",
+                            err
+                        ))
+                    })?
+                }
+            }
         };
 
         if *user_info.sub() != self.sub {
@@ -782,10 +1718,70 @@ where
             )));
         }
 
+        let out_of_bounds = |claim_name: &str| {
+            ClaimsVerificationError::Other(format!("{} out of bounds", claim_name))
+        };
+        let expiration = user_info
+            .expiration()
+            .map_or(Ok(None), |exp| exp.map(Some))
+            .map_err(|_| out_of_bounds("expiration"))?;
+        let not_before = user_info
+            .not_before()
+            .map_or(Ok(None), |nbf| nbf.map(Some))
+            .map_err(|_| out_of_bounds("not before time"))?;
+        verify_validity_period(
+            "UserInfo response",
+            (*self.time_fn)(),
+            self.leeway,
+            expiration,
+            not_before,
+        )?;
+
         Ok(user_info)
     }
 }
 
+// Converts a std::time::Duration to a chrono::Duration, saturating if it's out of range.
+fn to_chrono_duration(duration: Duration) -> ChronoDuration {
+    ChronoDuration::from_std(duration).unwrap_or_else(|_| ChronoDuration::max_value())
+}
+
+// Returns whether `time` is more than `leeway` after `reference_time`.
+fn is_after(time: &DateTime<Utc>, reference_time: &DateTime<Utc>, leeway: ChronoDuration) -> bool {
+    // If adding the leeway overflows, no representable time is after it.
+    reference_time
+        .checked_add_signed(leeway)
+        .map_or(false, |latest_time| *time > latest_time)
+}
+
+// Verifies that the current time falls within the validity period defined by the exp and nbf
+// claims (if present), allowing for `leeway` of clock skew in either direction.
+fn verify_validity_period(
+    token_description: &str,
+    cur_time: DateTime<Utc>,
+    leeway: ChronoDuration,
+    expiration: Option<DateTime<Utc>>,
+    not_before: Option<DateTime<Utc>>,
+) -> Result<(), ClaimsVerificationError> {
+    if let Some(expiration) = expiration {
+        if is_after(&cur_time, &expiration, leeway) {
+            return Err(ClaimsVerificationError::Expired(format!(
+                "{} expired at {} (current time is {})",
+                token_description, expiration, cur_time
+            )));
+        }
+    }
+    if let Some(not_before) = not_before {
+        if is_after(&not_before, &cur_time, leeway) {
+            return Err(ClaimsVerificationError::Expired(format!(
+                "{} not valid before {} (current time is {})",
+                token_description, not_before, cur_time
+            )));
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use oauth2::prelude::*;
@@ -1039,6 +2035,45 @@ mod tests {
             )).expect("failed to deserialize"),
         ).expect("verification should succeed");
 
+        // Multiple audiences, all of which are trusted
+        verifier
+            .clone()
+            .set_trusted_audiences(vec![
+                Audience::new("aud1".to_string()),
+                Audience::new("aud2".to_string()),
+            ]).verified_claims(
+                serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
+                    "eyJhbGciOiJSUzI1NiJ9.eyJhdWQiOlsiYXVkMSIsIm15X2NsaWVudCIsImF1ZDIiXSwiaXNzIjoia\
+                     HR0cHM6Ly9leGFtcGxlLmNvbSIsInBheWxvYWQiOiJoZWxsbyB3b3JsZCJ9.N9ibisEe0kKLe1GDWM\
+                     ON3PmYqbL73dag-loM8pjKJNinF9SB7n4JuSu4FrNkeW4F1Cz8MIbLuWfKvDa_4v_3FstMA3GODZWH\
+                     BVIiuNFay2ovCfGFyykwe47dF_47g_OM5AkJc_teE5MN8lPh9V5zYCy3ON3zZ3acFPJMOPTdbU56xD\
+                     eFe7lil6DmV4JU9A52t5ZkJILFaIuxxXJUIDmqpPTvHkggh_QOj9C2US9bgg5b543JwT4j-HbDp51L\
+                     dDB4k3azOssT1ddtoAuuDOctnraMKUtqffJXexxfwA1uM6EIofSrK5v11xwgTciL9xDXAvav_G2buP\
+                     ol1bjGLa2t0Q"
+                        .to_string(),
+                )).expect("failed to deserialize"),
+            ).expect("verification should succeed");
+
+        // Multiple audiences, where one is untrusted
+        match verifier
+            .clone()
+            .set_trusted_audiences(vec![Audience::new("aud1".to_string())])
+            .verified_claims(
+                serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
+                    "eyJhbGciOiJSUzI1NiJ9.eyJhdWQiOlsiYXVkMSIsIm15X2NsaWVudCIsImF1ZDIiXSwiaXNzIjoia\
+                     HR0cHM6Ly9leGFtcGxlLmNvbSIsInBheWxvYWQiOiJoZWxsbyB3b3JsZCJ9.N9ibisEe0kKLe1GDWM\
+                     ON3PmYqbL73dag-loM8pjKJNinF9SB7n4JuSu4FrNkeW4F1Cz8MIbLuWfKvDa_4v_3FstMA3GODZWH\
+                     BVIiuNFay2ovCfGFyykwe47dF_47g_OM5AkJc_teE5MN8lPh9V5zYCy3ON3zZ3acFPJMOPTdbU56xD\
+                     eFe7lil6DmV4JU9A52t5ZkJILFaIuxxXJUIDmqpPTvHkggh_QOj9C2US9bgg5b543JwT4j-HbDp51L\
+                     dDB4k3azOssT1ddtoAuuDOctnraMKUtqffJXexxfwA1uM6EIofSrK5v11xwgTciL9xDXAvav_G2buP\
+                     ol1bjGLa2t0Q"
+                        .to_string(),
+                )).expect("failed to deserialize"),
+            ) {
+            Err(ClaimsVerificationError::InvalidAudience(ref msg)) if msg.contains("aud2") => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
         // Multiple audiences, where none is a match
         match verifier.verified_claims(
             serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
@@ -1217,6 +2252,15 @@ mod tests {
                 kid: Some(kid.clone()),
                 n: None,
                 e: None,
+                crv: None,
+                x: None,
+                y: None,
+                d: None,
+                p: None,
+                q: None,
+                dp: None,
+                dq: None,
+                qi: None,
                 k: Some(Base64UrlEncodedBytes::new(vec![1, 2, 3, 4])),
             }]),
         ).verified_claims(valid_rs256_jwt.clone())
@@ -1237,6 +2281,15 @@ mod tests {
                 kid: Some(kid.clone()),
                 n: Some(n.clone()),
                 e: Some(e.clone()),
+                crv: None,
+                x: None,
+                y: None,
+                d: None,
+                p: None,
+                q: None,
+                dp: None,
+                dq: None,
+                qi: None,
                 k: None,
             }]),
         ).verified_claims(valid_rs256_jwt.clone())
@@ -1,3 +1,4 @@
+extern crate futures;
 extern crate oauth2;
 extern crate openidconnect;
 #[macro_use]
@@ -5,6 +6,11 @@ extern crate pretty_assertions;
 extern crate serde_json;
 extern crate url;
 
+use std::cell::RefCell;
+use std::io;
+
+use futures::future::{self, FutureResult};
+use futures::Future;
 use oauth2::prelude::*;
 use oauth2::{AuthUrl, Scope, TokenUrl};
 use url::Url;
@@ -14,10 +20,13 @@ use openidconnect::core::{
     CoreJweContentEncryptionAlgorithm, CoreJweKeyManagementAlgorithm, CoreJwsSigningAlgorithm,
     CoreProviderMetadata, CoreResponseMode, CoreResponseType, CoreSubjectIdentifierType,
 };
-use openidconnect::discovery::{JsonWebKeySetUrl, ProviderMetadata};
+use openidconnect::discovery::{
+    get_provider_metadata, get_provider_metadata_async, JsonWebKeySetUrl, ProviderMetadata,
+};
 use openidconnect::{
-    AuthenticationContextClass, IssuerUrl, LanguageTag, OpPolicyUrl, OpTosUrl, RegistrationUrl,
-    ResponseTypes, ServiceDocUrl, UserInfoUrl,
+    AsyncHttpClient, AuthenticationContextClass, DiscoveryError, HttpClient, HttpRequest,
+    HttpRequestMethod, HttpResponse, IssuerUrl, LanguageTag, OpPolicyUrl, OpTosUrl,
+    PkceCodeChallengeMethod, RegistrationUrl, ResponseTypes, ServiceDocUrl, UserInfoUrl,
 };
 
 #[test]
@@ -538,6 +547,7 @@ fn test_discovery_deserialization() {
     );
     assert_eq!(None, provider_metadata.op_policy_uri());
     assert_eq!(None, provider_metadata.op_tos_uri());
+    assert_eq!(None, provider_metadata.code_challenge_methods_supported());
 
     // Note: the following fields provided by the response above are not part of the OpenID
     // Connect Discovery 1.0 spec:
@@ -595,7 +605,11 @@ fn test_discovery_deserialization_other_fields() {
            \"yue-HK\"
         ],
         \"op_policy_uri\" : \"https://rp.certification.openid.net:8080/openidconnect-rs/rp-response_type-code/op_policy\",
-        \"op_tos_uri\" : \"https://rp.certification.openid.net:8080/openidconnect-rs/rp-response_type-code/op_tos\"
+        \"op_tos_uri\" : \"https://rp.certification.openid.net:8080/openidconnect-rs/rp-response_type-code/op_tos\",
+        \"code_challenge_methods_supported\" : [
+           \"S256\",
+           \"plain\"
+        ]
     }";
 
     let provider_metadata: CoreProviderMetadata = serde_json::from_str(json_response).unwrap();
@@ -763,6 +777,13 @@ fn test_discovery_deserialization_other_fields() {
         ),
         provider_metadata.op_tos_uri()
     );
+    assert_eq!(
+        Some(&vec![
+            PkceCodeChallengeMethod::new("S256".to_string()),
+            PkceCodeChallengeMethod::new("plain".to_string()),
+        ]),
+        provider_metadata.code_challenge_methods_supported()
+    );
 
     let serialized_json = serde_json::to_string(&provider_metadata).unwrap();
 
@@ -770,3 +791,136 @@ fn test_discovery_deserialization_other_fields() {
         serde_json::from_str(&serialized_json).unwrap();
     assert_eq!(provider_metadata, redeserialized_metadata);
 }
+
+struct MockHttpClient {
+    response: HttpResponse,
+    requests: RefCell<Vec<HttpRequest>>,
+}
+impl MockHttpClient {
+    fn new(status_code: u32, content_type: Option<&str>, body: &str) -> Self {
+        MockHttpClient {
+            response: HttpResponse {
+                status_code,
+                content_type: content_type.map(str::to_string),
+                body: body.as_bytes().to_vec(),
+            },
+            requests: RefCell::new(Vec::new()),
+        }
+    }
+}
+impl HttpClient for MockHttpClient {
+    type Error = io::Error;
+
+    fn request(&self, request: HttpRequest) -> Result<HttpResponse, io::Error> {
+        self.requests.borrow_mut().push(request);
+        Ok(self.response.clone())
+    }
+}
+impl AsyncHttpClient for MockHttpClient {
+    type Error = io::Error;
+    type Future = FutureResult<HttpResponse, io::Error>;
+
+    fn request(&self, request: HttpRequest) -> Self::Future {
+        future::result(HttpClient::request(self, request))
+    }
+}
+
+const MINIMAL_PROVIDER_METADATA: &str = "{
+    \"issuer\": \"https://example.com\",
+    \"authorization_endpoint\": \"https://example.com/authorize\",
+    \"jwks_uri\": \"https://example.com/jwks.json\",
+    \"response_types_supported\": [\"code\"],
+    \"subject_types_supported\": [\"public\"],
+    \"id_token_signing_alg_values_supported\": [\"RS256\"]
+}";
+
+#[test]
+fn test_get_provider_metadata_custom_http_client() {
+    let issuer_url = IssuerUrl::new("https://example.com".to_string()).unwrap();
+    let http_client = MockHttpClient::new(
+        200,
+        Some("application/json; charset=UTF-8"),
+        MINIMAL_PROVIDER_METADATA,
+    );
+
+    let provider_metadata: CoreProviderMetadata =
+        get_provider_metadata(&issuer_url, &http_client).unwrap();
+    assert_eq!(&issuer_url, provider_metadata.issuer());
+    assert_eq!(
+        Some(&JsonWebKeySetUrl::new("https://example.com/jwks.json".to_string()).unwrap()),
+        provider_metadata.jwks_uri()
+    );
+
+    let requests = http_client.requests.borrow();
+    assert_eq!(1, requests.len());
+    assert_eq!(
+        "https://example.com/.well-known/openid-configuration",
+        requests[0].url.as_str()
+    );
+    assert_eq!(HttpRequestMethod::Get, requests[0].method);
+    assert_eq!(
+        vec![("Accept".to_string(), "application/json".to_string())],
+        requests[0].headers
+    );
+}
+
+#[test]
+fn test_get_provider_metadata_custom_http_client_errors() {
+    let issuer_url = IssuerUrl::new("https://example.com".to_string()).unwrap();
+
+    let result: Result<CoreProviderMetadata, _> = get_provider_metadata(
+        &issuer_url,
+        &MockHttpClient::new(404, Some("text/html"), "Not Found"),
+    );
+    match result {
+        Err(DiscoveryError::Response(404, _)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let result: Result<CoreProviderMetadata, _> = get_provider_metadata(
+        &issuer_url,
+        &MockHttpClient::new(200, Some("text/html"), MINIMAL_PROVIDER_METADATA),
+    );
+    match result {
+        Err(DiscoveryError::Response(200, _)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let other_issuer_url = IssuerUrl::new("https://example.org".to_string()).unwrap();
+    let result: Result<CoreProviderMetadata, _> = get_provider_metadata(
+        &other_issuer_url,
+        &MockHttpClient::new(200, Some("application/json"), MINIMAL_PROVIDER_METADATA),
+    );
+    match result {
+        Err(DiscoveryError::Validation(_)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_get_provider_metadata_async() {
+    let issuer_url = IssuerUrl::new("https://example.com".to_string()).unwrap();
+    let http_client = MockHttpClient::new(200, Some("application/json"), MINIMAL_PROVIDER_METADATA);
+
+    let provider_metadata: CoreProviderMetadata =
+        get_provider_metadata_async(&issuer_url, &http_client)
+            .wait()
+            .unwrap();
+    let sync_provider_metadata: CoreProviderMetadata =
+        get_provider_metadata(&issuer_url, &http_client).unwrap();
+    assert_eq!(sync_provider_metadata, provider_metadata);
+
+    {
+        let requests = http_client.requests.borrow();
+        assert_eq!(2, requests.len());
+        assert_eq!(requests[0], requests[1]);
+    }
+
+    let other_issuer_url = IssuerUrl::new("https://example.org".to_string()).unwrap();
+    let result: Result<CoreProviderMetadata, _> =
+        get_provider_metadata_async(&other_issuer_url, &http_client).wait();
+    match result {
+        Err(DiscoveryError::Validation(_)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
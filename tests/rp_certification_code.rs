@@ -13,7 +13,7 @@ use std::collections::HashMap;
 
 use curl::easy::Easy;
 use oauth2::prelude::*;
-use oauth2::{AccessToken, AuthType, AuthorizationCode, CsrfToken, RequestTokenError, Scope};
+use oauth2::{AccessToken, AuthType, AuthorizationCode, CsrfToken, Scope};
 use url::Url;
 
 use openidconnect::core::{
@@ -28,8 +28,8 @@ use openidconnect::registration::{
 };
 use openidconnect::Nonce;
 use openidconnect::{
-    AuthenticationFlow, ClaimsVerificationError, SignatureVerificationError, StandardClaims,
-    SubjectIdentifier, UserInfoError,
+    AuthenticationFlow, ClaimsVerificationError, CurlHttpClient, RequestTokenError,
+    SignatureVerificationError, StandardClaims, SubjectIdentifier, UserInfoError,
 };
 
 #[macro_use]
@@ -134,6 +134,7 @@ impl TestState {
                 self.authorization_code
                     .take()
                     .expect("no authorization_code"),
+                &CurlHttpClient,
             ).panic_if_fail("failed to exchange authorization code for token");
         log_debug!(
             "Authorization Server returned token response: {:?}",
@@ -182,7 +183,7 @@ impl TestState {
         self.provider_metadata
             .jwks_uri()
             .unwrap()
-            .get_keys()
+            .get_keys(&CurlHttpClient)
             .panic_if_fail("failed to fetch JWK set")
     }
 
@@ -209,17 +210,17 @@ impl TestState {
         self.provider_metadata
             .userinfo_endpoint()
             .unwrap()
-            .get_user_info(self.access_token(), &verifier)
+            .get_user_info(self.access_token(), &verifier, &CurlHttpClient)
             .panic_if_fail("failed to get UserInfo")
     }
 
-    pub fn user_info_claims_failure(&self) -> UserInfoError {
+    pub fn user_info_claims_failure(&self) -> UserInfoError<curl::Error> {
         let verifier = self.user_info_verifier(self.jwks(), self.id_token_claims().sub().clone());
-        let user_info_result: Result<CoreUserInfoClaims, UserInfoError> = self
+        let user_info_result: Result<CoreUserInfoClaims, UserInfoError<curl::Error>> = self
             .provider_metadata
             .userinfo_endpoint()
             .unwrap()
-            .get_user_info(self.access_token(), &verifier);
+            .get_user_info(self.access_token(), &verifier, &CurlHttpClient);
         match user_info_result {
             Err(err) => err,
             _ => panic!("claims verification succeeded but was expected to fail"),
@@ -345,10 +346,11 @@ fn rp_id_token_iat() {
             .authorization_code
             .take()
             .expect("no authorization_code"),
+        &CurlHttpClient,
     );
 
     match token_response {
-        Err(RequestTokenError::Parse(_)) => {
+        Err(RequestTokenError::Parse(_, _)) => {
             log_error!("ID token failed to parse without `iat` claim (expected result)")
         }
         other => panic!("Unexpected result verifying ID token claims: {:?}", other),
@@ -446,10 +448,11 @@ fn rp_id_token_sub() {
             .authorization_code
             .take()
             .expect("no authorization_code"),
+        &CurlHttpClient,
     );
 
     match token_response {
-        Err(RequestTokenError::Parse(_)) => {
+        Err(RequestTokenError::Parse(_, _)) => {
             log_error!("ID token failed to parse without `sub` claim (expected result)")
         }
         other => panic!("Unexpected result verifying ID token claims: {:?}", other),
@@ -564,7 +567,7 @@ fn rp_userinfo_sig() {
         .provider_metadata
         .userinfo_endpoint()
         .unwrap()
-        .get_user_info(test_state.access_token(), &verifier)
+        .get_user_info(test_state.access_token(), &verifier, &CurlHttpClient)
         .panic_if_fail("failed to get user info");
 
     log_debug!("UserInfo response: {:?}", user_info_claims);
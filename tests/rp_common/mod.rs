@@ -16,7 +16,7 @@ use openidconnect::core::{
 };
 use openidconnect::discovery::ProviderMetadata;
 use openidconnect::registration::ClientRegistrationRequest;
-use openidconnect::{ClientName, ContactEmail, IssuerUrl};
+use openidconnect::{ClientName, ContactEmail, CurlHttpClient, IssuerUrl};
 
 pub const CERTIFICATION_BASE_URL: &str = "https://rp.certification.openid.net:8080";
 pub const RP_CONTACT_EMAIL: &str = "ramos@cs.stanford.edu";
@@ -113,7 +113,7 @@ pub fn issuer_url(test_id: &str) -> IssuerUrl {
 
 pub fn get_provider_metadata(test_id: &str) -> CoreProviderMetadata {
     let _issuer_url = issuer_url(test_id);
-    openidconnect::discovery::get_provider_metadata(&_issuer_url).expect(&format!(
+    openidconnect::discovery::get_provider_metadata(&_issuer_url, &CurlHttpClient).expect(&format!(
         "Failed to fetch provider metadata from {:?}",
         _issuer_url
     ))
@@ -138,7 +138,7 @@ where
         .registration_endpoint()
         .expect("provider does not support dynamic registration");
     registration_request_post
-        .register(&registration_endpoint)
+        .register(&registration_endpoint, &CurlHttpClient)
         .expect(&format!(
             "Failed to register client at {:?}",
             registration_endpoint